@@ -0,0 +1,231 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{PrimeField, UniformRand};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use base64::Engine;
+use rand::{rngs::StdRng, SeedableRng};
+use serde_json::Value;
+use sha3::{Digest, Sha3_256};
+
+use crate::error::{CsdError, CsdResult};
+
+/// The multibase prefix of base64url without padding.
+const MULTIBASE_BASE64URL_PREFIX: char = 'u';
+
+/// The public parameters of an accumulator: the generators `P` of G1 and `P̃` of G2 that the
+/// accumulator and the issuer's public key are built on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsdParams<C: Pairing> {
+    pub p: C::G1Affine,
+    pub p_tilde: C::G2Affine,
+}
+
+///
+/// generate_params(param_seed: u64) -> CsdParams<C>
+///
+/// The accumulator parameters of a token, which only carries the seed they are derived from.
+/// They are drawn the way `vb_accumulator`'s `SetupParams::generate_using_rng` draws them, so
+/// issuers using it and verifiers using this agree on them.
+///
+pub fn generate_params<C: Pairing>(param_seed: u64) -> CsdParams<C> {
+
+    let mut rng = StdRng::seed_from_u64(param_seed);
+    let p = C::G1Affine::rand(&mut rng);
+    let p_tilde = C::G2Affine::rand(&mut rng);
+    CsdParams { p, p_tilde }
+
+}
+
+///
+/// scalar_from_str(string: &str) -> Fr
+///
+/// This function takes in input a string and converts it into a scalar number that can be
+/// accumulated into the accumulator by hashing using sha3_256 and treating the result as a 256 bit
+/// number
+///
+pub fn scalar_from_str<S: Pairing>(string: &str) -> S::ScalarField {
+
+    let mut hasher = Sha3_256::new();
+    hasher.update(string);
+    let result = hasher.finalize();
+
+    S::ScalarField::from_be_bytes_mod_order(result.as_slice())
+
+}
+
+///
+/// canonical_value(value: &Value) -> Value
+///
+/// `value` with the keys of every object in CBOR canonical order, i.e. shorter keys first and
+/// then bytewise, as in the deterministic encoding of RFC 8949. Accumulated claims are hashed in
+/// this form so that the JSON and CBOR encodings of a credential share the accumulator. The order
+/// only survives in a `Value` with serde_json's `preserve_order`, see [`canonical_json`].
+///
+pub fn canonical_value(value: &Value) -> Value {
+
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(&String, &Value)> = object.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| canonical_order(a, b));
+            Value::Object(entries.into_iter().map(|(key, value)| (key.to_owned(), canonical_value(value))).collect())
+        }
+        Value::Array(array) => Value::Array(array.iter().map(canonical_value).collect()),
+        value => value.clone(),
+    }
+
+}
+
+///
+/// canonical_json(value: &Value) -> String
+///
+/// The compact JSON of [`canonical_value`], with the object keys written in canonical order by
+/// hand, so that it comes out the same whether serde_json keeps insertion order or sorts keys.
+///
+pub fn canonical_json(value: &Value) -> String {
+
+    match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(&String, &Value)> = object.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| canonical_order(a, b));
+            let entries: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| format!("{}:{}", Value::String(key.to_owned()), canonical_json(value)))
+                .collect();
+            format!("{{{}}}", entries.join(","))
+        }
+        Value::Array(array) => format!("[{}]", array.iter().map(canonical_json).collect::<Vec<String>>().join(",")),
+        value => value.to_string(),
+    }
+
+}
+
+fn canonical_order(a: &str, b: &str) -> core::cmp::Ordering {
+    a.len().cmp(&b.len()).then(a.cmp(b))
+}
+
+///
+/// claim_key(name: &str, value: &Value) -> String
+///
+/// The element a claim is accumulated as, `name::value` with `value` in [`canonical_json`].
+/// Issuers and verifiers hash it with [`scalar_from_str`], so both must build it here.
+///
+pub fn claim_key(name: &str, value: &Value) -> String {
+
+    format!("{}::{}", name, canonical_json(value))
+
+}
+
+/// How accumulators, witnesses and public keys are encoded as text in tokens. Every encoding is
+/// read back by the deserializers, which tell them apart, so verifiers don't need to be told.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PointEncoding {
+    /// base64url without padding, as the rest of a JWT.
+    #[default]
+    Base64Url,
+    /// base64url after the multibase `u` prefix.
+    Multibase,
+    /// Standard base64 with padding, the encoding of tokens issued before it was configurable.
+    Base64,
+}
+
+impl PointEncoding {
+    pub const ALL: [PointEncoding; 3] = [PointEncoding::Base64Url, PointEncoding::Multibase, PointEncoding::Base64];
+
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            PointEncoding::Base64Url => base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes),
+            PointEncoding::Multibase => format!("{}{}", MULTIBASE_BASE64URL_PREFIX, base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)),
+            PointEncoding::Base64 => base64::engine::general_purpose::STANDARD.encode(bytes),
+        }
+    }
+
+    /// Decodes `encoded`, which must be exactly what [`PointEncoding::encode`] gives for the result.
+    pub fn decode(&self, encoded: &str) -> Option<Vec<u8>> {
+        let bytes = match self {
+            PointEncoding::Base64Url => base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(encoded).ok()?,
+            PointEncoding::Multibase => base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(encoded.strip_prefix(MULTIBASE_BASE64URL_PREFIX)?)
+                .ok()?,
+            PointEncoding::Base64 => base64::engine::general_purpose::STANDARD.decode(encoded).ok()?,
+        };
+        (self.encode(&bytes) == encoded).then_some(bytes)
+    }
+}
+
+///
+/// point_to_bytes(point: &T) -> Vec<u8>
+///
+/// The raw compressed serialization of an accumulator, witness or public key, for binary formats.
+///
+pub fn point_to_bytes<T: CanonicalSerialize>(point: &T) -> Vec<u8> {
+
+    let mut compressed_bytes = Vec::new();
+    point.serialize_compressed(&mut compressed_bytes).unwrap();
+    compressed_bytes
+
+}
+
+///
+/// point_from_bytes(bytes: &[u8], name: &str) -> CsdResult<T>
+///
+/// Reads back a point serialized by [`point_to_bytes`], which must span all of `bytes`.
+///
+pub fn point_from_bytes<T: CanonicalDeserialize>(mut bytes: &[u8], name: &str) -> CsdResult<T> {
+
+    let point = T::deserialize_compressed(&mut bytes)
+        .map_err(|err| CsdError::DeserializationError(format!("{name} is not a valid point: {err}")))?;
+    if !bytes.is_empty() {
+        return Err(CsdError::DeserializationError(format!("{name} has trailing bytes")));
+    }
+    Ok(point)
+
+}
+
+///
+/// decode_point_bytes(encoded: &str, name: &str) -> CsdResult<(Vec<u8>, PointEncoding)>
+///
+/// The compressed bytes of a serialized point of type `T`, along with the encoding it was found
+/// in, for formats which carry points as bytes. A multibase string may also read as a longer
+/// base64url one, so the bytes of an encoding are only taken once they make a valid `T`.
+///
+pub fn decode_point_bytes<T: CanonicalDeserialize>(encoded: &str, name: &str) -> CsdResult<(Vec<u8>, PointEncoding)> {
+
+    decode_point::<T>(encoded, name).map(|(_, bytes, encoding)| (bytes, encoding))
+
+}
+
+///
+/// deserialize_point(encoded: &str, name: &str) -> CsdResult<T>
+///
+/// Reads an accumulator, witness or public key in any [`PointEncoding`], trying each one until
+/// the bytes make a valid point. `name` says which in errors.
+///
+pub fn deserialize_point<T: CanonicalDeserialize>(encoded: &str, name: &str) -> CsdResult<T> {
+
+    decode_point(encoded, name).map(|(point, _, _)| point)
+
+}
+
+fn decode_point<T: CanonicalDeserialize>(encoded: &str, name: &str) -> CsdResult<(T, Vec<u8>, PointEncoding)> {
+
+    let mut decoded = PointEncoding::ALL
+        .into_iter()
+        .filter_map(|encoding| encoding.decode(encoded).map(|bytes| (bytes, encoding)))
+        .peekable();
+    if decoded.peek().is_none() {
+        return Err(CsdError::DeserializationError(format!("{name} is neither base64url, multibase nor base64")));
+    }
+    let mut error = None;
+    for (bytes, encoding) in decoded {
+        match point_from_bytes(&bytes, name) {
+            Ok(point) => return Ok((point, bytes, encoding)),
+            Err(err) => error = Some(err),
+        }
+    }
+    Err(error.unwrap_or(CsdError::DeserializationError(format!("{name} is not a valid point"))))
+
+}
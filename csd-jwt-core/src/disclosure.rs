@@ -0,0 +1,181 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::accumulator::claim_key;
+use crate::error::CsdError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CsdDisclosure {
+    /// The serialized accumulator witness of the claim, in place of the SD-JWT salt.
+    pub witness: Option<String>,
+    /// The SD-JWT salt of a claim whose digest is in the `_sd` array of the issuer JWT instead of
+    /// the accumulator.
+    pub salt: Option<String>,
+    /// The claim name, optional for array elements.
+    pub claim_name: Option<String>,
+    /// The claim Value which can be of any type.
+    pub claim_value: Value,
+    /// The base64url-encoded string.
+    pub disclosure: String,
+}
+
+impl CsdDisclosure {
+    /// Creates a new instance of [`::Disclosure`].
+    ///
+    /// Use `.to_string()` to get the actual disclosure.
+    pub fn new(claim_name: Option<String>, claim_value: Value) -> Self {
+        let input = if let Some(name) = &claim_name {
+            format!("[ \"{}\", {}]", &name, &claim_value.to_string())
+        } else {
+            format!("[{}]", &claim_value.to_string())
+        };
+
+        let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(input);
+        Self {
+            witness: None,
+            salt: None,
+            claim_name,
+            claim_value,
+            disclosure: encoded,
+        }
+    }
+
+    /// Creates a new instance of [`CsdDisclosure`] of the form `[<witness>, <name>, <value>]`,
+    /// i.e. an SD-JWT disclosure whose salt is replaced by the accumulator witness of `name::value`.
+    pub fn with_witness(witness: String, claim_name: String, claim_value: Value) -> Self {
+        let input = Value::Array(vec![
+            Value::String(witness.clone()),
+            Value::String(claim_name.clone()),
+            claim_value.clone(),
+        ]).to_string();
+
+        let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(input);
+        Self {
+            witness: Some(witness),
+            salt: None,
+            claim_name: Some(claim_name),
+            claim_value,
+            disclosure: encoded,
+        }
+    }
+
+    /// Creates a new instance of [`CsdDisclosure`] of the SD-JWT form `[<salt>, <name>, <value>]`,
+    /// whose [`CsdDisclosure::digest`] the issuer signs instead of accumulating `name::value`.
+    pub fn with_salt(salt: String, claim_name: String, claim_value: Value) -> Self {
+        let Self { witness, claim_name, claim_value, disclosure, .. } = Self::with_witness(salt, claim_name, claim_value);
+        Self { witness: None, salt: witness, claim_name, claim_value, disclosure }
+    }
+
+    /// Reads the first element of a parsed disclosure as a salt instead of a witness. Both forms
+    /// look alike, verifiers tell them apart by finding the [`CsdDisclosure::digest`] in `_sd`.
+    pub fn into_salted(self) -> Self {
+        match self.salt {
+            Some(_) => self,
+            None => Self { salt: self.witness, witness: None, ..self },
+        }
+    }
+
+    /// The SD-JWT digest of this disclosure, the base64url-encoded SHA-256 of its encoded form.
+    pub fn digest(&self) -> String {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(self.disclosure.as_bytes()))
+    }
+
+    /// The accumulated element this disclosure stands for, i.e. `name::value`, see [`claim_key`].
+    pub fn claim_key(&self) -> Option<String> {
+        self.claim_name
+            .as_ref()
+            .map(|name| claim_key(name, &self.claim_value))
+    }
+
+    /// Parses a Base64 encoded disclosure into a [`CsdDisclosure`]. The first element of a
+    /// `[<witness>, <name>, <value>]` disclosure is read as a witness, see
+    /// [`CsdDisclosure::into_salted`] for salted ones.
+    ///
+    /// ## Error
+    ///
+    /// Returns an [`Error::InvalidDisclosure`] if input is not a valid disclosure.
+    pub fn parse(disclosure: String) -> Result<Self, CsdError> {
+        let decoded: Vec<Value> = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(&disclosure)
+            .map_err(|_e| {
+                CsdError::InvalidDisclosure(format!(
+                    "Base64 decoding of the disclosure was not possible {}",
+                    disclosure
+                ))
+            })
+            .and_then(|data| {
+                serde_json::from_slice(&data).map_err(|_e| {
+                    CsdError::InvalidDisclosure(format!(
+                        "decoded disclosure could not be serialized as an array {}",
+                        disclosure
+                    ))
+                })
+            })?;
+
+        if decoded.len() == 2 {
+            Ok(Self {
+                witness: None,
+                salt: None,
+                claim_name: None,
+                claim_value: decoded
+                    .get(1)
+                    .ok_or(CsdError::InvalidDisclosure("invalid claim name".to_string()))?
+                    .clone(),
+                disclosure,
+            })
+        } else if decoded.len() == 3 {
+            Ok(Self {
+                witness: decoded
+                    .first()
+                    .and_then(|witness| witness.as_str())
+                    .map(|witness| witness.to_owned()),
+                salt: None,
+                claim_name: Some(
+                    decoded
+                        .get(1)
+                        .ok_or(CsdError::InvalidDisclosure("invalid claim name".to_string()))?
+                        .as_str()
+                        .ok_or(CsdError::InvalidDisclosure(
+                            "claim name could not be parsed as a string".to_string(),
+                        ))?
+                        .to_owned(),
+                ),
+                claim_value: decoded
+                    .get(2)
+                    .ok_or(CsdError::InvalidDisclosure("invalid claim name".to_string()))?
+                    .clone(),
+                disclosure,
+            })
+        } else {
+            Err(CsdError::InvalidDisclosure(format!(
+                "deserialized array has an invalid length of {}",
+                decoded.len()
+            )))
+        }
+    }
+
+    /// Reference the actual disclosure.
+    pub fn as_str(&self) -> &str {
+        &self.disclosure
+    }
+
+    /// Convert this object into the actual disclosure.
+    pub fn into_string(self) -> String {
+        self.disclosure
+    }
+}
+
+impl Display for CsdDisclosure {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.disclosure)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,18 @@
+// Copyright 2020-2023 IOTA Stiftung
+// SPDX-License-Identifier: Apache-2.0
+
+use alloc::string::String;
+
+/// Alias for a `Result` with the error type [`CsdError`].
+pub type CsdResult<T> = Result<T, CsdError>;
+
+/// The errors of the core crate. The `std` crate wraps them in its own `CsdError`, which matches
+/// them exhaustively, so this enum is deliberately not `#[non_exhaustive]`.
+#[derive(Debug, thiserror::Error, strum::IntoStaticStr)]
+pub enum CsdError {
+    #[error("invalid input: {0}")]
+    InvalidDisclosure(String),
+
+    #[error("invalid input")]
+    DeserializationError(String),
+}
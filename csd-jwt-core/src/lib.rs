@@ -0,0 +1,13 @@
+//!
+//! The `no_std` core of CSD-JWT: claim hashing, accumulator (de)serialization, witness
+//! verification and disclosure parsing, on `alloc` alone. JWS, threading and persistence live in
+//! the std-only `csd_jwt` crate, which re-exports these modules.
+//!
+#![no_std]
+
+extern crate alloc;
+
+pub mod accumulator;
+pub mod disclosure;
+pub mod error;
+pub mod witness;
@@ -0,0 +1,146 @@
+use alloc::string::String;
+
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::Zero;
+
+use crate::accumulator::{deserialize_point, scalar_from_str, CsdParams};
+use crate::disclosure::CsdDisclosure;
+use crate::error::{CsdError, CsdResult};
+
+///
+/// verify_membership(accumulator: &C::G1Affine, element: &Fr, witness: &C::G1Affine, pk: &C::G2Affine, params: &CsdParams<C>) -> bool
+///
+/// Checks that `witness` proves `element` is in `accumulator`, the value `V` of a positive
+/// accumulator with the public key `Q̃`, as e(witness, element·P̃ + Q̃) = e(V, P̃). This is the
+/// check of `vb_accumulator`, which needs std, on the bare points.
+///
+pub fn verify_membership<C: Pairing>(
+    accumulator: &C::G1Affine,
+    element: &C::ScalarField,
+    witness: &C::G1Affine,
+    pk: &C::G2Affine,
+    params: &CsdParams<C>,
+) -> bool {
+
+    let p_tilde_times_y_plus_q_tilde = params.p_tilde * *element + *pk;
+    C::multi_pairing(
+        [*witness, *accumulator],
+        [p_tilde_times_y_plus_q_tilde, -params.p_tilde.into_group()],
+    )
+    .is_zero()
+
+}
+
+///
+/// verify_witnesses(accumulator: &C::G1Affine, members: &[(Fr, C::G1Affine)], pk: &C::G2Affine, params: &CsdParams<C>) -> bool
+///
+/// Verifies the witness of every accumulated element of `members`, one after the other. The std
+/// crate spreads them across threads, each running this on its share.
+///
+pub fn verify_witnesses<C: Pairing>(
+    accumulator: &C::G1Affine,
+    members: &[(C::ScalarField, C::G1Affine)],
+    pk: &C::G2Affine,
+    params: &CsdParams<C>,
+) -> bool {
+
+    members.iter().all(|(element, witness)| verify_membership(accumulator, element, witness, pk, params))
+
+}
+
+///
+/// verify_disclosure(accumulator: &C::G1Affine, disclosure: &CsdDisclosure, pk: &C::G2Affine, params: &CsdParams<C>) -> CsdResult<bool>
+///
+/// Checks that the `name::value` claim of `disclosure` is in `accumulator`, with its witness.
+///
+/// ## Error
+/// Returns [`CsdError::InvalidDisclosure`] if the disclosure has no claim name or witness.
+///
+pub fn verify_disclosure<C: Pairing>(
+    accumulator: &C::G1Affine,
+    disclosure: &CsdDisclosure,
+    pk: &C::G2Affine,
+    params: &CsdParams<C>,
+) -> CsdResult<bool> {
+
+    let (claim_key, witness) = match (disclosure.claim_key(), &disclosure.witness) {
+        (Some(claim_key), Some(witness)) => (claim_key, witness),
+        _ => return Err(CsdError::InvalidDisclosure(String::from("the disclosure has no claim name or witness"))),
+    };
+    let witness = deserialize_point::<C::G1Affine>(witness, "witness")?;
+    Ok(verify_membership(accumulator, &scalar_from_str::<C>(&claim_key), &witness, pk, params))
+
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::String;
+    use ark_bn254::{Bn254, G1Affine, G2Affine};
+    use rand::{rngs::StdRng, SeedableRng};
+    use serde_json::json;
+    use vb_accumulator::positive::{Accumulator, PositiveAccumulator};
+    use vb_accumulator::setup::{Keypair, SetupParams};
+
+    use crate::accumulator::{deserialize_point, generate_params, point_to_bytes, scalar_from_str, PointEncoding};
+    use crate::disclosure::CsdDisclosure;
+
+    use super::verify_disclosure;
+
+    /// What a wallet holds after an issuer accumulated `claim_key` with `vb_accumulator`: the
+    /// accumulator, public key and witness of the token as text.
+    fn issued(claim_key: &str) -> (String, String, String) {
+        let params = SetupParams::<Bn254>::generate_using_rng(&mut StdRng::seed_from_u64(1));
+        let keypair = Keypair::<Bn254>::generate_using_rng(&mut StdRng::seed_from_u64(0), &params);
+        let element = scalar_from_str::<Bn254>(claim_key);
+        let accumulator = PositiveAccumulator::<Bn254>::initialize(&params);
+        let value = accumulator.compute_new_post_add(&element, &keypair.secret_key);
+        let accumulator = PositiveAccumulator::<Bn254>::from_accumulated(value);
+        let witness = accumulator.compute_membership_witness(&element, &keypair.secret_key);
+        let encoding = PointEncoding::default();
+        (
+            encoding.encode(&point_to_bytes(&accumulator)),
+            encoding.encode(&point_to_bytes(&keypair.public_key)),
+            encoding.encode(&point_to_bytes(&witness)),
+        )
+    }
+
+    #[test]
+    fn params_match_vb_accumulator() {
+        let params = generate_params::<Bn254>(1);
+        let setup = SetupParams::<Bn254>::generate_using_rng(&mut StdRng::seed_from_u64(1));
+        assert_eq!((params.p, params.p_tilde), (setup.P, setup.P_tilde));
+    }
+
+    #[test]
+    fn disclosure_round_trip() {
+        let params = generate_params::<Bn254>(1);
+        let (accumulator, pk, witness) = issued(r#"name::"Alice""#);
+        let accumulator = deserialize_point::<G1Affine>(&accumulator, "accumulator").unwrap();
+        let pk = deserialize_point::<G2Affine>(&pk, "public key").unwrap();
+
+        let issued = CsdDisclosure::with_witness(witness, "name".into(), json!("Alice"));
+        let disclosure = CsdDisclosure::parse(issued.disclosure.clone()).unwrap();
+        assert_eq!(disclosure, issued);
+        assert!(verify_disclosure(&accumulator, &disclosure, &pk, &params).unwrap());
+
+        let forged = CsdDisclosure::with_witness(disclosure.witness.clone().unwrap(), "name".into(), json!("Bob"));
+        assert!(!verify_disclosure(&accumulator, &forged, &pk, &params).unwrap());
+        assert!(!verify_disclosure(&accumulator, &disclosure, &pk, &generate_params::<Bn254>(2)).unwrap());
+        assert!(verify_disclosure(&accumulator, &CsdDisclosure::new(None, json!("Alice")), &pk, &params).is_err());
+    }
+
+    #[test]
+    fn nested_disclosure() {
+        let params = generate_params::<Bn254>(1);
+        // As issuers hash it: keys in CBOR canonical order, which isn't the order serde_json sorts in.
+        let (accumulator, pk, witness) = issued(r#"address::{"zip":"10115","street":"Main St"}"#);
+        let accumulator = deserialize_point::<G1Affine>(&accumulator, "accumulator").unwrap();
+        let pk = deserialize_point::<G2Affine>(&pk, "public key").unwrap();
+
+        let issued = CsdDisclosure::with_witness(witness, "address".into(), json!({"street": "Main St", "zip": "10115"}));
+        let disclosure = CsdDisclosure::parse(issued.disclosure.clone()).unwrap();
+        assert_eq!(disclosure.claim_key().unwrap(), r#"address::{"zip":"10115","street":"Main St"}"#);
+        assert!(verify_disclosure(&accumulator, &disclosure, &pk, &params).unwrap());
+    }
+}
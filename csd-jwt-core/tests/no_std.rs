@@ -0,0 +1,21 @@
+//!
+//! Checks that the core builds for a target without std, so that a dependency pulling std in
+//! fails the tests instead of the wallets on microcontrollers that use it.
+//!
+use std::process::Command;
+
+const TARGET: &str = "thumbv7em-none-eabihf";
+
+#[test]
+fn builds_without_std() {
+    let output = Command::new(env!("CARGO"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["build", "--lib", "--target", TARGET])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "csd-jwt-core doesn't build for {TARGET} (install it with `rustup target add {TARGET}`):\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
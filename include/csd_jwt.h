@@ -0,0 +1,221 @@
+#ifndef CSD_JWT_H
+#define CSD_JWT_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. Regenerate with `CSD_JWT_UPDATE_HEADER=1 cargo test --test c_api`. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The result of a C API call: [`CsdErrorCode::Ok`], a [`CsdError`] variant, or a misuse of the API.
+ */
+typedef enum {
+  CSD_ERROR_CODE_OK = 0,
+  CSD_ERROR_CODE_INVALID_DISCLOSURE = 1,
+  CSD_ERROR_CODE_MISSING_HASHER = 2,
+  CSD_ERROR_CODE_DATA_TYPE_MISMATCH = 3,
+  CSD_ERROR_CODE_CLAIM_COLLISION = 4,
+  CSD_ERROR_CODE_DUPLICATE_DIGEST = 5,
+  CSD_ERROR_CODE_INVALID_ARRAY_DISCLOSURE_OBJECT = 6,
+  CSD_ERROR_CODE_INVALID_PATH = 7,
+  CSD_ERROR_CODE_DESERIALIZATION = 8,
+  CSD_ERROR_CODE_UNSPECIFIED = 9,
+  CSD_ERROR_CODE_INVALID_SALT_SIZE = 10,
+  CSD_ERROR_CODE_UNUSED_DISCLOSURES = 11,
+  CSD_ERROR_CODE_ADD_BATCH = 12,
+  CSD_ERROR_CODE_WITNESS_BATCH = 13,
+  CSD_ERROR_CODE_REMOVE_BATCH = 14,
+  CSD_ERROR_CODE_VERIFICATION_FAILED = 15,
+  CSD_ERROR_CODE_JWS = 16,
+  CSD_ERROR_CODE_ALGORITHM_NOT_ALLOWED = 17,
+  CSD_ERROR_CODE_STATE_INTEGRITY = 18,
+  CSD_ERROR_CODE_DID_RESOLUTION = 19,
+  CSD_ERROR_CODE_UNTRUSTED_ISSUER = 20,
+  CSD_ERROR_CODE_CLAIM_VALIDATION = 21,
+  CSD_ERROR_CODE_KEY_BINDING = 22,
+  CSD_ERROR_CODE_UNMET_REQUIREMENT = 23,
+  /**
+   * A required pointer argument was null.
+   */
+  CSD_ERROR_CODE_NULL_POINTER = 100,
+  /**
+   * A string argument was not valid UTF-8.
+   */
+  CSD_ERROR_CODE_INVALID_UTF8 = 101,
+  /**
+   * The library panicked; the handles passed to the call must not be used again.
+   */
+  CSD_ERROR_CODE_PANIC = 102,
+} CsdErrorCode;
+
+/**
+ * The pairing curve of the accumulator.
+ */
+typedef enum {
+  CSD_CURVE_BN254 = 0,
+  CSD_CURVE_BLS12381 = 1,
+} CsdCurve;
+
+/**
+ * An issued credential, from which presentations are derived.
+ */
+typedef struct CsdCredential CsdCredential;
+
+/**
+ * A JWS key of an issuer, with the curve of its accumulator. The accumulator secret key is kept
+ * in the JWK, see [`set_jwk_accumulator_key`]. A key made from a public JWK can only verify.
+ */
+typedef struct CsdIssuerKey CsdIssuerKey;
+
+/**
+ * A presentation disclosing some of the claims of a credential.
+ */
+typedef struct CsdPresentation CsdPresentation;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The message of the last error on the calling thread, or null if there was none. The string is
+ * owned by the library and valid until the next failing call on the thread.
+ */
+const char *csd_last_error_message(void);
+
+/**
+ * Frees a string returned by the library.
+ *
+ * # Safety
+ * `string` must be null or a string returned by the library that hasn't been freed.
+ */
+void csd_string_free(char *string);
+
+/**
+ * Generates an issuer key for the JWS algorithm `alg` (ES256, ES384, ES512, EdDSA or PS256),
+ * along with a random accumulator secret key on `curve`.
+ *
+ * # Safety
+ * `alg` must be a NUL-terminated string and `out` valid for writes.
+ */
+CsdErrorCode csd_issuer_key_generate(const char *alg, CsdCurve curve, CsdIssuerKey **out);
+
+/**
+ * Reads an issuer key from a private or public JWK, whose `alg` parameter names the JWS algorithm.
+ * Only private JWKs written by [`csd_issuer_key_jwk`] hold the accumulator key needed to issue.
+ *
+ * # Safety
+ * `jwk` must be a NUL-terminated string and `out` valid for writes.
+ */
+CsdErrorCode csd_issuer_key_from_jwk(const char *jwk, CsdCurve curve, CsdIssuerKey **out);
+
+/**
+ * Writes the public JWK of `key` to `out`, to be freed with [`csd_string_free`]. It holds the
+ * accumulator public key if `key` has an accumulator secret key.
+ *
+ * # Safety
+ * `key` must be a live issuer key and `out` valid for writes.
+ */
+CsdErrorCode csd_issuer_key_public_jwk(const CsdIssuerKey *key, char **out);
+
+/**
+ * Writes the JWK of `key`, private along with its accumulator key if it is, to `out`, to be freed
+ * with [`csd_string_free`].
+ *
+ * # Safety
+ * `key` must be a live issuer key and `out` valid for writes.
+ */
+CsdErrorCode csd_issuer_key_jwk(const CsdIssuerKey *key, char **out);
+
+/**
+ * Frees an issuer key.
+ *
+ * # Safety
+ * `key` must be null or an issuer key that hasn't been freed.
+ */
+void csd_issuer_key_free(CsdIssuerKey *key);
+
+/**
+ * Issues a credential for the JSON object `claims`, with one disclosure per claim.
+ *
+ * # Safety
+ * `key` must be a live issuer key, `claims` a NUL-terminated string and `out` valid for writes.
+ */
+CsdErrorCode csd_issue(const CsdIssuerKey *key, const char *claims, CsdCredential **out);
+
+/**
+ * Reads a serialized credential.
+ *
+ * # Safety
+ * `credential` must be a NUL-terminated string and `out` valid for writes.
+ */
+CsdErrorCode csd_credential_parse(const char *credential, CsdCredential **out);
+
+/**
+ * Writes the serialized `credential` to `out`, to be freed with [`csd_string_free`].
+ *
+ * # Safety
+ * `credential` must be a live credential and `out` valid for writes.
+ */
+CsdErrorCode csd_credential_serialize(const CsdCredential *credential, char **out);
+
+/**
+ * Frees a credential.
+ *
+ * # Safety
+ * `credential` must be null or a credential that hasn't been freed.
+ */
+void csd_credential_free(CsdCredential *credential);
+
+/**
+ * Derives a presentation of `credential` disclosing the `count` claims at `pointers`, e.g. `"/name"`.
+ *
+ * # Safety
+ * `credential` must be a live credential, `pointers` an array of `count` NUL-terminated strings
+ * (or null if `count` is 0) and `out` valid for writes.
+ */
+CsdErrorCode csd_derive_presentation(const CsdCredential *credential,
+                                     const char *const *pointers,
+                                     size_t count,
+                                     CsdPresentation **out);
+
+/**
+ * Reads a serialized presentation.
+ *
+ * # Safety
+ * `presentation` must be a NUL-terminated string and `out` valid for writes.
+ */
+CsdErrorCode csd_presentation_parse(const char *presentation, CsdPresentation **out);
+
+/**
+ * Writes the serialized `presentation` to `out`, to be freed with [`csd_string_free`].
+ *
+ * # Safety
+ * `presentation` must be a live presentation and `out` valid for writes.
+ */
+CsdErrorCode csd_presentation_serialize(const CsdPresentation *presentation, char **out);
+
+/**
+ * Frees a presentation.
+ *
+ * # Safety
+ * `presentation` must be null or a presentation that hasn't been freed.
+ */
+void csd_presentation_free(CsdPresentation *presentation);
+
+/**
+ * Verifies `presentation` against the issuer `key` and writes the disclosed claims, as a JSON
+ * object, to `out`, to be freed with [`csd_string_free`].
+ *
+ * # Safety
+ * `key` must be a live issuer key, `presentation` a live presentation and `out` valid for writes.
+ */
+CsdErrorCode csd_verify(const CsdIssuerKey *key, const CsdPresentation *presentation, char **out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CSD_JWT_H */
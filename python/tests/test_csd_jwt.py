@@ -0,0 +1,85 @@
+"""Issuance, presentation and verification through the csd_jwt extension module.
+
+Build the module with `maturin develop` (or copy the `python` feature build of the library to
+`csd_jwt.so` on the path) and run `pytest python/tests`.
+"""
+import json
+
+import pytest
+
+import csd_jwt
+
+CLAIMS = {"name": "Alice", "age": 30, "address": {"country": "DE"}}
+
+
+@pytest.fixture(scope="module")
+def jwk():
+    return csd_jwt.generate_key("ES256")
+
+
+@pytest.fixture(scope="module")
+def credential(jwk):
+    return csd_jwt.Encoder(CLAIMS).issue(jwk)
+
+
+def test_keys(jwk):
+    private = json.loads(jwk)
+    public = json.loads(csd_jwt.public_key(jwk))
+    assert private["alg"] == public["alg"] == "ES256"
+    assert "d" in private and "d" not in public
+    assert "sk" in private["csd_accumulator"] and "csd_accumulator" not in public
+
+
+@pytest.mark.parametrize("curve", ["bn254", "bls12381"])
+def test_issue_present_verify(curve):
+    jwk = csd_jwt.generate_key("ES256", curve=curve)
+    credential = csd_jwt.Encoder(CLAIMS, curve=curve).issue(jwk)
+    holder = csd_jwt.Holder(credential)
+    assert sorted(holder.disclosures) == ["address", "age", "name"]
+    assert holder.claims() == CLAIMS
+
+    presentation = holder.present(["/name", "/address"])
+    decoder = csd_jwt.Decoder(curve=curve)
+    decoder.set_threads(1)
+    claims = decoder.verify(presentation, csd_jwt.public_key(jwk))
+    assert claims == {"name": "Alice", "address": {"country": "DE"}}
+
+
+def test_encoder_options(jwk):
+    encoder = csd_jwt.Encoder(json.dumps(CLAIMS))
+    encoder.set_param_seed(11)
+    encoder.set_always_disclosed(["age"])
+    holder = csd_jwt.Holder(encoder.issue(jwk))
+    assert holder.always_disclosed == ["age"]
+
+    claims = csd_jwt.Decoder().verify(holder.present(["/name"]), csd_jwt.public_key(jwk))
+    assert claims == {"name": "Alice", "age": 30}
+
+
+def test_errors(jwk, credential):
+    holder = csd_jwt.Holder(credential)
+    presentation = holder.present(["/name"])
+
+    with pytest.raises(csd_jwt.InvalidPathError):
+        holder.present(["/email"])
+    with pytest.raises(csd_jwt.AlgorithmNotAllowedError):
+        csd_jwt.generate_key("HS256")
+    with pytest.raises(csd_jwt.JwsError):
+        csd_jwt.Decoder().verify(presentation, csd_jwt.public_key(csd_jwt.generate_key()))
+    with pytest.raises(csd_jwt.DeserializationError):
+        csd_jwt.Holder("not a credential")
+    with pytest.raises(csd_jwt.DataTypeMismatchError):
+        csd_jwt.Encoder(["not", "an", "object"]).issue(jwk)
+    with pytest.raises(csd_jwt.DataTypeMismatchError):
+        csd_jwt.Encoder(CLAIMS, curve="bls12381").issue(jwk)
+    with pytest.raises(csd_jwt.UnspecifiedError):
+        csd_jwt.Encoder(CLAIMS).issue(csd_jwt.public_key(jwk))
+
+    decoder = csd_jwt.Decoder()
+    decoder.set_allowed_algorithms(["EdDSA"])
+    with pytest.raises(csd_jwt.AlgorithmNotAllowedError) as error:
+        decoder.verify(presentation, csd_jwt.public_key(jwk))
+    assert isinstance(error.value, csd_jwt.CsdError)
+
+    with pytest.raises(ValueError):
+        csd_jwt.Decoder(curve="secp256k1")
@@ -1,129 +1,249 @@
 extern crate vb_accumulator;
 
-use ark_ff::PrimeField;
 use ark_ec::pairing::Pairing;
-use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use rand::{prelude::StdRng, SeedableRng};
-use sha3::{Digest, Sha3_256};
+use base64::Engine;
+use rand::rngs::OsRng;
+use sha2::Sha256;
 use vb_accumulator::{
+    kb_universal_accumulator::KBUniversalAccumulator,
     positive::PositiveAccumulator,
     prelude::MembershipWitness,
     setup::Keypair,
     setup::PublicKey,
+    setup::SecretKey,
     setup::SetupParams,
+    setup_keyed_verification::SetupParams as KeyedSetupParams,
 };
 use vb_accumulator::positive::Accumulator;
-use base64;
-use base64::Engine;
+use csd_jwt_core::accumulator::{deserialize_point, CsdParams};
+use csd_jwt_core::witness::verify_membership;
+use crate::csd::csd_error::{CsdCoreError, CsdError, CsdResult};
 use crate::state::InMemoryState;
 
+// Hashing claims and (de)serializing points needs no std, so the core crate does it. It works on
+// the bare points, as vb_accumulator needs std; the functions below wrap them in its types.
+pub use csd_jwt_core::accumulator::{
+    canonical_json, canonical_value, claim_key, decode_point_bytes, point_from_bytes, point_to_bytes, scalar_from_str,
+    PointEncoding,
+};
 
+///
+/// generate_params(param_seed: u64) -> SetupParams<C>
+///
+/// The accumulator parameters of a token, which only carries the seed they are derived from.
+///
 pub fn generate_params<C: Pairing>(param_seed: u64) -> SetupParams<C> {
 
-    SetupParams::<C>::generate_using_rng(&mut StdRng::seed_from_u64(param_seed))
+    let params = csd_jwt_core::accumulator::generate_params::<C>(param_seed);
+    SetupParams { P: params.p, P_tilde: params.p_tilde }
 
 }
 
 ///
-/// initialize_accumulator(seed: u64) -> (SetupParams<C>, Keypair<C>, PositiveAccumulator<C>, InMemoryState<Fr>)
+/// serialize_accumulator(accumulator: PositiveAccumulator<C>) -> String
 ///
-/// This function uses a seed to initialize the accumulator and every single variable needed to add,
-/// remove, generating witnesses and verifying them including, of course, the ecc key pair.
+/// This function takes in input an accumulator and converts it into a string with the default
+/// [`PointEncoding`] of its compressed point. See [`serialize_accumulator_with`] for the others.
 ///
+pub fn serialize_accumulator<C: Pairing>(accumulator: PositiveAccumulator<C>) -> String {
 
-pub fn initialize_accumulator<C: Pairing>(key_seed: u64, param_seed: u64) -> (SetupParams<C>, Keypair<C>, PositiveAccumulator<C>, InMemoryState<C::ScalarField>) {
+    serialize_accumulator_with(accumulator, PointEncoding::default())
 
-    let params = generate_params(param_seed);
-    let keypair = Keypair::<C>::generate_using_rng(&mut StdRng::seed_from_u64(key_seed), &params);
-    let accumulator = PositiveAccumulator::initialize(&params);
-    let state: InMemoryState<C::ScalarField> = InMemoryState::new();
+}
 
-    (params, keypair, accumulator, state)
+pub fn serialize_accumulator_with<C: Pairing>(accumulator: PositiveAccumulator<C>, encoding: PointEncoding) -> String {
+
+    encoding.encode(&point_to_bytes(&accumulator))
+
+}
+
+pub fn serialize_witness<C: Pairing>(witness: MembershipWitness<C::G1Affine>) -> String {
+
+    serialize_witness_with::<C>(witness, PointEncoding::default())
+
+}
+
+pub fn serialize_witness_with<C: Pairing>(witness: MembershipWitness<C::G1Affine>, encoding: PointEncoding) -> String {
+
+    encoding.encode(&point_to_bytes(&witness))
+
+}
+
+pub fn serialize_pk<C: Pairing>(pk: PublicKey<C>) -> String {
+
+    serialize_pk_with(pk, PointEncoding::default())
+
+}
+
+pub fn serialize_pk_with<C: Pairing>(pk: PublicKey<C>, encoding: PointEncoding) -> String {
+
+    encoding.encode(&point_to_bytes(&pk))
 
 }
 
 ///
-/// scalar_from_str(string: &str) -> Fr
+/// deserialize_accumulator(coords: String) -> CsdResult<PositiveAccumulator<C>>
 ///
-/// This function takes in input a string and converts it into a scalar number that can be
-/// accumulated into the accumulator by hashing using sha3_256 and treating the result as a 256 bit
-/// number
+/// This function takes in input a string created previously through serialize_accumulator, in
+/// any [`PointEncoding`], and reads back the accumulator.
 ///
-pub fn scalar_from_str<S: Pairing>(string: &str) -> S::ScalarField {
+pub fn deserialize_accumulator<C: Pairing>(coords: String) -> CsdResult<PositiveAccumulator<C>> {
+
+    Ok(deserialize_point(&coords, "accumulator")?)
+
+}
+
+pub fn deserialize_witness<C: Pairing>(coords: String) -> CsdResult<MembershipWitness<C::G1Affine>> {
+
+    Ok(deserialize_point(&coords, "witness")?)
 
-    let mut hasher = Sha3_256::new();
-    hasher.update(string);
-    let result = hasher.finalize();
+}
+
+pub fn deserialize_pk<C: Pairing>(coords: String) -> CsdResult<PublicKey<C>> {
 
-    S::ScalarField::from_be_bytes_mod_order(&result.as_slice())
+    Ok(deserialize_point(&coords, "public key")?)
 
 }
 
 ///
-/// serialize_accumulator(accumulator: PositiveAccumulator<C>) -> String
+/// verify_witnesses(accumulator: &PositiveAccumulator<C>, members: &[(Fr, MembershipWitness<C::G1Affine>)], pk: &PublicKey<C>, params: &SetupParams<C>) -> bool
 ///
-/// This function takes in input an accumulator and converts into a string value by mapping the
-/// x and y coordinates of the point that represents the accumulator in the curve.
-/// Specifically, both the BigNum employed to map these 384 bit coordinates are converted into an
-/// array of 6 u64, parsed, concatenated with a ';' for separation.
-/// Finally, the two coordinates are concatenated with a '|' for separation.
+/// Verifies the witness of every accumulated element of `members` with the core's
+/// [`verify_membership`], one after the other.
 ///
-pub fn serialize_accumulator<C: Pairing>(accumulator: PositiveAccumulator<C>) -> String {
+pub fn verify_witnesses<C: Pairing>(
+    accumulator: &PositiveAccumulator<C>,
+    members: &[(C::ScalarField, MembershipWitness<C::G1Affine>)],
+    pk: &PublicKey<C>,
+    params: &SetupParams<C>,
+) -> bool {
 
-    let mut compressed_bytes = Vec::new();
-    accumulator.serialize_compressed(&mut compressed_bytes).unwrap();
-    base64::engine::general_purpose::STANDARD.encode(compressed_bytes)
+    let params = CsdParams::<C> { p: params.P, p_tilde: params.P_tilde };
+    members.iter().all(|(element, witness)| verify_membership(accumulator.value(), element, &witness.0, &pk.0, &params))
 
 }
 
-pub fn serialize_witness<C: Pairing>(witness: MembershipWitness<C::G1Affine>) -> String {
 
-    let mut compressed_bytes = Vec::new();
-    witness.serialize_compressed(&mut compressed_bytes).unwrap();
-    base64::engine::general_purpose::STANDARD.encode(compressed_bytes)
+///
+/// generate_secret_key() -> SecretKey<Fr>
+///
+/// This function draws a fresh accumulator secret key from the operating system. Whoever knows
+/// the key can compute a witness for any claim, so it must never be derived from public data.
+///
+pub fn generate_secret_key<C: Pairing>() -> SecretKey<C::ScalarField> {
+
+    SecretKey::new(&mut OsRng)
 
 }
 
-pub fn serialize_pk<C: Pairing>(pk: PublicKey<C>) -> String {
+///
+/// serialize_secret_key(secret_key: &SecretKey<Fr>) -> String
+///
+/// The base64url encoding of an accumulator secret key, for issuers to store it with their JWS key.
+///
+pub fn serialize_secret_key<C: Pairing>(secret_key: &SecretKey<C::ScalarField>) -> String {
 
-    let mut compressed_bytes = Vec::new();
-    pk.serialize_compressed(&mut compressed_bytes).unwrap();
-    base64::engine::general_purpose::STANDARD.encode(compressed_bytes)
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(point_to_bytes(secret_key))
 
 }
 
+///
+/// deserialize_secret_key(encoded: &str) -> CsdResult<SecretKey<Fr>>
+///
+/// Reads back a secret key serialized by [`serialize_secret_key`].
+///
+pub fn deserialize_secret_key<C: Pairing>(encoded: &str) -> CsdResult<SecretKey<C::ScalarField>> {
+
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("accumulator secret key is not base64url: {err}"))))?;
+    Ok(point_from_bytes(&bytes, "accumulator secret key")?)
+
+}
 
 ///
-/// deserialize_accumulator(coords: String) -> PositiveAccumulator<C>
+/// initialize_accumulator(secret_key: &SecretKey<Fr>, param_seed: u64) -> (SetupParams<C>, Keypair<C>, PositiveAccumulator<C>, InMemoryState<Fr>)
 ///
-/// This function takes in input a string containing the x and y coordinates of the point that
-/// represents the accumulator in the curve. The string must be created previously through
-/// serialize_accumulator.
+/// This function uses the issuer's secret key and the seed of the public parameters to initialize
+/// the accumulator and every single variable needed to add, remove, generating witnesses and
+/// verifying them including, of course, the ecc key pair.
 ///
-pub fn deserialize_accumulator<C: Pairing>(coords: String) -> PositiveAccumulator<C> {
+pub fn initialize_accumulator<C: Pairing>(secret_key: &SecretKey<C::ScalarField>, param_seed: u64) -> (SetupParams<C>, Keypair<C>, PositiveAccumulator<C>, InMemoryState<C::ScalarField>) {
 
-    let decoded = base64::engine::general_purpose::STANDARD.decode(coords).unwrap();
-    PositiveAccumulator::deserialize_compressed(&*decoded).unwrap()
+    let params = generate_params(param_seed);
+    let public_key = Keypair::<C>::public_key_from_secret_key(secret_key, &params);
+    let keypair = Keypair { secret_key: secret_key.clone(), public_key };
+    let accumulator = PositiveAccumulator::initialize(&params);
+    let state: InMemoryState<C::ScalarField> = InMemoryState::new();
+
+    (params, keypair, accumulator, state)
 
 }
 
-pub fn deserialize_witness<C: Pairing>(coords: String) -> MembershipWitness<C::G1Affine> {
+/// A keyed-verification accumulator along with the membership witnesses of its elements.
+pub type KeyedAccumulation<C> = (PositiveAccumulator<C>, Vec<MembershipWitness<<C as Pairing>::G1Affine>>);
+
+///
+/// generate_keyed_params(param_seed: u64) -> KeyedSetupParams<C::G1Affine>
+///
+/// The parameters of the keyed-verification suite: a G1 point hashed from the seed. Its witnesses
+/// are checked with the secret key alone, so there is no G2 part and no pairing to set up.
+///
+pub fn generate_keyed_params<C: Pairing>(param_seed: u64) -> KeyedSetupParams<C::G1Affine> {
+
+    KeyedSetupParams::<C::G1Affine>::new::<Sha256>(format!("CSD-JWT keyed verification {param_seed}").as_bytes())
 
-    let decoded = base64::engine::general_purpose::STANDARD.decode(coords).unwrap();
-    MembershipWitness::deserialize_compressed(&*decoded).unwrap()
+}
+
+///
+/// accumulate_keyed(secret_key: &SecretKey<Fr>, param_seed: u64, elements: &[Fr]) -> CsdResult<KeyedAccumulation<C>>
+///
+/// This function accumulates `elements` in a [`KBUniversalAccumulator`] on the keyed-verification
+/// parameters, whose domain is the elements themselves, and returns its membership accumulator
+/// along with the witness of every element.
+///
+pub fn accumulate_keyed<C: Pairing>(
+    secret_key: &SecretKey<C::ScalarField>,
+    param_seed: u64,
+    elements: &[C::ScalarField],
+) -> CsdResult<KeyedAccumulation<C>> {
+
+    let params = generate_keyed_params::<C>(param_seed);
+    let mut mem_state: InMemoryState<C::ScalarField> = InMemoryState::new();
+    let mut non_mem_state: InMemoryState<C::ScalarField> = InMemoryState::new();
+    let accumulator = KBUniversalAccumulator::<C>::initialize(&params, secret_key, elements.to_vec(), &mut non_mem_state)
+        .and_then(|accumulator| accumulator.add_batch(elements.to_vec(), secret_key, &mut mem_state, &mut non_mem_state))
+        .map_err(|err| CsdError::AddBatch(format!("{:?}", err)))?;
+    let witnesses = accumulator
+        .get_membership_witnesses_for_batch(elements, secret_key, &mem_state)
+        .map_err(|err| CsdError::WitnessBatch(format!("{:?}", err)))?;
+    Ok((accumulator.mem, witnesses.into_iter().map(|witness| witness.0.clone()).collect()))
 
 }
 
-pub fn deserialize_pk<C: Pairing>(coords: String) -> PublicKey<C> {
+///
+/// verify_state(state: &InMemoryState<Fr>, accumulator: &PositiveAccumulator<C>, ...) -> bool
+///
+/// This function recomputes the accumulated value from a (possibly restored) state and checks it
+/// against a published accumulator. Since the accumulated value is a product over the members,
+/// the insertion order of the state doesn't matter.
+///
+pub fn verify_state<C: Pairing>(
+    state: &InMemoryState<C::ScalarField>,
+    accumulator: &PositiveAccumulator<C>,
+    params: &SetupParams<C>,
+    secret_key: &SecretKey<C::ScalarField>,
+) -> bool {
 
-    let decoded = base64::engine::general_purpose::STANDARD.decode(coords).unwrap();
-    PublicKey::deserialize_compressed(&*decoded).unwrap()
+    let elements: Vec<C::ScalarField> = state.db.iter().cloned().collect();
+    let recomputed = PositiveAccumulator::<C>::initialize(params).compute_new_post_add_batch(&elements, secret_key);
+    recomputed == *accumulator.value()
 
 }
 
 
 pub fn acc_demo<C: Pairing>() -> PositiveAccumulator<C> {
-    let (params, keypair, accumulator, mut state) = initialize_accumulator::<C>(0u64, 0u64);
+    let (params, keypair, accumulator, mut state) = initialize_accumulator::<C>(&generate_secret_key::<C>(), 0u64);
     assert!(params.is_valid());
     assert!(keypair.public_key.is_valid());
 
@@ -146,13 +266,13 @@ pub fn acc_demo<C: Pairing>() -> PositiveAccumulator<C> {
     println!("Post add batch accumulator:\n{:?}\n", accumulator);
 
     let m_wit = accumulator.get_membership_witness(&elem, &keypair.secret_key, &state).unwrap();
-    let batch_wit = accumulator.get_membership_witnesses_for_batch(&batch_elem.as_slice(), &keypair.secret_key, &state).unwrap();
+    let batch_wit = accumulator.get_membership_witnesses_for_batch(batch_elem.as_slice(), &keypair.secret_key, &state).unwrap();
     assert!(accumulator.verify_membership(&elem, &m_wit, &keypair.public_key, &params));
     assert!(accumulator.verify_membership(&elem, &batch_wit[0], &keypair.public_key, &params));
 
     let coords = serialize_accumulator(accumulator.clone());
     println!("{coords}");
-    let acc2 = deserialize_accumulator::<C>(coords);
+    let acc2 = deserialize_accumulator::<C>(coords).unwrap();
     println!("Serialized accumulator:\n{:?}\n", accumulator);
     println!("Deserialized accumulator:\n{:?}\n", acc2);
 
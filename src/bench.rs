@@ -0,0 +1,258 @@
+///
+/// Benchmark harness comparing CSD-JWT against classical SD-JWT (`sd-jwt-payload`): issuance,
+/// presentation and verification time plus serialized sizes, over a grid of claim counts,
+/// disclosure ratios, curves and verification thread counts.
+///
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use clap::{Args, ValueEnum};
+use josekit::jws::{JwsHeader, ES256};
+use josekit::jwt::{self, JwtPayload};
+use sd_jwt_payload::{Disclosure, SdJwt, SdObjectDecoder, SdObjectEncoder};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use csd_jwt::csd::csd_holder::CsdHolder;
+use csd_jwt::csd::csd_issuer::CsdIssuer;
+use csd_jwt::csd::csd_jws::CsdJwsAlgorithm;
+use csd_jwt::csd::csd_verifier::CsdVerifier;
+
+use crate::cli::Curve;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Claim counts, comma separated
+    #[arg(long, value_delimiter = ',', default_value = "10,25,50,100")]
+    claims: Vec<usize>,
+    /// Fractions of the claims disclosed in the presentation, comma separated
+    #[arg(long, value_delimiter = ',', default_value = "0.5,1.0", value_parser = parse_ratio)]
+    ratios: Vec<f64>,
+    /// Accumulator curves, comma separated
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "bn254,bls12381")]
+    curves: Vec<Curve>,
+    /// Witness verification threads, comma separated; 0 verifies every witness on its own thread
+    #[arg(long, value_delimiter = ',', default_value = "0")]
+    threads: Vec<usize>,
+    /// Runs averaged per configuration
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+    #[arg(long, value_enum, default_value = "csv")]
+    format: Format,
+    /// File to write the results to (stdout if omitted)
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+/// Parses a disclosure ratio, which must lie in [0, 1].
+fn parse_ratio(ratio: &str) -> Result<f64, String> {
+    match ratio.parse::<f64>() {
+        Ok(ratio) if (0.0..=1.0).contains(&ratio) => Ok(ratio),
+        Ok(_) => Err(format!("{ratio} is not in [0, 1]")),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// One configuration of the grid, times in microseconds averaged over the iterations.
+#[derive(Debug, Serialize)]
+pub struct BenchRecord {
+    pub scheme: &'static str,
+    /// `-` for SD-JWT, which doesn't depend on a curve.
+    pub curve: &'static str,
+    pub claims: usize,
+    pub disclosed: usize,
+    /// 0 for one thread per witness; always 1 for SD-JWT.
+    pub threads: usize,
+    pub issuance_us: u128,
+    pub presentation_us: u128,
+    pub verification_us: u128,
+    pub credential_bytes: usize,
+    pub presentation_bytes: usize,
+}
+
+const CSV_HEADER: &str = "scheme,curve,claims,disclosed,threads,issuance_us,presentation_us,verification_us,credential_bytes,presentation_bytes";
+
+impl BenchRecord {
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            self.scheme, self.curve, self.claims, self.disclosed, self.threads, self.issuance_us,
+            self.presentation_us, self.verification_us, self.credential_bytes, self.presentation_bytes
+        )
+    }
+}
+
+pub fn run(args: BenchArgs) -> Result<(), Box<dyn Error>> {
+    let mut records: Vec<BenchRecord> = vec![];
+    for &n_claims in &args.claims {
+        for &ratio in &args.ratios {
+            let disclosed = ((n_claims as f64) * ratio).round() as usize;
+            for &curve in &args.curves {
+                for &threads in &args.threads {
+                    records.push(match curve {
+                        Curve::Bn254 => csd_jwt_run::<Bn254>("bn254", n_claims, disclosed, threads, args.iterations)?,
+                        Curve::Bls12381 => csd_jwt_run::<Bls12_381>("bls12381", n_claims, disclosed, threads, args.iterations)?,
+                    });
+                }
+            }
+            records.push(sd_jwt_run(n_claims, disclosed, args.iterations)?);
+        }
+    }
+
+    let output = match args.format {
+        Format::Csv => std::iter::once(CSV_HEADER.to_string())
+            .chain(records.iter().map(BenchRecord::to_csv))
+            .collect::<Vec<String>>()
+            .join("\n"),
+        Format::Json => serde_json::to_string_pretty(&records)?,
+    };
+    match args.out {
+        Some(path) => fs::write(path, format!("{}\n", output))?,
+        None => println!("{}", output),
+    }
+    Ok(())
+}
+
+///
+/// Creates a map with dummy claims
+///
+fn claims(n_claims: usize) -> Value {
+    let mut claim_map: Map<String, Value> = Map::new();
+    for i in 0..n_claims {
+        claim_map.insert(
+            format!("Claim Key {}", i),
+            Value::String(format!("Claim Value {}", i))
+        );
+    }
+    Value::from(claim_map)
+}
+
+///
+/// Pointers to the first `disclosed` dummy claims
+///
+fn pointers(disclosed: usize) -> Vec<String> {
+    (0..disclosed).map(|i| format!("/Claim Key {}", i)).collect()
+}
+
+/// Accumulates the averages of the timed phases over the iterations.
+#[derive(Default)]
+struct Timings {
+    issuance: u128,
+    presentation: u128,
+    verification: u128,
+}
+
+///
+/// Simulates CSD-JWT
+///
+fn csd_jwt_run<C: Pairing>(curve: &'static str, n_claims: usize, disclosed: usize, threads: usize, iterations: u32) -> Result<BenchRecord, Box<dyn Error>> {
+    let alg = CsdJwsAlgorithm::ES256;
+    let jwk = alg.generate_jwk()?;
+    let issuer = CsdIssuer::<C>::new(alg.signer_from_jwk(&jwk)?);
+    let mut verifier = CsdVerifier::<C>::new(alg.verifier_from_jwk(&jwk)?);
+    verifier.set_threads(if threads == 0 { None } else { Some(threads) });
+    let pointers = pointers(disclosed);
+    let pointers = pointers.iter().map(|pointer| pointer.as_str()).collect::<Vec<&str>>();
+
+    let mut timings = Timings::default();
+    let (mut credential, mut presentation) = (String::new(), String::new());
+    for _ in 0..iterations {
+        let now = Instant::now();
+        credential = issuer.issue(claims(n_claims))?.presentation();
+        timings.issuance += now.elapsed().as_micros();
+
+        let now = Instant::now();
+        presentation = CsdHolder::new(&credential)?.present(&pointers)?.presentation();
+        timings.presentation += now.elapsed().as_micros();
+
+        let now = Instant::now();
+        verifier.verify(&presentation)?;
+        timings.verification += now.elapsed().as_micros();
+    }
+
+    Ok(BenchRecord {
+        scheme: "csd-jwt",
+        curve,
+        claims: n_claims,
+        disclosed,
+        threads,
+        issuance_us: timings.issuance / iterations.max(1) as u128,
+        presentation_us: timings.presentation / iterations.max(1) as u128,
+        verification_us: timings.verification / iterations.max(1) as u128,
+        credential_bytes: credential.len(),
+        presentation_bytes: presentation.len(),
+    })
+}
+
+///
+/// Simulates classical SD-JWT, with every claim concealed
+///
+fn sd_jwt_run(n_claims: usize, disclosed: usize, iterations: u32) -> Result<BenchRecord, Box<dyn Error>> {
+    let jwk = CsdJwsAlgorithm::ES256.generate_jwk()?;
+    let signer = ES256.signer_from_jwk(&jwk)?;
+    let verifier = ES256.verifier_from_jwk(&jwk)?;
+    let disclosed_names = pointers(disclosed)
+        .into_iter()
+        .map(|pointer| pointer[1..].to_string())
+        .collect::<Vec<String>>();
+
+    let mut timings = Timings::default();
+    let (mut credential, mut presentation) = (String::new(), String::new());
+    for _ in 0..iterations {
+        let now = Instant::now();
+        let mut encoder: SdObjectEncoder = claims(n_claims).try_into()?;
+        let disclosures = (0..n_claims)
+            .map(|i| encoder.conceal(&format!("/Claim Key {}", i), None).map(|disclosure| disclosure.into_string()))
+            .collect::<Result<Vec<String>, _>>()?;
+        encoder.add_sd_alg_property();
+        let mut header = JwsHeader::new();
+        header.set_token_type("sd-jwt");
+        let payload = JwtPayload::from_map(encoder.object()?.clone())?;
+        let jwt = jwt::encode_with_signer(&payload, &header, &signer)?;
+        credential = SdJwt::new(jwt, disclosures, None).presentation();
+        timings.issuance += now.elapsed().as_micros();
+
+        let now = Instant::now();
+        let sd_jwt = SdJwt::parse(&credential)?;
+        let mut selected: Vec<String> = vec![];
+        for disclosure in sd_jwt.disclosures {
+            let parsed = Disclosure::parse(disclosure.clone())?;
+            if parsed.claim_name.is_some_and(|name| disclosed_names.contains(&name)) {
+                selected.push(disclosure);
+            }
+        }
+        presentation = SdJwt::new(sd_jwt.jwt, selected, None).presentation();
+        timings.presentation += now.elapsed().as_micros();
+
+        let now = Instant::now();
+        let sd_jwt = SdJwt::parse(&presentation)?;
+        let (payload, _header) = jwt::decode_with_verifier(&sd_jwt.jwt, &verifier)?;
+        let decoder = SdObjectDecoder::new_with_sha256();
+        decoder.decode(payload.claims_set(), &sd_jwt.disclosures)?;
+        timings.verification += now.elapsed().as_micros();
+    }
+
+    Ok(BenchRecord {
+        scheme: "sd-jwt",
+        curve: "-",
+        claims: n_claims,
+        disclosed,
+        threads: 1,
+        issuance_us: timings.issuance / iterations.max(1) as u128,
+        presentation_us: timings.presentation / iterations.max(1) as u128,
+        verification_us: timings.verification / iterations.max(1) as u128,
+        credential_bytes: credential.len(),
+        presentation_bytes: presentation.len(),
+    })
+}
@@ -0,0 +1,331 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use anyhow::anyhow;
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::{AffineRepr, CurveGroup, pairing::Pairing};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use base64::Engine;
+use dock_crypto_utils::hashing_utils::{affine_group_elem_from_try_and_incr, field_elem_from_try_and_incr};
+use josekit::{
+    JoseError,
+    jwk::Jwk,
+    jws::{JwsAlgorithm, JwsSigner, JwsVerifier},
+};
+use serde_json::Value;
+use sha3::Sha3_512;
+use vb_accumulator::setup::{PublicKey, SecretKey};
+
+/// Domain separation tag prepended to the signing input before hashing it into G1.
+const BLS_SIG_DST: &[u8] = b"CSD-JWT-BLS-SIG-";
+/// Domain separation tag prepended to the accumulator secret key to derive the signing key.
+const BLS_SIGNING_KEY_DST: &[u8] = b"CSD-JWT-BLS-SIGNING-KEY-";
+
+/// Pairing curves the issuer JWT can be BLS-signed on, so an issuer runs on the curve of its
+/// accumulator. Signatures live in G1 and public keys in G2.
+pub trait BlsCurve: Pairing {
+    /// The JWS `alg` value.
+    const JWS_ALG: &'static str;
+    /// The JWK `kty` of the public keys.
+    const KEY_TYPE: &'static str;
+    /// The JWK `crv` of the public keys.
+    const CURVE: &'static str;
+}
+
+/// The key type and curve registered for BLS12-381 G2 keys by the IETF draft on BLS key
+/// representations.
+impl BlsCurve for Bls12_381 {
+    const JWS_ALG: &'static str = "BLS12381G2";
+    const KEY_TYPE: &'static str = "OKP";
+    const CURVE: &'static str = "BLS12381G2";
+}
+
+/// BN254 keys have no registered representation, so they get a private key type.
+impl BlsCurve for Bn254 {
+    const JWS_ALG: &'static str = "BN254G2";
+    const KEY_TYPE: &'static str = "CSD-BN254";
+    const CURVE: &'static str = "BN254G2";
+}
+
+///
+/// is_bls_jwk(jwk: &Jwk) -> bool
+///
+/// Whether `jwk` holds a G2 point of one of the [`BlsCurve`]s, i.e. a BLS signing key or an
+/// accumulator key.
+///
+pub fn is_bls_jwk(jwk: &Jwk) -> bool {
+    let is = |key_type: &str, curve: &str| jwk.key_type() == key_type && jwk.curve() == Some(curve);
+    is(Bls12_381::KEY_TYPE, Bls12_381::CURVE) || is(Bn254::KEY_TYPE, Bn254::CURVE)
+}
+
+///
+/// signing_key(accumulator_key: &SecretKey<Fr>) -> SecretKey<Fr>
+///
+/// This function derives the BLS signing key of an issuer from its accumulator secret key, hashing
+/// it with a domain separation tag. The two keys are unrelated to anyone without the accumulator
+/// key, so signatures never reveal anything about the accumulator and vice versa.
+///
+pub fn signing_key<C: Pairing>(accumulator_key: &SecretKey<C::ScalarField>) -> SecretKey<C::ScalarField> {
+    let mut bytes = BLS_SIGNING_KEY_DST.to_vec();
+    accumulator_key.0.serialize_compressed(&mut bytes).unwrap();
+    SecretKey(field_elem_from_try_and_incr::<C::ScalarField, Sha3_512>(&bytes))
+}
+
+///
+/// hash_to_g1(message: &[u8]) -> C::G1Affine
+///
+/// This function maps the JWS signing input to a G1 point by try-and-increment. The signing input
+/// of a JWS is public, so the timing side channel of try-and-increment doesn't leak anything.
+///
+fn hash_to_g1<C: Pairing>(message: &[u8]) -> C::G1Affine {
+    affine_group_elem_from_try_and_incr::<C::G1Affine, Sha3_512>(&[BLS_SIG_DST, message].concat())
+}
+
+/// BLS signature algorithm over the curve `C`.
+pub struct BlsJwsAlgorithm<C: BlsCurve> {
+    phantom: PhantomData<C>,
+}
+
+impl<C: BlsCurve> BlsJwsAlgorithm<C> {
+    pub fn new() -> Self {
+        BlsJwsAlgorithm::<C> { phantom: Default::default() }
+    }
+
+    /// Returns a signer using the signing key derived from the accumulator `secret_key`, see
+    /// [`signing_key`].
+    pub fn signer_from_accumulator_key(&self, secret_key: &SecretKey<C::ScalarField>) -> BlsJwsSigner<C> {
+        BlsJwsSigner { algorithm: BlsJwsAlgorithm::new(), secret_key: signing_key::<C>(secret_key), key_id: None }
+    }
+
+    /// The public key of the signer returned by [`BlsJwsAlgorithm::signer_from_accumulator_key`].
+    pub fn public_key_from_accumulator_key(&self, secret_key: &SecretKey<C::ScalarField>) -> PublicKey<C> {
+        PublicKey((C::G2Affine::generator() * signing_key::<C>(secret_key).0).into_affine())
+    }
+
+    /// Returns a verifier for the signing `public_key`.
+    pub fn verifier_from_public_key(&self, public_key: PublicKey<C>) -> BlsJwsVerifier<C> {
+        BlsJwsVerifier { algorithm: BlsJwsAlgorithm::new(), public_key, key_id: None }
+    }
+
+    /// Converts the signing `public_key` into a JWK of key type [`BlsCurve::KEY_TYPE`] and curve
+    /// [`BlsCurve::CURVE`], with the base64url-encoded compressed point as `x`.
+    pub fn to_jwk(&self, public_key: &PublicKey<C>) -> Jwk {
+        point_jwk::<C>(public_key, C::JWS_ALG)
+    }
+
+    /// Returns a verifier for a JWK produced by [`BlsJwsAlgorithm::to_jwk`].
+    pub fn verifier_from_jwk(&self, jwk: &Jwk) -> Result<BlsJwsVerifier<C>, JoseError> {
+        if jwk.key_type() != C::KEY_TYPE {
+            return Err(JoseError::InvalidKeyFormat(anyhow!("key type must be {}: {}", C::KEY_TYPE, jwk.key_type())));
+        }
+        if jwk.curve() != Some(C::CURVE) {
+            return Err(JoseError::InvalidKeyFormat(anyhow!("curve must be {}: {:?}", C::CURVE, jwk.curve())));
+        }
+        // Accumulator keys have the same form but another algorithm, and never verify JWTs.
+        if jwk.algorithm().is_some_and(|alg| alg != C::JWS_ALG) {
+            return Err(JoseError::InvalidKeyFormat(anyhow!("algorithm must be {}: {:?}", C::JWS_ALG, jwk.algorithm())));
+        }
+        let x = match jwk.parameter("x") {
+            Some(Value::String(x)) => base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(x)
+                .map_err(|err| JoseError::InvalidKeyFormat(anyhow!(err)))?,
+            _ => return Err(JoseError::InvalidKeyFormat(anyhow!("missing x parameter"))),
+        };
+        let public_key = PublicKey::<C>::deserialize_compressed(&*x)
+            .map_err(|err| JoseError::InvalidKeyFormat(anyhow!("{:?}", err)))?;
+
+        let mut verifier = self.verifier_from_public_key(public_key);
+        if let Some(key_id) = jwk.key_id() {
+            verifier.set_key_id(key_id);
+        }
+        Ok(verifier)
+    }
+}
+
+///
+/// point_jwk(public_key: &PublicKey<C>, alg: &str) -> Jwk
+///
+/// The JWK of a G2 `public_key` of the curve `C` with the algorithm `alg`, the JWS one for signing
+/// keys and the `_sd_alg` for accumulator keys.
+///
+pub fn point_jwk<C: BlsCurve>(public_key: &PublicKey<C>, alg: &str) -> Jwk {
+    let mut compressed_bytes = Vec::new();
+    public_key.serialize_compressed(&mut compressed_bytes).unwrap();
+
+    let mut jwk = Jwk::new(C::KEY_TYPE);
+    jwk.set_curve(C::CURVE);
+    jwk.set_algorithm(alg);
+    jwk.set_parameter("x", Some(Value::String(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(compressed_bytes))))
+        .unwrap();
+    jwk
+}
+
+impl<C: BlsCurve> Default for BlsJwsAlgorithm<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: BlsCurve> Clone for BlsJwsAlgorithm<C> {
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+impl<C: BlsCurve> Debug for BlsJwsAlgorithm<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(C::JWS_ALG)
+    }
+}
+
+impl<C: BlsCurve> JwsAlgorithm for BlsJwsAlgorithm<C> {
+    fn name(&self) -> &str {
+        C::JWS_ALG
+    }
+
+    fn box_clone(&self) -> Box<dyn JwsAlgorithm> {
+        Box::new(self.clone())
+    }
+}
+
+/// Signs JWS signing inputs as `sk * H(input)` in G1.
+#[derive(Clone)]
+pub struct BlsJwsSigner<C: BlsCurve> {
+    algorithm: BlsJwsAlgorithm<C>,
+    secret_key: SecretKey<C::ScalarField>,
+    key_id: Option<String>,
+}
+
+impl<C: BlsCurve> BlsJwsSigner<C> {
+    pub fn set_key_id(&mut self, value: impl Into<String>) {
+        self.key_id = Some(value.into());
+    }
+}
+
+impl<C: BlsCurve> Debug for BlsJwsSigner<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The secret key is deliberately left out.
+        f.debug_struct("BlsJwsSigner")
+            .field("algorithm", &self.algorithm)
+            .field("key_id", &self.key_id)
+            .finish()
+    }
+}
+
+impl<C: BlsCurve> JwsSigner for BlsJwsSigner<C> {
+    fn algorithm(&self) -> &dyn JwsAlgorithm {
+        &self.algorithm
+    }
+
+    fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    fn signature_len(&self) -> usize {
+        C::G1Affine::generator().compressed_size()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, JoseError> {
+        let signature = (hash_to_g1::<C>(message) * self.secret_key.0).into_affine();
+
+        let mut compressed_bytes = Vec::new();
+        signature.serialize_compressed(&mut compressed_bytes)
+            .map_err(|err| JoseError::InvalidSignature(anyhow!("{:?}", err)))?;
+        Ok(compressed_bytes)
+    }
+
+    fn box_clone(&self) -> Box<dyn JwsSigner> {
+        Box::new(self.clone())
+    }
+}
+
+/// Checks `e(signature, g2) = e(H(input), pk)`.
+#[derive(Debug, Clone)]
+pub struct BlsJwsVerifier<C: BlsCurve> {
+    algorithm: BlsJwsAlgorithm<C>,
+    public_key: PublicKey<C>,
+    key_id: Option<String>,
+}
+
+impl<C: BlsCurve> BlsJwsVerifier<C> {
+    pub fn set_key_id(&mut self, value: impl Into<String>) {
+        self.key_id = Some(value.into());
+    }
+}
+
+impl<C: BlsCurve> JwsVerifier for BlsJwsVerifier<C> {
+    fn algorithm(&self) -> &dyn JwsAlgorithm {
+        &self.algorithm
+    }
+
+    fn key_id(&self) -> Option<&str> {
+        self.key_id.as_deref()
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), JoseError> {
+        let signature = C::G1Affine::deserialize_compressed(signature)
+            .map_err(|err| JoseError::InvalidSignature(anyhow!("{:?}", err)))?;
+
+        if C::pairing(signature, C::G2Affine::generator()) != C::pairing(hash_to_g1::<C>(message), self.public_key.0) {
+            return Err(JoseError::InvalidSignature(anyhow!("BLS signature verification failed")));
+        }
+        Ok(())
+    }
+
+    fn box_clone(&self) -> Box<dyn JwsVerifier> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use josekit::{jws::JwsHeader, jwt, jwt::JwtPayload};
+
+    use crate::accumulator::{generate_secret_key, initialize_accumulator};
+
+    use super::{is_bls_jwk, point_jwk, BlsCurve, BlsJwsAlgorithm};
+
+    fn round_trip<C: BlsCurve>() {
+        let secret_key = generate_secret_key::<C>();
+        let algorithm = BlsJwsAlgorithm::<C>::new();
+        let signer = algorithm.signer_from_accumulator_key(&secret_key);
+
+        let mut payload = JwtPayload::new();
+        payload.set_issuer("did:example:issuer");
+        let jwt = jwt::encode_with_signer(&payload, &JwsHeader::new(), &signer).unwrap();
+
+        let public_key = algorithm.public_key_from_accumulator_key(&secret_key);
+        let jwk = algorithm.to_jwk(&public_key);
+        assert!(is_bls_jwk(&jwk));
+        assert_eq!((jwk.key_type(), jwk.curve()), (C::KEY_TYPE, Some(C::CURVE)));
+        let verifier = algorithm.verifier_from_jwk(&jwk).unwrap();
+        let (decoded, header) = jwt::decode_with_verifier(&jwt, &verifier).unwrap();
+        assert_eq!(decoded.issuer(), Some("did:example:issuer"));
+        assert_eq!(header.algorithm(), Some(C::JWS_ALG));
+
+        // The signing key isn't the accumulator key, whose JWK can't verify JWTs.
+        let (_, keypair, _, _) = initialize_accumulator::<C>(&secret_key, 1u64);
+        assert_ne!(keypair.public_key, public_key);
+        let accumulator_jwk = point_jwk::<C>(&keypair.public_key, std::any::type_name::<C>());
+        assert!(algorithm.verifier_from_jwk(&accumulator_jwk).is_err());
+        let verifier = algorithm.verifier_from_public_key(keypair.public_key.clone());
+        assert!(jwt::decode_with_verifier(&jwt, &verifier).is_err());
+
+        let other = algorithm.public_key_from_accumulator_key(&generate_secret_key::<C>());
+        let verifier = algorithm.verifier_from_public_key(other);
+        assert!(jwt::decode_with_verifier(&jwt, &verifier).is_err());
+    }
+
+    #[test]
+    fn bls12_381() {
+        round_trip::<Bls12_381>();
+    }
+
+    #[test]
+    fn bn254() {
+        round_trip::<Bn254>();
+    }
+}
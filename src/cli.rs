@@ -0,0 +1,383 @@
+///
+/// Subcommands of the `CSD-JWT` binary, so credentials can be issued, presented, verified and
+/// inspected from the shell.
+///
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use base64::Engine;
+use clap::{Args, Subcommand, ValueEnum};
+use josekit::jwk::Jwk;
+use serde_json::{json, Value};
+
+use csd_jwt::csd::csd_compact::{from_compact_text, to_compact_text, COMPACT_TEXT_PREFIX};
+use csd_jwt::csd::csd_did::{CsdDidResolver, DidWebResolver};
+use csd_jwt::csd::csd_disclosure::CsdDisclosure;
+use csd_jwt::csd::csd_encoder::SD_DIGESTS_KEY;
+use csd_jwt::csd::csd_error::{CsdCoreError, CsdError};
+use csd_jwt::csd::csd_holder::CsdHolder;
+use csd_jwt::csd::csd_issuance::{CsdIssuancePolicy, CsdSelectivity};
+use csd_jwt::csd::csd_issuer::{jwk_accumulator_key, jwk_accumulator_public_key, set_jwk_accumulator_key, CsdIssuer};
+use csd_jwt::csd::csd_jws::CsdJwsAlgorithm;
+use csd_jwt::csd::csd_jwt::CsdJwt;
+use csd_jwt::csd::csd_registry::CsdIssuerRegistry;
+use csd_jwt::csd::csd_requirements::CsdPresentationRequirements;
+use csd_jwt::csd::csd_size::compare;
+use csd_jwt::csd::csd_vc::{CsdProfile, CsdTypeMetadataStore};
+use csd_jwt::csd::csd_verifier::CsdVerifier;
+
+use crate::bench::BenchArgs;
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Generate an issuer JWS key pair and accumulator key as JWKs
+    Keygen(KeygenArgs),
+    /// Issue a credential for the claims of a JSON file
+    Issue(IssueArgs),
+    /// Derive a presentation disclosing only the selected claims
+    Present(PresentArgs),
+    /// Verify a presentation against the issuer public key and print the disclosed claims
+    Verify(VerifyArgs),
+    /// Pretty-print the header, payload and disclosures of a token without verifying it
+    Inspect(InspectArgs),
+    /// Break a presentation down into bytes per part and compare it with the equivalent SD-JWT
+    Size(SizeArgs),
+    /// Benchmark CSD-JWT against sd-jwt-payload and print CSV or JSON results
+    Bench(BenchArgs),
+}
+
+/// The pairing curve of the accumulator.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Curve {
+    Bn254,
+    Bls12381,
+}
+
+#[derive(Args)]
+pub struct KeygenArgs {
+    /// JWS algorithm: ES256, ES384, ES512, EdDSA or PS256
+    #[arg(long, default_value = "ES256")]
+    alg: String,
+    /// Curve of the accumulator key stored with the JWS key
+    #[arg(long, value_enum, default_value = "bn254")]
+    curve: Curve,
+    /// File to write the private JWK to (stdout if omitted)
+    #[arg(long)]
+    out: Option<PathBuf>,
+    /// File to write the public JWK to (stdout if omitted)
+    #[arg(long)]
+    public_out: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct IssueArgs {
+    /// Private issuer JWK with its accumulator key, as written by `keygen`
+    #[arg(long)]
+    key: PathBuf,
+    /// JSON object holding the claims, `-` for stdin
+    #[arg(long)]
+    claims: PathBuf,
+    #[arg(long, value_enum, default_value = "bn254")]
+    curve: Curve,
+    /// Issuer DID, set as the `iss` claim
+    #[arg(long)]
+    iss: Option<String>,
+    /// DID URL of the signing key, set as the `kid` header
+    #[arg(long, requires = "iss")]
+    kid: Option<String>,
+    /// Leave the accumulator public key out of the token, for verifiers resolving it from the issuer DID or registry
+    #[arg(long, requires = "iss")]
+    omit_pk: bool,
+    /// Top-level claim to keep in plain in the signed payload, disclosed in every presentation; can be repeated
+    #[arg(long = "always-disclose")]
+    always_disclose: Vec<String>,
+    /// Top-level claim to disclose with a salted SD-JWT digest instead of the accumulator; can be repeated
+    #[arg(long = "salted")]
+    salted: Vec<String>,
+    /// Credential type, set as the `vct` claim
+    #[arg(long)]
+    vct: Option<String>,
+    /// Issue an SD-JWT VC, typed `vc+csd-jwt`, which requires `--iss` and `--vct`
+    #[arg(long, requires_all = ["iss", "vct"])]
+    sd_jwt_vc: bool,
+    /// File to write the credential to (stdout if omitted)
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct PresentArgs {
+    /// Credential issued by `issue`, `-` for stdin
+    #[arg(long)]
+    credential: PathBuf,
+    /// JSON pointer of a claim to disclose, e.g. `/name`; can be repeated
+    #[arg(long = "pointer", short = 'p', conflicts_with = "requirements")]
+    pointers: Vec<String>,
+    /// Presentation definition or DCQL query to select the claims to disclose with
+    #[arg(long)]
+    requirements: Option<PathBuf>,
+    /// Write the presentation in the deflated Base45 compact form, to fit in a QR code
+    #[arg(long)]
+    qr: bool,
+    /// File to write the presentation to (stdout if omitted)
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Public issuer JWK with its accumulator key, as written by `keygen`; if omitted, the issuer keys are resolved from its DID
+    #[arg(long)]
+    key: Option<PathBuf>,
+    /// JSON object mapping did:web document URLs to local files
+    #[arg(long, conflicts_with = "key")]
+    did_web_map: Option<PathBuf>,
+    /// JSON registry of trusted issuers; presentations of other issuers are rejected
+    #[arg(long)]
+    trusted_issuers: Option<PathBuf>,
+    /// Only accept SD-JWT VCs
+    #[arg(long)]
+    sd_jwt_vc: bool,
+    /// JSON array of SD-JWT VC type metadata; presentations of other types are rejected
+    #[arg(long, requires = "sd_jwt_vc")]
+    type_metadata: Option<PathBuf>,
+    /// Presentation definition or DCQL query the presentation must satisfy
+    #[arg(long)]
+    requirements: Option<PathBuf>,
+    /// Presentation to verify, in the JWT or Base45 compact form, `-` for stdin
+    #[arg(long)]
+    presentation: PathBuf,
+    #[arg(long, value_enum, default_value = "bn254")]
+    curve: Curve,
+}
+
+#[derive(Args)]
+pub struct InspectArgs {
+    /// Credential or presentation, `-` for stdin
+    token: PathBuf,
+}
+
+#[derive(Args)]
+pub struct SizeArgs {
+    /// Credential the presentation was derived from
+    #[arg(long)]
+    credential: PathBuf,
+    /// Presentation to analyze, `-` for stdin
+    #[arg(long)]
+    presentation: PathBuf,
+}
+
+pub fn keygen(args: KeygenArgs) -> Result<(), Box<dyn Error>> {
+    match args.curve {
+        Curve::Bn254 => keygen_on::<Bn254>(args),
+        Curve::Bls12381 => keygen_on::<Bls12_381>(args),
+    }
+}
+
+fn keygen_on<C: Pairing>(args: KeygenArgs) -> Result<(), Box<dyn Error>> {
+    let alg = CsdJwsAlgorithm::from_str(&args.alg)?;
+    let mut jwk = alg.generate_jwk()?;
+    let mut public_jwk = jwk.to_public_key()?;
+    public_jwk.set_algorithm(alg.name());
+    // A fresh accumulator key, so that nobody but the issuer can witness claims.
+    let issuer = CsdIssuer::<C>::new(alg.signer_from_jwk(&jwk)?);
+    set_jwk_accumulator_key(&mut jwk, &issuer, true)?;
+    set_jwk_accumulator_key(&mut public_jwk, &issuer, false)?;
+
+    write_output(args.out.as_deref(), &jwk.to_string())?;
+    write_output(args.public_out.as_deref(), &public_jwk.to_string())
+}
+
+pub fn issue(args: IssueArgs) -> Result<(), Box<dyn Error>> {
+    match args.curve {
+        Curve::Bn254 => issue_on::<Bn254>(args),
+        Curve::Bls12381 => issue_on::<Bls12_381>(args),
+    }
+}
+
+fn issue_on<C: Pairing>(args: IssueArgs) -> Result<(), Box<dyn Error>> {
+    let jwk = read_jwk(&args.key)?;
+    let alg = jwk_algorithm(&jwk)?;
+    let claims: Value = serde_json::from_str(&read_input(&args.claims)?)?;
+
+    let secret_key = jwk_accumulator_key::<C>(&jwk)?
+        .ok_or(CsdError::Unspecified(String::from("the issuer JWK has no accumulator key, generate one with keygen")))?;
+
+    let mut issuer = CsdIssuer::<C>::new(alg.signer_from_jwk(&jwk)?);
+    issuer.set_accumulator_secret_key(secret_key);
+    let mut issuance_policy = CsdIssuancePolicy::new();
+    for name in args.always_disclose {
+        issuance_policy.set(name, CsdSelectivity::NeverSelective);
+    }
+    issuer.set_issuance_policy(issuance_policy);
+    issuer.set_salted_claims(args.salted);
+    if let Some(iss) = args.iss {
+        issuer.set_issuer(iss, args.kid);
+    }
+    issuer.set_omit_accumulator_key(args.omit_pk);
+    if let Some(vct) = args.vct {
+        issuer.set_credential_type(vct);
+    }
+    if args.sd_jwt_vc {
+        issuer.set_profile(CsdProfile::SdJwtVc);
+    }
+    let credential = issuer.issue(claims)?;
+    write_output(args.out.as_deref(), &credential.presentation())
+}
+
+pub fn present(args: PresentArgs) -> Result<(), Box<dyn Error>> {
+    let holder = CsdHolder::new(&read_input(&args.credential)?)?;
+    let presentation = match &args.requirements {
+        Some(requirements) => holder.present_for(&read_requirements(requirements)?)?,
+        None => holder.present(&args.pointers.iter().map(|pointer| pointer.as_str()).collect::<Vec<&str>>())?,
+    };
+    let presentation = match args.qr {
+        true => to_compact_text(&presentation)?,
+        false => presentation.presentation(),
+    };
+    write_output(args.out.as_deref(), &presentation)
+}
+
+pub fn verify(args: VerifyArgs) -> Result<(), Box<dyn Error>> {
+    match args.curve {
+        Curve::Bn254 => verify_on::<Bn254>(args),
+        Curve::Bls12381 => verify_on::<Bls12_381>(args),
+    }
+}
+
+fn verify_on<C: Pairing>(args: VerifyArgs) -> Result<(), Box<dyn Error>> {
+    let mut verifier = match &args.key {
+        Some(key) => {
+            let jwk = read_jwk(key)?;
+            let (public_key, param_seed) = jwk_accumulator_public_key::<C>(&jwk)?
+                .ok_or(CsdError::Unspecified(String::from("the issuer JWK has no accumulator key, generate one with keygen")))?;
+            let mut verifier = CsdVerifier::<C>::new(jwk_algorithm(&jwk)?.verifier_from_jwk(&jwk)?);
+            verifier.set_accumulator_key(public_key, param_seed);
+            verifier
+        }
+        None => {
+            let mut resolver = CsdDidResolver::new();
+            if let Some(map) = &args.did_web_map {
+                resolver.set_web_resolver(DidWebResolver::from_file_map(map)?);
+            }
+            CsdVerifier::<C>::with_resolver(Box::new(resolver))
+        }
+    };
+    if let Some(registry) = &args.trusted_issuers {
+        let registry: Value = serde_json::from_str(&read_input(registry)?)?;
+        verifier.set_registry(CsdIssuerRegistry::<C>::from_json(&registry)?);
+    }
+    if args.sd_jwt_vc {
+        verifier.set_profile(CsdProfile::SdJwtVc);
+    }
+    if let Some(type_metadata) = &args.type_metadata {
+        let type_metadata: Value = serde_json::from_str(&read_input(type_metadata)?)?;
+        verifier.set_type_metadata(CsdTypeMetadataStore::from_json(&type_metadata)?);
+    }
+    if let Some(requirements) = &args.requirements {
+        verifier.set_requirements(read_requirements(requirements)?);
+    }
+    let mut presentation = read_input(&args.presentation)?;
+    if presentation.trim_start().starts_with(COMPACT_TEXT_PREFIX) {
+        presentation = from_compact_text(&presentation)?.presentation();
+    }
+    let claims = verifier.verify(&presentation)?;
+    println!("{}", serde_json::to_string_pretty(&claims)?);
+    Ok(())
+}
+
+pub fn inspect(args: InspectArgs) -> Result<(), Box<dyn Error>> {
+    let csd_jwt = CsdJwt::parse(&read_input(&args.token)?)?;
+
+    let mut segments = csd_jwt.jwt.split('.');
+    let header = decode_segment(segments.next())?;
+    let payload = decode_segment(segments.next())?;
+    let disclosures = csd_jwt.disclosures
+        .iter()
+        .map(|disclosure| {
+            let disclosure = CsdDisclosure::parse(disclosure.to_owned())?;
+            let digest = Value::String(disclosure.digest());
+            if payload[SD_DIGESTS_KEY].as_array().is_some_and(|digests| digests.contains(&digest)) {
+                return Ok(json!({
+                    "name": disclosure.claim_name,
+                    "value": disclosure.claim_value,
+                    "salt": disclosure.into_salted().salt,
+                }));
+            }
+            Ok(json!({
+                "name": disclosure.claim_name,
+                "value": disclosure.claim_value,
+                "witness": disclosure.witness,
+            }))
+        })
+        .collect::<Result<Vec<Value>, CsdError>>()?;
+
+    let token = json!({
+        "header": header,
+        "payload": payload,
+        "disclosures": disclosures,
+        "key_binding_jwt": csd_jwt.key_binding_jwt,
+    });
+    println!("{}", serde_json::to_string_pretty(&token)?);
+    Ok(())
+}
+
+pub fn size(args: SizeArgs) -> Result<(), Box<dyn Error>> {
+    let comparison = compare(&read_input(&args.credential)?, &read_input(&args.presentation)?)?;
+    let report = json!({
+        "csd_jwt": comparison.csd_jwt,
+        "sd_jwt": comparison.sd_jwt,
+        "ratio": comparison.ratio(),
+    });
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+/// Decodes a base64url JWT segment holding a JSON object.
+fn decode_segment(segment: Option<&str>) -> Result<Value, Box<dyn Error>> {
+    let segment = segment.ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("JWT has less than 2 segments"))))?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(segment)?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+fn read_jwk(path: &Path) -> Result<Jwk, Box<dyn Error>> {
+    Ok(Jwk::from_bytes(read_input(path)?.as_bytes())?)
+}
+
+fn read_requirements(path: &Path) -> Result<CsdPresentationRequirements, Box<dyn Error>> {
+    Ok(CsdPresentationRequirements::from_json(&serde_json::from_str(&read_input(path)?)?)?)
+}
+
+/// The JWS algorithm of a key generated by `keygen` is stored in its `alg` parameter.
+fn jwk_algorithm(jwk: &Jwk) -> Result<CsdJwsAlgorithm, Box<dyn Error>> {
+    let alg = jwk
+        .algorithm()
+        .ok_or(CsdError::Jws(String::from("the JWK has no alg parameter")))?;
+    Ok(CsdJwsAlgorithm::from_str(alg)?)
+}
+
+/// Reads a whole file, or stdin if `path` is `-`, without surrounding whitespace.
+fn read_input(path: &Path) -> Result<String, Box<dyn Error>> {
+    let input = if path == Path::new("-") {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        input
+    } else {
+        fs::read_to_string(path)?
+    };
+    Ok(input.trim().to_string())
+}
+
+fn write_output(path: Option<&Path>, output: &str) -> Result<(), Box<dyn Error>> {
+    match path {
+        Some(path) => fs::write(path, format!("{}\n", output))?,
+        None => println!("{}", output),
+    }
+    Ok(())
+}
@@ -1,5 +1,30 @@
 pub mod csd_jwt;
 pub mod csd_decoder;
+pub mod csd_keyed_decoder;
 pub mod csd_encoder;
 pub mod csd_disclosure;
-pub mod csd_error;
\ No newline at end of file
+pub mod csd_error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod csd_issuer;
+pub mod csd_holder;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod csd_verifier;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod csd_jws;
+pub mod csd_jwk_verifier;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod csd_did;
+pub mod csd_registry;
+pub mod csd_policy;
+pub mod csd_issuance;
+pub mod csd_requirements;
+pub mod csd_vc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod csd_vcdm;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod csd_data_integrity;
+pub mod csd_cwt;
+pub mod csd_compact;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod csd_kb_jwt;
+pub mod csd_size;
\ No newline at end of file
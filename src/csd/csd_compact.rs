@@ -0,0 +1,413 @@
+use std::io::{Read, Write};
+
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::{Map, Value};
+
+use crate::accumulator::PointEncoding;
+use crate::csd;
+use csd::csd_decoder::salted_digests;
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_encoder::{ACCUMULATOR_KEY, PK_KEY};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+use csd::csd_jwt::CsdJwt;
+
+/// Version byte of the compact format. Version 1, which had no salted disclosures, is still read.
+pub const COMPACT_VERSION: u8 = 2;
+const UNSALTED_COMPACT_VERSION: u8 = 1;
+/// Prefix of the Base45 text form, as `HC1:` for EU DCCs.
+pub const COMPACT_TEXT_PREFIX: &str = "CSD1:";
+const DEFLATE_FLAG: u8 = 0x01;
+/// The flag bits holding the index of the [`PointEncoding`] of the points in [`PointEncoding::ALL`].
+const POINT_ENCODING_SHIFT: u8 = 1;
+const POINT_ENCODING_MASK: u8 = 0x03;
+
+/// Claim names encoded as their index instead of in full. Names out of the dictionary are listed
+/// in the presentation and indexed after it. Only ever append to it, the indexes are the format.
+pub const CLAIM_DICTIONARY: [&str; 36] = [
+    "name", "given_name", "family_name", "middle_name", "nickname", "email", "email_verified",
+    "phone_number", "phone_number_verified", "address", "birthdate", "gender", "age", "nationality",
+    "nationalities", "country", "locality", "region", "postal_code", "street_address", "picture",
+    "website", "locale", "zoneinfo", "updated_at", "age_over_18", "age_over_21", "document_number",
+    "issuing_country", "issuing_authority", "issue_date", "expiry_date", "place_of_birth", "sub",
+    "degree", "student_id",
+];
+
+///
+/// to_compact(csd_jwt: &CsdJwt, deflate: bool) -> CsdResult<Vec<u8>>
+///
+/// Packs a CSD-JWT presentation into the compact binary format: a version byte, a flags byte and
+/// the varint length-prefixed JWT header, payload without accumulator and public key, accumulator,
+/// public key and signature as raw bytes, followed by the disclosures as raw witness (or salt),
+/// claim index in [`CLAIM_DICTIONARY`] (or in the names listed after it) shifted left by one with
+/// the salt tag in the low bit, and value, and the KB-JWT. Salted disclosures are the ones whose
+/// digest is in the `_sd` array of the payload. The flags also record the [`PointEncoding`] the
+/// points are restored in. With `deflate`, everything after the flags byte is DEFLATE-compressed.
+///
+/// [`from_compact`] restores the presentation byte for byte, so the issuer signature and KB-JWT
+/// still verify.
+///
+/// ## Error
+/// Returns [`CsdCoreError::DeserializationError`] if `csd_jwt` is not a well-formed CSD-JWT.
+///
+pub fn to_compact(csd_jwt: &CsdJwt, deflate: bool) -> CsdResult<Vec<u8>> {
+    let segments: Vec<&str> = csd_jwt.jwt.split('.').collect();
+    let [header, payload, signature] = segments[..] else {
+        return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("JWT has not 3 segments"))));
+    };
+    let mut payload = String::from_utf8(decode_segment(payload)?)
+        .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("JWT payload is not UTF-8: {err}"))))?;
+    let object: Map<String, Value> = serde_json::from_str(&payload)
+        .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("JWT payload is not a JSON object: {err}"))))?;
+    let digests = salted_digests(&object)?;
+    let disclosures = csd_jwt.disclosures
+        .iter()
+        .map(|disclosure| CsdDisclosure::parse(disclosure.to_owned()))
+        .map(|disclosure| disclosure.map(|disclosure| match digests.contains(&disclosure.digest()) {
+            true => disclosure.into_salted(),
+            false => disclosure,
+        }))
+        .collect::<Result<Vec<CsdDisclosure>, _>>()?;
+    let points: Vec<&str> = [point_value(&payload, ACCUMULATOR_KEY), point_value(&payload, PK_KEY)]
+        .into_iter()
+        .flatten()
+        .chain(disclosures.iter().filter_map(|disclosure| disclosure.witness.as_deref()))
+        .collect();
+    let encoding = PointEncoding::ALL
+        .into_iter()
+        .position(|encoding| points.iter().all(|point| encoding.decode(point).is_some()))
+        .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("the points of the presentation don't share an encoding"))))?;
+    let point_encoding = PointEncoding::ALL[encoding];
+    let accumulator = extract_point(&mut payload, ACCUMULATOR_KEY, point_encoding);
+    let pk = extract_point(&mut payload, PK_KEY, point_encoding);
+    let mut names: Vec<&str> = vec![];
+    for disclosure in &disclosures {
+        let name = disclosure.claim_name.as_deref().unwrap_or_default();
+        if !CLAIM_DICTIONARY.contains(&name) && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    let mut body = vec![];
+    put_bytes(&mut body, &decode_segment(header)?);
+    put_bytes(&mut body, payload.as_bytes());
+    put_bytes(&mut body, &accumulator);
+    put_bytes(&mut body, &pk);
+    put_bytes(&mut body, &decode_segment(signature)?);
+    put_varint(&mut body, names.len() as u64);
+    for name in &names {
+        put_bytes(&mut body, name.as_bytes());
+    }
+    put_varint(&mut body, disclosures.len() as u64);
+    for disclosure in &disclosures {
+        // Disclosures restored from the fields must be the ones presented, or the KB-JWT breaks.
+        let (restored, bytes) = match (&disclosure.witness, &disclosure.salt, &disclosure.claim_name) {
+            (Some(witness), None, Some(name)) => (
+                CsdDisclosure::with_witness(witness.to_owned(), name.to_owned(), disclosure.claim_value.clone()),
+                point_encoding.decode(witness).unwrap_or_default(),
+            ),
+            (None, Some(salt), Some(name)) => (
+                CsdDisclosure::with_salt(salt.to_owned(), name.to_owned(), disclosure.claim_value.clone()),
+                base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(salt).unwrap_or_default(),
+            ),
+            _ => return Err(CsdError::Core(CsdCoreError::InvalidDisclosure(format!("disclosure {} has no claim name or witness", disclosure)))),
+        };
+        let name = restored.claim_name.as_deref().unwrap_or_default();
+        if restored.disclosure != disclosure.disclosure {
+            return Err(CsdError::Core(CsdCoreError::InvalidDisclosure(format!("disclosure {} is not in canonical form", disclosure))));
+        }
+        put_bytes(&mut body, &bytes);
+        let index = match CLAIM_DICTIONARY.iter().position(|entry| *entry == name) {
+            Some(index) => index,
+            None => CLAIM_DICTIONARY.len() + names.iter().position(|entry| *entry == name).unwrap_or_default(),
+        };
+        put_varint(&mut body, (index as u64) << 1 | u64::from(disclosure.salt.is_some()));
+        put_bytes(&mut body, disclosure.claim_value.to_string().as_bytes());
+    }
+    put_bytes(&mut body, csd_jwt.key_binding_jwt.as_deref().unwrap_or_default().as_bytes());
+
+    let flags = (encoding as u8) << POINT_ENCODING_SHIFT | if deflate { DEFLATE_FLAG } else { 0 };
+    let mut compact = vec![COMPACT_VERSION, flags];
+    if deflate {
+        let mut encoder = DeflateEncoder::new(compact, Compression::best());
+        encoder.write_all(&body).map_err(|err| CsdError::Unspecified(err.to_string()))?;
+        compact = encoder.finish().map_err(|err| CsdError::Unspecified(err.to_string()))?;
+    } else {
+        compact.extend(body);
+    }
+    Ok(compact)
+}
+
+///
+/// from_compact(compact: &[u8]) -> CsdResult<CsdJwt>
+///
+/// Unpacks a presentation packed by [`to_compact`].
+///
+/// ## Error
+/// Returns [`CsdCoreError::DeserializationError`] if `compact` is truncated, has an unknown version
+/// or doesn't inflate.
+///
+pub fn from_compact(compact: &[u8]) -> CsdResult<CsdJwt> {
+    let (version, flags, body) = match compact {
+        [version, flags, body @ ..] => (*version, *flags, body),
+        _ => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("compact presentation is truncated")))),
+    };
+    if version != COMPACT_VERSION && version != UNSALTED_COMPACT_VERSION {
+        return Err(CsdError::Core(CsdCoreError::DeserializationError(format!("unknown compact format version {}", version))));
+    }
+    let point_encoding = *PointEncoding::ALL
+        .get(usize::from(flags >> POINT_ENCODING_SHIFT & POINT_ENCODING_MASK))
+        .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("unknown point encoding"))))?;
+    let mut inflated = vec![];
+    let body = if flags & DEFLATE_FLAG != 0 {
+        DeflateDecoder::new(body)
+            .read_to_end(&mut inflated)
+            .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("compact presentation doesn't inflate: {err}"))))?;
+        &inflated[..]
+    } else {
+        body
+    };
+
+    let mut reader = CompactReader { bytes: body };
+    let header = reader.bytes()?;
+    let mut payload = reader.string()?;
+    insert_point(&mut payload, ACCUMULATOR_KEY, reader.bytes()?, point_encoding)?;
+    insert_point(&mut payload, PK_KEY, reader.bytes()?, point_encoding)?;
+    let signature = reader.bytes()?;
+    let jwt = [header, payload.as_bytes(), signature]
+        .map(|segment| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(segment))
+        .join(".");
+
+    let names = (0..reader.varint()?).map(|_| reader.string()).collect::<CsdResult<Vec<String>>>()?;
+    let mut disclosures = vec![];
+    for _ in 0..reader.varint()? {
+        let bytes = reader.bytes()?;
+        let (index, salted) = match version {
+            UNSALTED_COMPACT_VERSION => (reader.varint()? as usize, false),
+            _ => {
+                let tagged = reader.varint()?;
+                ((tagged >> 1) as usize, tagged & 1 == 1)
+            }
+        };
+        let name = match CLAIM_DICTIONARY.get(index) {
+            Some(name) => name.to_string(),
+            None => names
+                .get(index - CLAIM_DICTIONARY.len())
+                .cloned()
+                .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("claim index {} is out of the dictionary", index))))?,
+        };
+        let value: Value = serde_json::from_str(&reader.string()?)
+            .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("claim value: {err}"))))?;
+        let disclosure = match salted {
+            true => CsdDisclosure::with_salt(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes), name, value),
+            false => CsdDisclosure::with_witness(point_encoding.encode(bytes), name, value),
+        };
+        disclosures.push(disclosure.into_string());
+    }
+    let key_binding_jwt = Some(reader.string()?).filter(|key_binding_jwt| !key_binding_jwt.is_empty());
+    if !reader.bytes.is_empty() {
+        return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("compact presentation has trailing bytes"))));
+    }
+    Ok(CsdJwt::new(jwt, disclosures, key_binding_jwt))
+}
+
+///
+/// to_compact_text(csd_jwt: &CsdJwt) -> CsdResult<String>
+///
+/// The deflated compact form of `csd_jwt` in Base45 after [`COMPACT_TEXT_PREFIX`], which QR codes
+/// hold in their alphanumeric mode.
+///
+pub fn to_compact_text(csd_jwt: &CsdJwt) -> CsdResult<String> {
+    Ok(format!("{}{}", COMPACT_TEXT_PREFIX, base45::encode(to_compact(csd_jwt, true)?)))
+}
+
+///
+/// from_compact_text(text: &str) -> CsdResult<CsdJwt>
+///
+/// Unpacks a presentation encoded by [`to_compact_text`].
+///
+pub fn from_compact_text(text: &str) -> CsdResult<CsdJwt> {
+    let encoded = text
+        .trim()
+        .strip_prefix(COMPACT_TEXT_PREFIX)
+        .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("compact text doesn't start with {}", COMPACT_TEXT_PREFIX))))?;
+    let compact = base45::decode(encoded).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("compact text is not Base45: {err}"))))?;
+    from_compact(&compact)
+}
+
+fn decode_segment(segment: &str) -> CsdResult<Vec<u8>> {
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("JWT segment is not base64url: {err}"))))
+}
+
+/// The value of the `"key":"<point>"` entry of a raw JSON `payload`, if it's there exactly once.
+fn point_value<'a>(payload: &'a str, key: &str) -> Option<&'a str> {
+    let entry = format!("\"{}\":\"", key);
+    if payload.matches(&entry).count() != 1 {
+        return None;
+    }
+    let start = payload.find(&entry)? + entry.len();
+    let length = payload[start..].find('"')?;
+    Some(&payload[start..start + length])
+}
+
+/// Blanks the value of the `"key":"<point>"` entry of a raw JSON `payload` and returns the point's
+/// bytes, or nothing if the entry isn't there exactly once.
+fn extract_point(payload: &mut String, key: &str, encoding: PointEncoding) -> Vec<u8> {
+    let Some(point) = point_value(payload, key).and_then(|point| encoding.decode(point)) else {
+        return vec![];
+    };
+    let entry = format!("\"{}\":\"", key);
+    let start = payload.find(&entry).unwrap_or_default() + entry.len();
+    let length = payload[start..].find('"').unwrap_or_default();
+    payload.replace_range(start..start + length, "");
+    point
+}
+
+/// Restores a point blanked by [`extract_point`].
+fn insert_point(payload: &mut String, key: &str, point: &[u8], encoding: PointEncoding) -> CsdResult<()> {
+    if point.is_empty() {
+        return Ok(());
+    }
+    let entry = format!("\"{}\":\"\"", key);
+    let start = payload
+        .find(&entry)
+        .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("JWT payload has no blank {}", key))))?;
+    payload.insert_str(start + entry.len() - 1, &encoding.encode(point));
+    Ok(())
+}
+
+/// Appends `value` as an unsigned LEB128 varint.
+fn put_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn put_bytes(output: &mut Vec<u8>, bytes: &[u8]) {
+    put_varint(output, bytes.len() as u64);
+    output.extend_from_slice(bytes);
+}
+
+struct CompactReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> CompactReader<'a> {
+    fn varint(&mut self) -> CsdResult<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (byte, rest) = self
+                .bytes
+                .split_first()
+                .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("compact presentation is truncated"))))?;
+            self.bytes = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("varint is too long"))))
+    }
+
+    fn bytes(&mut self) -> CsdResult<&'a [u8]> {
+        let length = usize::try_from(self.varint()?).unwrap_or(usize::MAX);
+        if length > self.bytes.len() {
+            return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("compact presentation is truncated"))));
+        }
+        let (bytes, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn string(&mut self) -> CsdResult<String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("string is not UTF-8: {err}"))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::{json, Map, Value};
+
+    use crate::accumulator::PointEncoding;
+    use crate::csd::csd_error::{CsdCoreError, CsdError};
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_verifier::CsdVerifier;
+
+    use super::{from_compact, from_compact_text, to_compact, to_compact_text, COMPACT_VERSION};
+
+    /// Bytes of a version 40 QR code with low error correction in alphanumeric mode.
+    const QR_ALPHANUMERIC_CAPACITY: usize = 4296;
+
+    #[test]
+    fn compact_round_trip() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let claims: Map<String, Value> = [
+            ("given_name", json!("Alice")),
+            ("family_name", json!("Smith")),
+            ("birthdate", json!("1990-01-01")),
+            ("email", json!("alice@example.com")),
+            ("nationality", json!("IT")),
+            ("age_over_18", json!(true)),
+            ("address", json!({"locality": "Rome", "country": "IT"})),
+            ("document_number", json!("AB1234567")),
+            ("membership", json!("gold")),
+            ("points", json!(1200)),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_owned(), value))
+        .collect();
+        let credential = issuer.issue(Value::Object(claims.clone())).unwrap().presentation();
+        let pointers: Vec<String> = claims.keys().map(|name| format!("/{}", name)).collect();
+        let presentation = CsdHolder::new(&credential)
+            .unwrap()
+            .present(&pointers.iter().map(String::as_str).collect::<Vec<&str>>())
+            .unwrap();
+
+        let compact = to_compact(&presentation, false).unwrap();
+        assert_eq!(from_compact(&compact).unwrap(), presentation);
+        let deflated = to_compact(&presentation, true).unwrap();
+        assert_eq!(from_compact(&deflated).unwrap(), presentation);
+        assert!(compact.len() < presentation.presentation().len() * 2 / 3);
+
+        let text = to_compact_text(&presentation).unwrap();
+        assert!(text.len() < presentation.presentation().len() && text.len() <= QR_ALPHANUMERIC_CAPACITY);
+        let verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        let restored = from_compact_text(&text).unwrap().presentation();
+        assert_eq!(Value::Object(verifier.verify(&restored).unwrap()), Value::Object(claims));
+
+        assert!(matches!(from_compact(&compact[..compact.len() - 1]).unwrap_err(), CsdError::Core(CsdCoreError::DeserializationError(_))));
+        assert!(matches!(from_compact_text("HC1:NCF").unwrap_err(), CsdError::Core(CsdCoreError::DeserializationError(_))));
+    }
+
+    #[test]
+    fn salted_disclosures() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        for encoding in PointEncoding::ALL {
+            let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+            issuer.set_point_encoding(encoding);
+            issuer.set_salted_claims(vec![String::from("age_over_18"), String::from("photo")]);
+            let credential = issuer.issue(json!({"name": "Alice", "age_over_18": true, "photo": "iVBORw0KGgo"})).unwrap();
+            let presentation = CsdHolder::new(&credential.presentation()).unwrap().present(&["/name", "/age_over_18"]).unwrap();
+
+            // Salts are tagged, so they are restored in base64url whatever the points are encoded in.
+            let compact = to_compact(&presentation, true).unwrap();
+            assert_eq!(compact[0], COMPACT_VERSION);
+            let restored = from_compact(&compact).unwrap();
+            assert_eq!(restored, presentation);
+            assert_eq!(Value::Object(verifier.verify(&restored.presentation()).unwrap()), json!({"name": "Alice", "age_over_18": true}));
+        }
+    }
+}
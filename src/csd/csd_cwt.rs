@@ -0,0 +1,494 @@
+use ciborium::Value as CborValue;
+use coset::{CoseSign1, TaggedCborSerializable};
+#[cfg(not(target_arch = "wasm32"))]
+use coset::{iana, Algorithm, CoseSign1Builder, HeaderBuilder};
+#[cfg(not(target_arch = "wasm32"))]
+use josekit::jws::{JwsSigner, JwsVerifier};
+use serde_json::{Map, Number, Value};
+
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    ark_bls12_381::Bls12_381,
+    ark_bn254::Bn254,
+    ark_ec::pairing::Pairing,
+    crate::accumulator::decode_point_bytes,
+};
+use crate::accumulator::PointEncoding;
+use crate::csd;
+#[cfg(not(target_arch = "wasm32"))]
+use csd::csd_decoder::{salted_digests, unverified_payload};
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_encoder::{ACCUMULATOR_KEY, PARAM_SEED_KEY, PK_KEY};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+#[cfg(not(target_arch = "wasm32"))]
+use csd::csd_jws::issue_with_key_id;
+#[cfg(not(target_arch = "wasm32"))]
+use csd::csd_jwt::CsdJwt;
+
+/// The content type of the COSE_Sign1 protected header.
+pub const CWT_CONTENT_TYPE: &str = "application/csd+cwt";
+
+/// The registered CWT claims (RFC 8392, RFC 8747), keyed by their integer labels instead of names.
+const CWT_CLAIM_LABELS: [(&str, i64); 7] = [("iss", 1), ("sub", 2), ("aud", 3), ("exp", 4), ("nbf", 5), ("iat", 6), ("cnf", 8)];
+
+/// The COSE algorithms of the JWS ones. The BLS algorithms have no COSE registration and use
+/// their JWS name as a text label.
+#[cfg(not(target_arch = "wasm32"))]
+const COSE_ALGORITHMS: [(&str, iana::Algorithm); 5] = [
+    ("ES256", iana::Algorithm::ES256),
+    ("ES384", iana::Algorithm::ES384),
+    ("ES512", iana::Algorithm::ES512),
+    ("EdDSA", iana::Algorithm::EdDSA),
+    ("PS256", iana::Algorithm::PS256),
+];
+
+/// A CSD credential in CBOR: a COSE_Sign1 over the CWT claims of the issuer JWT payload, with
+/// the accumulator and public key as raw compressed points, and CBOR disclosures
+/// `[witness, name, value]` with raw witnesses. Salted disclosures are kept as the text of their
+/// SD-JWT form, which the `_sd` digests of the payload are over.
+///
+/// Both forms sign the same claims and carry the same witnesses, so converting with
+/// [`CsdCwt::from_jwt`] and [`CsdCwt::to_jwt`] only needs the issuer to sign again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsdCwt {
+    /// The tagged COSE_Sign1 of the issuer.
+    pub cose_sign1: Vec<u8>,
+    /// The CBOR-encoded disclosures.
+    pub disclosures: Vec<Vec<u8>>,
+}
+
+impl CsdCwt {
+    /// Signs the claims of `payload`, an issuer JWT payload as produced by
+    /// [`csd::csd_encoder::CsdEncoder::encode`], as a COSE_Sign1 with `signer`.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::DataTypeMismatch`] if a claim has no CBOR form, or [`CsdError::Jws`] if
+    /// signing fails.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn sign(payload: &Map<String, Value>, disclosures: &[CsdDisclosure], signer: &dyn JwsSigner, key_id: Option<&str>) -> CsdResult<Self> {
+        let algorithm = signer.algorithm().name();
+        let mut protected = HeaderBuilder::new().content_type(String::from(CWT_CONTENT_TYPE));
+        if let Some(key_id) = signer.key_id().or(key_id) {
+            protected = protected.key_id(key_id.as_bytes().to_vec());
+        }
+        let mut protected = protected.build();
+        protected.alg = Some(match COSE_ALGORITHMS.iter().find(|(name, _)| *name == algorithm) {
+            Some((_, cose_algorithm)) => Algorithm::Assigned(*cose_algorithm),
+            None => Algorithm::Text(algorithm.to_owned()),
+        });
+
+        let cose_sign1 = CoseSign1Builder::new()
+            .protected(protected)
+            .payload(to_bytes(&payload_to_cbor(payload)?)?)
+            .try_create_signature(b"", |data| signer.sign(data))
+            .map_err(|err| CsdError::Jws(err.to_string()))?
+            .build()
+            .to_tagged_vec()
+            .map_err(|err| CsdError::Jws(err.to_string()))?;
+        let disclosures = disclosures.iter().map(disclosure_to_cbor).collect::<CsdResult<Vec<Vec<u8>>>>()?;
+        Ok(Self { cose_sign1, disclosures })
+    }
+
+    /// Converts a CSD-JWT credential or presentation, re-signing its issuer payload with `signer`.
+    /// The KB-JWT, if any, is dropped as it signs the JWT form.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_jwt(csd_jwt: &CsdJwt, signer: &dyn JwsSigner) -> CsdResult<Self> {
+        let payload = unverified_payload(&csd_jwt.jwt)?;
+        let digests = salted_digests(&payload)?;
+        let disclosures = csd_jwt.disclosures
+            .iter()
+            .map(|disclosure| CsdDisclosure::parse(disclosure.to_owned()))
+            .map(|disclosure| disclosure.map(|disclosure| match digests.contains(&disclosure.digest()) {
+                true => disclosure.into_salted(),
+                false => disclosure,
+            }))
+            .collect::<Result<Vec<CsdDisclosure>, _>>()?;
+        let key_id = josekit::jwt::decode_header(&csd_jwt.jwt)
+            .ok()
+            .and_then(|header| header.claim("kid").and_then(Value::as_str).map(String::from));
+        Self::sign(&payload, &disclosures, signer, key_id.as_deref())
+    }
+
+    /// Converts back into a CSD-JWT, re-signing the issuer payload with `signer`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn to_jwt(&self, signer: &dyn JwsSigner) -> CsdResult<CsdJwt> {
+        let sign1 = self.cose_sign1()?;
+        let key_id = String::from_utf8(sign1.protected.header.key_id.clone()).ok().filter(|key_id| !key_id.is_empty());
+        let payload = self.unverified_payload()?;
+        let mut csd_jwt = issue_with_key_id(&payload, signer, key_id.as_deref())?;
+        csd_jwt.disclosures = self.disclosures()?.into_iter().map(|disclosure| disclosure.into_string()).collect();
+        Ok(csd_jwt)
+    }
+
+    /// Verifies the COSE_Sign1 with `verifier`, whose algorithm must be in `allowed_algorithms`,
+    /// and returns the issuer payload in its JSON form.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::AlgorithmNotAllowed`] if the `alg` header is missing, differs from the
+    /// verifier's or is not allowed, and [`CsdError::Jws`] if the signature doesn't verify.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn verify(&self, verifier: &dyn JwsVerifier, allowed_algorithms: &[String]) -> CsdResult<Map<String, Value>> {
+        let sign1 = self.cose_sign1()?;
+        let algorithm = match &sign1.protected.header.alg {
+            Some(Algorithm::Assigned(cose_algorithm)) => COSE_ALGORITHMS
+                .iter()
+                .find(|(_, assigned)| assigned == cose_algorithm)
+                .map(|(name, _)| name.to_string())
+                .ok_or(CsdError::AlgorithmNotAllowed(format!("{:?}", cose_algorithm)))?,
+            Some(Algorithm::Text(name)) => name.to_owned(),
+            _ => return Err(CsdError::AlgorithmNotAllowed(String::from("none"))),
+        };
+        if algorithm != verifier.algorithm().name() || !allowed_algorithms.contains(&algorithm) {
+            return Err(CsdError::AlgorithmNotAllowed(algorithm));
+        }
+        sign1
+            .verify_signature(b"", |signature, data| verifier.verify(data, signature))
+            .map_err(|err| CsdError::Jws(err.to_string()))?;
+        payload_from_cbor(&sign1)
+    }
+
+    /// The issuer payload in its JSON form, without verifying the signature.
+    pub fn unverified_payload(&self) -> CsdResult<Map<String, Value>> {
+        payload_from_cbor(&self.cose_sign1()?)
+    }
+
+    pub fn disclosures(&self) -> CsdResult<Vec<CsdDisclosure>> {
+        self.disclosures.iter().map(|disclosure| disclosure_from_cbor(disclosure)).collect()
+    }
+
+    /// A presentation keeping the disclosures of the top-level claims `names` only.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::InvalidPath`] if a name has no disclosure.
+    pub fn present(&self, names: &[&str]) -> CsdResult<Self> {
+        let disclosures = self.disclosures()?;
+        let mut presented = vec![];
+        for name in names {
+            let index = disclosures
+                .iter()
+                .position(|disclosure| disclosure.claim_name.as_deref() == Some(*name))
+                .ok_or(CsdError::InvalidPath(format!("{} has no disclosure", name)))?;
+            presented.push(self.disclosures[index].clone());
+        }
+        Ok(Self { cose_sign1: self.cose_sign1.clone(), disclosures: presented })
+    }
+
+    /// Serializes into the CBOR array `[COSE_Sign1, [disclosure, ...]]`.
+    pub fn to_bytes(&self) -> CsdResult<Vec<u8>> {
+        to_bytes(&CborValue::Array(vec![
+            CborValue::Bytes(self.cose_sign1.clone()),
+            CborValue::Array(self.disclosures.iter().cloned().map(CborValue::Bytes).collect()),
+        ]))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> CsdResult<Self> {
+        let items = match from_bytes(bytes)? {
+            CborValue::Array(items) if items.len() == 2 => items,
+            _ => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("CWT is not a [COSE_Sign1, disclosures] array")))),
+        };
+        match (&items[0], &items[1]) {
+            (CborValue::Bytes(cose_sign1), CborValue::Array(disclosures)) => Ok(Self {
+                cose_sign1: cose_sign1.to_owned(),
+                disclosures: disclosures
+                    .iter()
+                    .map(|disclosure| disclosure.as_bytes().cloned())
+                    .collect::<Option<Vec<Vec<u8>>>>()
+                    .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("disclosure is not a byte string"))))?,
+            }),
+            _ => Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("CWT is not a [COSE_Sign1, disclosures] array")))),
+        }
+    }
+
+    fn cose_sign1(&self) -> CsdResult<CoseSign1> {
+        CoseSign1::from_tagged_slice(&self.cose_sign1).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("COSE_Sign1: {err}"))))
+    }
+}
+
+fn to_bytes(value: &CborValue) -> CsdResult<Vec<u8>> {
+    let mut bytes = vec![];
+    ciborium::into_writer(value, &mut bytes).map_err(|err| CsdError::DataTypeMismatch(err.to_string()))?;
+    Ok(bytes)
+}
+
+fn from_bytes(bytes: &[u8]) -> CsdResult<CborValue> {
+    ciborium::from_reader(bytes).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("CBOR: {err}"))))
+}
+
+/// A CBOR map with its entries in the canonical order of their encoded keys.
+#[cfg(not(target_arch = "wasm32"))]
+fn canonical_map(entries: Vec<(CborValue, CborValue)>) -> CsdResult<CborValue> {
+    let mut entries = entries
+        .into_iter()
+        .map(|(key, value)| Ok((to_bytes(&key)?, key, value)))
+        .collect::<CsdResult<Vec<(Vec<u8>, CborValue, CborValue)>>>()?;
+    entries.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+    Ok(CborValue::Map(entries.into_iter().map(|(_, key, value)| (key, value)).collect()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn json_to_cbor(value: &Value) -> CsdResult<CborValue> {
+    Ok(match value {
+        Value::Null => CborValue::Null,
+        Value::Bool(boolean) => CborValue::Bool(*boolean),
+        Value::Number(number) => match (number.as_i64(), number.as_u64(), number.as_f64()) {
+            (Some(integer), _, _) => CborValue::Integer(integer.into()),
+            (_, Some(integer), _) => CborValue::Integer(integer.into()),
+            (_, _, Some(float)) => CborValue::Float(float),
+            _ => return Err(CsdError::DataTypeMismatch(format!("number {} has no CBOR form", number))),
+        },
+        Value::String(string) => CborValue::Text(string.to_owned()),
+        Value::Array(array) => CborValue::Array(array.iter().map(json_to_cbor).collect::<CsdResult<Vec<CborValue>>>()?),
+        Value::Object(object) => canonical_map(
+            object
+                .iter()
+                .map(|(key, value)| Ok((CborValue::Text(key.to_owned()), json_to_cbor(value)?)))
+                .collect::<CsdResult<Vec<(CborValue, CborValue)>>>()?,
+        )?,
+    })
+}
+
+fn cbor_to_json(value: &CborValue) -> CsdResult<Value> {
+    Ok(match value {
+        CborValue::Null => Value::Null,
+        CborValue::Bool(boolean) => Value::Bool(*boolean),
+        CborValue::Integer(integer) => {
+            let integer = i128::from(*integer);
+            match (i64::try_from(integer), u64::try_from(integer)) {
+                (Ok(integer), _) => Value::from(integer),
+                (_, Ok(integer)) => Value::from(integer),
+                _ => return Err(CsdError::Core(CsdCoreError::DeserializationError(format!("integer {} is out of range", integer)))),
+            }
+        }
+        CborValue::Float(float) => Number::from_f64(*float)
+            .map(Value::Number)
+            .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("float {} has no JSON form", float))))?,
+        CborValue::Text(string) => Value::String(string.to_owned()),
+        CborValue::Array(array) => Value::Array(array.iter().map(cbor_to_json).collect::<CsdResult<Vec<Value>>>()?),
+        CborValue::Map(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| match key {
+                    CborValue::Text(key) => Ok((key.to_owned(), cbor_to_json(value)?)),
+                    _ => Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("map key is not a text string")))),
+                })
+                .collect::<CsdResult<Map<String, Value>>>()?,
+        ),
+        _ => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("CBOR value has no JSON form")))),
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn decode_point(name: &str, value: &Value) -> CsdResult<CborValue> {
+    let encoded = value
+        .as_str()
+        .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("{} is not a serialized point", name))))?;
+    // CWTs don't say the curve, so the bytes must make a point of one of the supported curves.
+    let bytes = curve_point_bytes::<Bn254>(name, encoded).or_else(|_| curve_point_bytes::<Bls12_381>(name, encoded))?;
+    Ok(CborValue::Bytes(bytes))
+}
+
+/// The bytes of the public key, in G2, or of the accumulator or a witness, in G1, on the curve `C`.
+#[cfg(not(target_arch = "wasm32"))]
+fn curve_point_bytes<C: Pairing>(name: &str, encoded: &str) -> CsdResult<Vec<u8>> {
+    let (bytes, _) = match name {
+        PK_KEY => decode_point_bytes::<C::G2Affine>(encoded, name)?,
+        _ => decode_point_bytes::<C::G1Affine>(encoded, name)?,
+    };
+    Ok(bytes)
+}
+
+fn encode_point(name: &str, value: &CborValue) -> CsdResult<Value> {
+    value
+        .as_bytes()
+        .map(|bytes| Value::String(PointEncoding::default().encode(bytes)))
+        .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("{} is not a byte string", name))))
+}
+
+/// The CWT claims of an issuer JWT payload: registered claims under their labels, the accumulator
+/// and public key as bytes and the param seed as an integer.
+#[cfg(not(target_arch = "wasm32"))]
+fn payload_to_cbor(payload: &Map<String, Value>) -> CsdResult<CborValue> {
+    let mut entries = vec![];
+    for (name, value) in payload {
+        let key = match CWT_CLAIM_LABELS.iter().find(|(claim, _)| claim == name) {
+            Some((_, label)) => CborValue::Integer((*label).into()),
+            None => CborValue::Text(name.to_owned()),
+        };
+        let value = match name.as_str() {
+            ACCUMULATOR_KEY | PK_KEY => decode_point(name, value)?,
+            PARAM_SEED_KEY => value
+                .as_str()
+                .and_then(|seed| seed.parse::<u64>().ok())
+                .map(|seed| CborValue::Integer(seed.into()))
+                .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("param seed is not a number"))))?,
+            _ => json_to_cbor(value)?,
+        };
+        entries.push((key, value));
+    }
+    canonical_map(entries)
+}
+
+fn payload_from_cbor(sign1: &CoseSign1) -> CsdResult<Map<String, Value>> {
+    let payload = sign1.payload.as_deref().ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("COSE_Sign1 has no payload"))))?;
+    let entries = match from_bytes(payload)? {
+        CborValue::Map(entries) => entries,
+        _ => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("CWT claims are not a map")))),
+    };
+    let mut object = Map::new();
+    for (key, value) in entries {
+        let name = match &key {
+            CborValue::Text(name) => name.to_owned(),
+            CborValue::Integer(label) => CWT_CLAIM_LABELS
+                .iter()
+                .find(|(_, registered)| i128::from(*registered) == i128::from(*label))
+                .map(|(claim, _)| claim.to_string())
+                .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("unknown CWT claim {}", i128::from(*label)))))?,
+            _ => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("CWT claim key is neither a label nor a name")))),
+        };
+        let value = match name.as_str() {
+            ACCUMULATOR_KEY | PK_KEY => encode_point(&name, &value)?,
+            PARAM_SEED_KEY => Value::String(cbor_to_json(&value)?.to_string()),
+            _ => cbor_to_json(&value)?,
+        };
+        object.insert(name, value);
+    }
+    Ok(object)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn disclosure_to_cbor(disclosure: &CsdDisclosure) -> CsdResult<Vec<u8>> {
+    // The digests are over the JSON disclosures, which the CBOR arrays can't reproduce.
+    if disclosure.salt.is_some() {
+        return to_bytes(&CborValue::Text(disclosure.as_str().to_owned()));
+    }
+    let (Some(witness), Some(name)) = (&disclosure.witness, &disclosure.claim_name) else {
+        return Err(CsdError::Core(CsdCoreError::InvalidDisclosure(format!("disclosure {} has no claim name or witness", disclosure))));
+    };
+    to_bytes(&CborValue::Array(vec![
+        decode_point("witness", &Value::String(witness.to_owned()))?,
+        CborValue::Text(name.to_owned()),
+        json_to_cbor(&disclosure.claim_value)?,
+    ]))
+}
+
+fn disclosure_from_cbor(bytes: &[u8]) -> CsdResult<CsdDisclosure> {
+    match from_bytes(bytes)? {
+        CborValue::Array(items) if items.len() == 3 => match (&items[0], &items[1]) {
+            (CborValue::Bytes(_), CborValue::Text(name)) => {
+                let witness = encode_point("witness", &items[0])?;
+                Ok(CsdDisclosure::with_witness(
+                    witness.as_str().unwrap_or_default().to_owned(),
+                    name.to_owned(),
+                    cbor_to_json(&items[2])?,
+                ))
+            }
+            _ => Err(CsdError::Core(CsdCoreError::InvalidDisclosure(String::from("disclosure is not [witness, name, value]")))),
+        },
+        CborValue::Text(disclosure) => Ok(CsdDisclosure::parse(disclosure)?.into_salted()),
+        _ => Err(CsdError::Core(CsdCoreError::InvalidDisclosure(String::from("disclosure is not [witness, name, value]")))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::pairing::Pairing;
+    use serde_json::{json, Value};
+
+    use crate::accumulator::PointEncoding;
+    use crate::csd::csd_disclosure::CsdDisclosure;
+    use crate::csd::csd_encoder::ACCUMULATOR_KEY;
+    use crate::csd::csd_error::{CsdCoreError, CsdError};
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::{default_allowed_algorithms, CsdJwsAlgorithm};
+    use crate::csd::csd_jwt::CsdJwt;
+    use crate::csd::csd_verifier::CsdVerifier;
+
+    use super::{decode_point, disclosure_to_cbor, CsdCwt};
+
+    #[test]
+    fn jwt_round_trip() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let signer = alg.signer_from_jwk(&jwk).unwrap();
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let credential = issuer
+            .issue(json!({"name": "Alice", "age": 30, "address": {"street": "Main St", "city": "Rome", "zip": "00100"}}))
+            .unwrap();
+
+        let cwt = CsdCwt::from_jwt(&credential, &*signer).unwrap();
+        let cwt = CsdCwt::from_bytes(&cwt.to_bytes().unwrap()).unwrap();
+        assert!(cwt.to_bytes().unwrap().len() < credential.presentation().len());
+        assert_eq!(cwt.disclosures().unwrap(), CsdHolder::new(&credential.presentation()).unwrap().disclosures());
+
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        let presentation = cwt.present(&["name", "address"]).unwrap();
+        assert_eq!(
+            Value::Object(verifier.verify_cwt(&presentation).unwrap()),
+            json!({"name": "Alice", "address": {"zip": "00100", "city": "Rome", "street": "Main St"}})
+        );
+
+        // Back in the JWT form, the witnesses still verify against the accumulator.
+        let jwt = presentation.to_jwt(&*signer).unwrap();
+        assert_eq!(verifier.verify(&jwt.presentation()).unwrap().len(), 2);
+        let converted = CsdCwt::from_jwt(&CsdJwt::parse(&jwt.presentation()).unwrap(), &*signer).unwrap();
+        assert_eq!(converted.unverified_payload().unwrap(), presentation.unverified_payload().unwrap());
+        assert_eq!(converted.disclosures, presentation.disclosures);
+
+        let other = alg.verifier_from_jwk(&alg.generate_jwk().unwrap()).unwrap();
+        assert!(matches!(cwt.verify(&*other, &default_allowed_algorithms()).unwrap_err(), CsdError::Jws(_)));
+        verifier.set_allowed_algorithms(vec![String::from("EdDSA")]);
+        assert!(matches!(verifier.verify_cwt(&cwt).unwrap_err(), CsdError::AlgorithmNotAllowed(_)));
+    }
+
+    #[test]
+    fn salted_disclosures() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let signer = alg.signer_from_jwk(&jwk).unwrap();
+        let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        issuer.set_salted_claims(vec![String::from("age_over_18"), String::from("photo")]);
+        let credential = issuer.issue(json!({"name": "Alice", "age_over_18": true, "photo": "iVBORw0KGgo"})).unwrap();
+
+        let cwt = CsdCwt::from_bytes(&CsdCwt::from_jwt(&credential, &*signer).unwrap().to_bytes().unwrap()).unwrap();
+        assert_eq!(cwt.unverified_payload().unwrap()["_sd"].as_array().unwrap().len(), 2);
+        let salted: Vec<CsdDisclosure> = cwt.disclosures().unwrap().into_iter().filter(|disclosure| disclosure.salt.is_some()).collect();
+        assert_eq!(salted.len(), 2);
+
+        let verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        let presentation = cwt.present(&["name", "age_over_18"]).unwrap();
+        assert_eq!(Value::Object(verifier.verify_cwt(&presentation).unwrap()), json!({"name": "Alice", "age_over_18": true}));
+
+        // The salted disclosures come back in their SD-JWT form, which the digests are over.
+        let jwt = presentation.to_jwt(&*signer).unwrap();
+        assert_eq!(Value::Object(verifier.verify(&jwt.presentation()).unwrap()), json!({"name": "Alice", "age_over_18": true}));
+        let forged = CsdDisclosure::with_salt(salted[0].salt.clone().unwrap(), String::from("age_over_18"), json!(false));
+        let tampered = CsdCwt { disclosures: vec![disclosure_to_cbor(&forged).unwrap()], ..presentation };
+        assert!(verifier.verify_cwt(&tampered).is_err());
+    }
+
+    fn round_trip_encodings<C: Pairing>() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let signer = alg.signer_from_jwk(&jwk).unwrap();
+        let verifier = CsdVerifier::<C>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        for encoding in PointEncoding::ALL {
+            let mut issuer = CsdIssuer::<C>::new(alg.signer_from_jwk(&jwk).unwrap());
+            issuer.set_point_encoding(encoding);
+            let credential = issuer.issue(json!({"name": "Alice", "age": 30})).unwrap();
+            let cwt = CsdCwt::from_jwt(&credential, &*signer).unwrap();
+            assert_eq!(verifier.verify_cwt(&cwt).unwrap().len(), 2);
+        }
+    }
+
+    #[test]
+    fn point_encodings() {
+        round_trip_encodings::<Bn254>();
+        round_trip_encodings::<Bls12_381>();
+        // Bytes are only taken once they make a point, whatever the encoding they decode in.
+        let not_a_point = Value::String(PointEncoding::Base64Url.encode(&[0xff; 32]));
+        assert!(matches!(decode_point(ACCUMULATOR_KEY, &not_a_point).unwrap_err(), CsdError::Core(CsdCoreError::DeserializationError(_))));
+    }
+}
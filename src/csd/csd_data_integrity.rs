@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+
+use ark_ec::pairing::Pairing;
+use base64::Engine;
+use josekit::jws::{JwsSigner, JwsVerifier};
+use multibase::Base;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use vb_accumulator::positive::Accumulator;
+use vb_accumulator::setup::SecretKey;
+
+use crate::accumulator::{
+    canonical_json, claim_key, deserialize_accumulator, deserialize_pk, deserialize_witness, generate_params, initialize_accumulator,
+    scalar_from_str, serialize_accumulator, serialize_pk, serialize_witness,
+};
+use crate::csd;
+use csd::csd_encoder::DEFAULT_PARAM_SEED;
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+use csd::csd_vcdm::{arrays, flatten, insert};
+
+/// The Data Integrity cryptosuite of accumulator-based selective disclosure.
+pub const CRYPTOSUITE: &str = "csd-accumulator-2025";
+pub const DATA_INTEGRITY_PROOF_TYPE: &str = "DataIntegrityProof";
+pub const PROOF_KEY: &str = "proof";
+
+/// Prefixes of the `proofValue` bytes telling base proofs from derived ones, as in `ecdsa-sd-2023`.
+const BASE_PROOF_HEADER: [u8; 3] = [0xd9, 0x5d, 0xc0];
+const DERIVED_PROOF_HEADER: [u8; 3] = [0xd9, 0x5d, 0xc1];
+
+/// The `proof` fields besides `proofValue`, and the accumulator secret key and parameter seed of
+/// the issuer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsdProofOptions<C: Pairing> {
+    verification_method: String,
+    proof_purpose: String,
+    created: Option<String>,
+    secret_key: SecretKey<C::ScalarField>,
+    param_seed: u64,
+}
+
+impl<C: Pairing> CsdProofOptions<C> {
+    /// Creates the options of an `assertionMethod` proof verified with `verification_method`,
+    /// whose leaves are accumulated with the issuer's `secret_key`, e.g. one from
+    /// [`crate::accumulator::generate_secret_key`].
+    pub fn new(verification_method: impl Into<String>, secret_key: SecretKey<C::ScalarField>) -> Self {
+        Self {
+            verification_method: verification_method.into(),
+            proof_purpose: String::from("assertionMethod"),
+            created: None,
+            secret_key,
+            param_seed: DEFAULT_PARAM_SEED,
+        }
+    }
+
+    pub fn set_proof_purpose(&mut self, proof_purpose: impl Into<String>) {
+        self.proof_purpose = proof_purpose.into();
+    }
+
+    /// Sets the `created` date-time of the proof, left out by default.
+    pub fn set_created(&mut self, created: impl Into<String>) {
+        self.created = Some(created.into());
+    }
+
+    /// Sets the seed of the accumulator parameters, see
+    /// [`csd::csd_encoder::CsdEncoder::set_param_seed`].
+    pub fn set_param_seed(&mut self, param_seed: u64) {
+        self.param_seed = param_seed;
+    }
+
+    fn proof_config(&self) -> Map<String, Value> {
+        let mut config = Map::new();
+        config.insert(String::from("type"), Value::from(DATA_INTEGRITY_PROOF_TYPE));
+        config.insert(String::from("cryptosuite"), Value::from(CRYPTOSUITE));
+        if let Some(created) = &self.created {
+            config.insert(String::from("created"), Value::from(created.to_owned()));
+        }
+        config.insert(String::from("verificationMethod"), Value::from(self.verification_method.to_owned()));
+        config.insert(String::from("proofPurpose"), Value::from(self.proof_purpose.to_owned()));
+        config
+    }
+}
+
+/// The components of a `proofValue`. Base proofs hold the witness of every non-mandatory leaf,
+/// derived proofs only those of the disclosed ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CsdProofValue {
+    alg: String,
+    signature: String,
+    accumulator: String,
+    pk: String,
+    param_seed: String,
+    mandatory_pointers: Vec<String>,
+    witnesses: BTreeMap<String, String>,
+}
+
+impl CsdProofValue {
+    fn encode(&self, header: [u8; 3]) -> String {
+        let mut bytes = header.to_vec();
+        bytes.extend(serde_json::to_vec(self).unwrap_or_default());
+        multibase::encode(Base::Base64Url, bytes)
+    }
+
+    fn decode(proof_value: &str, header: [u8; 3]) -> CsdResult<Self> {
+        let (_, bytes) = multibase::decode(proof_value)
+            .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("proofValue is not multibase: {err}"))))?;
+        let components = bytes
+            .strip_prefix(&header)
+            .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("proofValue has the wrong header"))))?;
+        serde_json::from_slice(components).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("proofValue: {err}"))))
+    }
+
+    /// The message the issuer signs: the proof configuration, the mandatory values and the
+    /// accumulator, hashed in the [`canonical_json`] claims are accumulated in.
+    fn message(&self, config: &Map<String, Value>, mandatory: &[(String, Value)]) -> Vec<u8> {
+        let signed = json!({
+            "proof": config,
+            "mandatory": mandatory.iter().map(|(pointer, value)| json!([pointer, value])).collect::<Vec<Value>>(),
+            "accumulator": self.accumulator,
+            "pk": self.pk,
+            "param_seed": self.param_seed,
+        });
+        Sha256::digest(canonical_json(&signed)).to_vec()
+    }
+}
+
+///
+/// create_base_proof(document: &Value, mandatory_pointers: &[&str], signer: &dyn JwsSigner, options: &CsdProofOptions<C>) -> CsdResult<Value>
+///
+/// Secures `document` with a base proof: every leaf outside the `mandatory_pointers` is accumulated
+/// as its [`claim_key`] `pointer::value`, and `signer` signs the accumulator together with the
+/// values at the mandatory pointers, in canonical JSON. The holder derives proofs from the returned
+/// document with [`derive_proof`].
+///
+/// ## Error
+/// Returns [`CsdError::InvalidPath`] if a mandatory pointer is not in `document`, or
+/// [`CsdError::Jws`] if signing fails.
+///
+pub fn create_base_proof<C: Pairing>(
+    document: &Value,
+    mandatory_pointers: &[&str],
+    signer: &dyn JwsSigner,
+    options: &CsdProofOptions<C>,
+) -> CsdResult<Value> {
+    let mut document = document
+        .as_object()
+        .ok_or(CsdError::DataTypeMismatch(String::from("expected object")))?
+        .clone();
+    document.remove(PROOF_KEY);
+    let unsecured = Value::Object(document.clone());
+
+    let mandatory = values_at(&unsecured, mandatory_pointers.iter().map(|pointer| pointer.to_string()))?;
+    let mut leaves = Map::new();
+    flatten("", &unsecured, &mut leaves);
+    let leaves: Vec<(String, Value)> = leaves
+        .into_iter()
+        .filter(|(pointer, _)| !is_mandatory(pointer, mandatory_pointers))
+        .collect();
+
+    let (_, keypair, accumulator, mut state) = initialize_accumulator::<C>(&options.secret_key, options.param_seed);
+    let elements: Vec<C::ScalarField> = leaves
+        .iter()
+        .map(|(pointer, value)| scalar_from_str::<C>(&claim_key(pointer, value)))
+        .collect();
+    let accumulator = accumulator
+        .add_batch(elements.clone(), &keypair.secret_key, &mut state)
+        .map_err(|err| CsdError::AddBatch(format!("{:?}", err)))?;
+    let witnesses = accumulator
+        .get_membership_witnesses_for_batch(&elements, &keypair.secret_key, &state)
+        .map_err(|err| CsdError::WitnessBatch(format!("{:?}", err)))?;
+
+    let mut proof_value = CsdProofValue {
+        alg: signer.algorithm().name().to_owned(),
+        signature: String::new(),
+        accumulator: serialize_accumulator(accumulator),
+        pk: serialize_pk(keypair.public_key.clone()),
+        param_seed: options.param_seed.to_string(),
+        mandatory_pointers: mandatory_pointers.iter().map(|pointer| pointer.to_string()).collect(),
+        witnesses: leaves
+            .into_iter()
+            .zip(witnesses)
+            .map(|((pointer, _), witness)| (pointer, serialize_witness::<C>(witness)))
+            .collect(),
+    };
+    let config = options.proof_config();
+    let signature = signer
+        .sign(&proof_value.message(&config, &mandatory))
+        .map_err(|err| CsdError::Jws(err.to_string()))?;
+    proof_value.signature = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(signature);
+
+    let mut proof = config;
+    proof.insert(String::from("proofValue"), Value::String(proof_value.encode(BASE_PROOF_HEADER)));
+    document.insert(String::from(PROOF_KEY), Value::Object(proof));
+    Ok(Value::Object(document))
+}
+
+///
+/// derive_proof(secured: &Value, selective_pointers: &[&str]) -> CsdResult<Value>
+///
+/// Derives from a document secured with [`create_base_proof`] the document revealing the mandatory
+/// values and the leaves at or below `selective_pointers`, secured with a derived proof holding
+/// only the witnesses of those leaves. Arrays with withheld elements are revealed without the gaps.
+///
+/// ## Error
+/// Returns [`CsdError::InvalidPath`] if a selective pointer matches no leaf, or
+/// [`CsdCoreError::DeserializationError`] if `secured` has no base proof.
+///
+pub fn derive_proof(secured: &Value, selective_pointers: &[&str]) -> CsdResult<Value> {
+    let (document, mut proof) = split_proof(secured)?;
+    let mut proof_value = CsdProofValue::decode(&proof_value(&proof)?, BASE_PROOF_HEADER)?;
+
+    let mut selected = BTreeMap::new();
+    for selective_pointer in selective_pointers {
+        let matching: Vec<(&String, &String)> = proof_value
+            .witnesses
+            .iter()
+            .filter(|(pointer, _)| is_below(pointer, selective_pointer))
+            .collect();
+        if matching.is_empty() {
+            return Err(CsdError::InvalidPath(format!("{} matches no selectively disclosable leaf", selective_pointer)));
+        }
+        selected.extend(matching.into_iter().map(|(pointer, witness)| (pointer.to_owned(), witness.to_owned())));
+    }
+    proof_value.witnesses = selected;
+
+    let pointers = proof_value.mandatory_pointers.iter().chain(proof_value.witnesses.keys()).cloned();
+    let Value::Object(mut revealed) = reveal(values_at(&document, pointers)?)? else {
+        unreachable!("revealed documents are objects");
+    };
+    proof.insert(String::from("proofValue"), Value::String(proof_value.encode(DERIVED_PROOF_HEADER)));
+    revealed.insert(String::from(PROOF_KEY), Value::Object(proof));
+    Ok(Value::Object(revealed))
+}
+
+///
+/// verify_derived_proof(secured: &Value, verifier: &dyn JwsVerifier) -> CsdResult<Value>
+///
+/// Verifies a document secured with [`derive_proof`]: the issuer signature over the mandatory
+/// values with `verifier`, and the witness of every other revealed leaf against the accumulator.
+/// Returns the document without its proof.
+///
+/// ## Error
+/// Returns [`CsdError::VerificationFailed`] if the signature or a witness doesn't verify, or the
+/// document reveals anything the proof doesn't cover.
+///
+pub fn verify_derived_proof<C: Pairing>(secured: &Value, verifier: &dyn JwsVerifier) -> CsdResult<Value> {
+    let (document, proof) = split_proof(secured)?;
+    if proof.get("type").and_then(Value::as_str) != Some(DATA_INTEGRITY_PROOF_TYPE)
+        || proof.get("cryptosuite").and_then(Value::as_str) != Some(CRYPTOSUITE)
+    {
+        return Err(CsdError::VerificationFailed(format!("proof is not a {} {}", CRYPTOSUITE, DATA_INTEGRITY_PROOF_TYPE)));
+    }
+    let proof_value = CsdProofValue::decode(&proof_value(&proof)?, DERIVED_PROOF_HEADER)?;
+    if proof_value.alg != verifier.algorithm().name() {
+        return Err(CsdError::AlgorithmNotAllowed(proof_value.alg));
+    }
+    let mandatory_pointers: Vec<&str> = proof_value.mandatory_pointers.iter().map(String::as_str).collect();
+    if let Some(pointer) = proof_value.witnesses.keys().find(|pointer| is_mandatory(pointer, &mandatory_pointers)) {
+        return Err(CsdError::VerificationFailed(format!("{} is both mandatory and selective", pointer)));
+    }
+
+    // Maps the pointers of the issued document onto the revealed one, where arrays have no gaps.
+    let pointers: Vec<String> = proof_value.mandatory_pointers.iter().chain(proof_value.witnesses.keys()).cloned().collect();
+    let mut revealed_pointers = Map::new();
+    flatten("", &reveal(pointers.iter().map(|pointer| (pointer.to_owned(), Value::String(pointer.to_owned()))).collect())?, &mut revealed_pointers);
+    let mut values = BTreeMap::new();
+    for (revealed_pointer, pointer) in revealed_pointers {
+        let value = document
+            .pointer(&revealed_pointer)
+            .ok_or(CsdError::VerificationFailed(format!("{} is not revealed", revealed_pointer)))?;
+        values.insert(pointer.as_str().unwrap_or_default().to_owned(), value.clone());
+    }
+    let mut values: Vec<(String, Value)> = pointers
+        .iter()
+        .map(|pointer| (pointer.to_owned(), values.remove(pointer).unwrap_or_default()))
+        .collect();
+    if reveal(values.clone())? != document {
+        return Err(CsdError::VerificationFailed(String::from("the document reveals claims the proof doesn't cover")));
+    }
+
+    let leaves = values.split_off(proof_value.mandatory_pointers.len());
+    let mut config = proof.clone();
+    config.remove("proofValue");
+    let signature = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(&proof_value.signature)
+        .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("signature is not valid base64url: {err}"))))?;
+    verifier
+        .verify(&proof_value.message(&config, &values), &signature)
+        .map_err(|err| CsdError::VerificationFailed(err.to_string()))?;
+
+    let params = generate_params::<C>(
+        proof_value
+            .param_seed
+            .parse()
+            .map_err(|_| CsdError::Core(CsdCoreError::DeserializationError(String::from("param_seed is not a number"))))?,
+    );
+    let accumulator = deserialize_accumulator::<C>(proof_value.accumulator.to_owned())?;
+    let pk = deserialize_pk::<C>(proof_value.pk.to_owned())?;
+    for (pointer, value) in leaves {
+        let witness = deserialize_witness::<C>(proof_value.witnesses[&pointer].to_owned())?;
+        let element = scalar_from_str::<C>(&claim_key(&pointer, &value));
+        if !accumulator.verify_membership(&element, &witness, &pk, &params) {
+            return Err(CsdError::VerificationFailed(format!("witness of {} is not valid", pointer)));
+        }
+    }
+    Ok(document)
+}
+
+/// Splits a secured document into the document and its `proof`.
+fn split_proof(secured: &Value) -> CsdResult<(Value, Map<String, Value>)> {
+    let mut document = secured
+        .as_object()
+        .ok_or(CsdError::DataTypeMismatch(String::from("expected object")))?
+        .clone();
+    match document.remove(PROOF_KEY) {
+        Some(Value::Object(proof)) => Ok((Value::Object(document), proof)),
+        _ => Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("document has no proof")))),
+    }
+}
+
+fn proof_value(proof: &Map<String, Value>) -> CsdResult<String> {
+    proof
+        .get("proofValue")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("proof has no proofValue"))))
+}
+
+/// The values of `document` at `pointers`, in order.
+fn values_at(document: &Value, pointers: impl Iterator<Item = String>) -> CsdResult<Vec<(String, Value)>> {
+    pointers
+        .map(|pointer| match document.pointer(&pointer) {
+            Some(value) if !pointer.is_empty() => Ok((pointer, value.clone())),
+            _ => Err(CsdError::InvalidPath(format!("{} is not in the document", pointer))),
+        })
+        .collect()
+}
+
+/// Builds the document holding `values` at their pointers, with index-keyed objects as arrays.
+fn reveal(values: Vec<(String, Value)>) -> CsdResult<Value> {
+    let mut document = Value::Object(Map::new());
+    for (pointer, value) in values {
+        insert(&mut document, &pointer, value)?;
+    }
+    Ok(arrays(document))
+}
+
+fn is_below(pointer: &str, prefix: &str) -> bool {
+    pointer == prefix || pointer.starts_with(&format!("{}/", prefix))
+}
+
+fn is_mandatory(pointer: &str, mandatory_pointers: &[&str]) -> bool {
+    mandatory_pointers.iter().any(|mandatory| is_below(pointer, mandatory))
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::json;
+
+    use crate::accumulator::generate_secret_key;
+    use crate::csd::csd_error::{CsdCoreError, CsdError};
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+
+    use super::{create_base_proof, derive_proof, verify_derived_proof, CsdProofOptions, PROOF_KEY};
+
+    #[test]
+    fn derived_proofs() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let document = json!({
+            "@context": ["https://www.w3.org/ns/credentials/v2"],
+            "type": ["VerifiableCredential"],
+            "issuer": "did:example:issuer",
+            "credentialSubject": {"name": "Alice", "age": 30, "languages": ["en", "nl", "it"]}
+        });
+        let mut options = CsdProofOptions::<Bn254>::new("did:example:issuer#key-1", generate_secret_key::<Bn254>());
+        options.set_created("2025-01-01T00:00:00Z");
+        let secured = create_base_proof::<Bn254>(&document, &["/@context", "/type", "/issuer"], &*alg.signer_from_jwk(&jwk).unwrap(), &options).unwrap();
+        let verifier = alg.verifier_from_jwk(&jwk).unwrap();
+
+        // Base proofs are for holders only.
+        assert!(matches!(verify_derived_proof::<Bn254>(&secured, &*verifier).unwrap_err(), CsdError::Core(CsdCoreError::DeserializationError(_))));
+
+        let derived = derive_proof(&secured, &["/credentialSubject/name", "/credentialSubject/languages/2"]).unwrap();
+        assert_eq!(
+            verify_derived_proof::<Bn254>(&derived, &*verifier).unwrap(),
+            json!({
+                "@context": ["https://www.w3.org/ns/credentials/v2"],
+                "type": ["VerifiableCredential"],
+                "issuer": "did:example:issuer",
+                "credentialSubject": {"name": "Alice", "languages": ["it"]}
+            })
+        );
+        let everything = derive_proof(&secured, &["/credentialSubject"]).unwrap();
+        let mut without_proof = everything.clone();
+        without_proof.as_object_mut().unwrap().remove(PROOF_KEY);
+        assert_eq!(without_proof, document);
+        assert_eq!(verify_derived_proof::<Bn254>(&everything, &*verifier).unwrap(), document);
+
+        let mut forged = derived.clone();
+        forged["credentialSubject"]["name"] = json!("Mallory");
+        assert!(matches!(verify_derived_proof::<Bn254>(&forged, &*verifier).unwrap_err(), CsdError::VerificationFailed(_)));
+        let mut forged = derived.clone();
+        forged["issuer"] = json!("did:example:mallory");
+        assert!(matches!(verify_derived_proof::<Bn254>(&forged, &*verifier).unwrap_err(), CsdError::VerificationFailed(_)));
+        let mut forged = derived.clone();
+        forged["credentialSubject"]["age"] = json!(30);
+        assert!(matches!(verify_derived_proof::<Bn254>(&forged, &*verifier).unwrap_err(), CsdError::VerificationFailed(_)));
+
+        let other = alg.verifier_from_jwk(&alg.generate_jwk().unwrap()).unwrap();
+        assert!(matches!(verify_derived_proof::<Bn254>(&derived, &*other).unwrap_err(), CsdError::VerificationFailed(_)));
+        assert!(matches!(derive_proof(&secured, &["/credentialSubject/email"]).unwrap_err(), CsdError::InvalidPath(_)));
+    }
+}
@@ -1,24 +1,244 @@
-use std::{str::FromStr, marker::PhantomData, thread};
-use std::thread::JoinHandle;
+use std::{str::FromStr, marker::PhantomData};
 use ark_ec::pairing::Pairing;
+use base64::Engine;
+use vb_accumulator::setup::{PublicKey, SetupParams};
 use serde_json::Map;
 use serde_json::Value;
-use vb_accumulator::positive::{Accumulator};
-use csd::csd_error::CsdError;
+use vb_accumulator::positive::PositiveAccumulator;
+use vb_accumulator::witness::MembershipWitness;
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
 
 use crate::csd;
-use csd::csd_encoder::{ACCUMULATOR_KEY, PARAM_SEED_KEY, PK_KEY, SD_ALG};
-use crate::accumulator::{deserialize_accumulator, deserialize_pk, deserialize_witness, generate_params, scalar_from_str};
+use csd::csd_encoder::{ACCUMULATOR_KEY, PARAM_SEED_KEY, PK_KEY, SD_ALG, SD_DIGESTS_KEY};
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_issuance::{remove_plain_claims, CsdIssuancePolicy};
+use csd::csd_jwk_verifier::{verify_compact_jws, CsdJwkVerifier, JoseHeader};
+use csd::csd_policy::CsdValidationPolicy;
+use csd::csd_registry::CsdIssuerRegistry;
+use csd::csd_vc::{validate_vc, CsdProfile, CsdTypeMetadataStore};
+use csd::csd_jwt::CsdJwt;
+use crate::accumulator::{deserialize_accumulator, deserialize_pk, deserialize_witness, generate_params, scalar_from_str, serialize_pk, verify_witnesses};
+#[cfg(not(target_arch = "wasm32"))]
+use {
+    ark_serialize::CanonicalDeserialize,
+    crate::bls::is_bls_jwk,
+    csd::csd_cwt::CsdCwt,
+    csd::csd_did::CsdKeyResolver,
+    csd::csd_encoder::ISSUER_KEY,
+    csd::csd_jws::{default_allowed_algorithms, verifier_for, verify_jwt},
+    josekit::jwk::Jwk,
+    josekit::jws::{JwsHeader, JwsVerifier},
+    josekit::jwt,
+};
 
 /// Substitutes digests in an SD-JWT object by their corresponding plain text values provided by disclosures.
 pub struct CsdDecoder<'a, C: Pairing> {
+    allowed_algorithms: Vec<String>,
+    threads: Option<usize>,
+    #[cfg(not(target_arch = "wasm32"))]
+    resolver: Option<Box<dyn CsdKeyResolver>>,
+    registry: Option<CsdIssuerRegistry<C>>,
+    accumulator_key: Option<(PublicKey<C>, u64)>,
+    policy: CsdValidationPolicy,
+    issuance_policy: Option<CsdIssuancePolicy>,
+    profile: CsdProfile,
+    type_metadata: Option<CsdTypeMetadataStore>,
     phantom: PhantomData<&'a C>,
 }
 
+impl<C: Pairing> Default for CsdDecoder<'_, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<C: Pairing> CsdDecoder<'_, C> {
-    /// Creates a new [`CsdDecoder`] without any hashers.
+    /// Creates a new [`CsdDecoder`] without any hashers, accepting the
+    /// `default_allowed_algorithms` of [`csd::csd_jws`], or the [`CsdJwkVerifier::ALGORITHMS`] on
+    /// wasm32.
     pub fn new() -> Self {
-        CsdDecoder::<C> { phantom: Default::default() }
+        #[cfg(not(target_arch = "wasm32"))]
+        let allowed_algorithms = default_allowed_algorithms();
+        #[cfg(target_arch = "wasm32")]
+        let allowed_algorithms = CsdJwkVerifier::ALGORITHMS.map(String::from).to_vec();
+        CsdDecoder::<C> {
+            allowed_algorithms,
+            threads: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            resolver: None,
+            registry: None,
+            accumulator_key: None,
+            policy: CsdValidationPolicy::new(),
+            issuance_policy: None,
+            profile: CsdProfile::CsdJwt,
+            type_metadata: None,
+            phantom: Default::default(),
+        }
+    }
+
+    /// Restricts the JWS algorithms accepted by [`CsdDecoder::decode_jwt`], e.g. to `ES384` only.
+    pub fn set_allowed_algorithms(&mut self, allowed_algorithms: Vec<String>) {
+        self.allowed_algorithms = allowed_algorithms;
+    }
+
+    /// Sets the number of threads [`CsdDecoder::validate_object`] splits the witnesses across.
+    /// With `None` (the default) every witness is verified on its own thread. On wasm32, which has
+    /// no threads, the witnesses are always verified one after the other.
+    pub fn set_threads(&mut self, threads: Option<usize>) {
+        self.threads = threads;
+    }
+
+    /// Sets the resolver [`CsdDecoder::decode_resolved`] looks the issuer keys up with.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_resolver(&mut self, resolver: Box<dyn CsdKeyResolver>) {
+        self.resolver = Some(resolver);
+    }
+
+    /// Only accepts tokens of the issuers in `registry`, see [`CsdIssuerRegistry::enforce`].
+    pub fn set_registry(&mut self, registry: CsdIssuerRegistry<C>) {
+        self.registry = Some(registry);
+    }
+
+    /// Only accepts tokens accumulated under the issuer's `public_key` and the parameters of
+    /// `param_seed`, as published in its public JWK, which also verifies the tokens without `pk`.
+    pub fn set_accumulator_key(&mut self, public_key: PublicKey<C>, param_seed: u64) {
+        self.accumulator_key = Some((public_key, param_seed));
+    }
+
+    /// Sets the policy [`CsdDecoder::decode_jwt`] checks the time, issuer and audience claims with.
+    pub fn set_policy(&mut self, policy: CsdValidationPolicy) {
+        self.policy = policy;
+    }
+
+    /// Requires the issuer JWT to follow `issuance_policy`, see [`CsdIssuancePolicy::enforce`].
+    pub fn set_issuance_policy(&mut self, issuance_policy: CsdIssuancePolicy) {
+        self.issuance_policy = Some(issuance_policy);
+    }
+
+    /// Requires the issuer JWT to follow `profile`, see [`validate_vc`].
+    pub fn set_profile(&mut self, profile: CsdProfile) {
+        self.profile = profile;
+    }
+
+    /// Sets the type metadata the `vct` of SD-JWT VCs is looked up in; tokens of unknown types are
+    /// then rejected.
+    pub fn set_type_metadata(&mut self, type_metadata: CsdTypeMetadataStore) {
+        self.type_metadata = Some(type_metadata);
+    }
+
+    pub fn profile(&self) -> CsdProfile {
+        self.profile
+    }
+
+    pub fn type_metadata(&self) -> Option<&CsdTypeMetadataStore> {
+        self.type_metadata.as_ref()
+    }
+
+    pub fn policy(&self) -> &CsdValidationPolicy {
+        &self.policy
+    }
+
+    /// Verifies the issuer JWT of `csd_jwt` with `verifier`, enforcing the algorithm allow-list
+    /// the validation and issuance policies and the profile, and decodes its payload.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn decode_jwt(
+        &self,
+        csd_jwt: &CsdJwt,
+        verifier: &dyn JwsVerifier,
+    ) -> Result<(Map<String, Value>, JwsHeader), CsdError> {
+        let (payload, header) = verify_jwt(csd_jwt, verifier, &self.allowed_algorithms)?;
+        Ok((self.decode_verified(header.token_type(), payload.claims_set())?, header))
+    }
+
+    /// Same as [`CsdDecoder::decode_jwt`] with a [`CsdJwkVerifier`], which builds without OpenSSL.
+    pub fn decode_jwt_with_jwk(
+        &self,
+        csd_jwt: &CsdJwt,
+        verifier: &CsdJwkVerifier,
+    ) -> Result<(Map<String, Value>, JoseHeader), CsdError> {
+        let (header, payload) = verify_compact_jws(&csd_jwt.jwt, verifier, &self.allowed_algorithms)?;
+        let object = self.decode_verified(header.get("typ").and_then(Value::as_str), &payload)?;
+        Ok((object, header))
+    }
+
+    /// Checks the payload of an issuer JWT whose signature has been verified against the
+    /// validation and issuance policies and the profile, given its `typ` header, and decodes it.
+    pub fn decode_verified(&self, typ: Option<&str>, payload: &Map<String, Value>) -> Result<Map<String, Value>, CsdError> {
+        self.policy.validate_claims(payload)?;
+        if let Some(issuance_policy) = &self.issuance_policy {
+            issuance_policy.enforce(payload)?;
+        }
+        if self.profile == CsdProfile::SdJwtVc {
+            validate_vc(typ, payload, self.type_metadata.as_ref())?;
+        }
+        self.decode(payload)
+    }
+
+    /// Same as [`CsdDecoder::decode_jwt`] for the COSE_Sign1 of a [`CsdCwt`]. The SD-JWT VC
+    /// profile has no CWT form.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn decode_cwt(&self, cwt: &CsdCwt, verifier: &dyn JwsVerifier) -> Result<Map<String, Value>, CsdError> {
+        if self.profile == CsdProfile::SdJwtVc {
+            return Err(CsdError::ClaimValidation(String::from("SD-JWT VCs have no CWT form")));
+        }
+        let payload = cwt.verify(verifier, &self.allowed_algorithms)?;
+        self.policy.validate_claims(&payload)?;
+        if let Some(issuance_policy) = &self.issuance_policy {
+            issuance_policy.enforce(&payload)?;
+        }
+        self.decode(&payload)
+    }
+
+    /// Verifies the issuer JWT of `csd_jwt` against the keys of the issuer's DID document instead
+    /// of trusting the token: the JWS key is the verification method named by the `kid` header
+    /// (or the first non-accumulator key of the `iss` DID) and the accumulator public key is the
+    /// document's key on the curve `C`. A `pk` carried by the token must match the resolved one.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::DidResolution`] if no resolver is set, the token names no DID, the `kid`
+    /// and `iss` DIDs differ or the document lacks one of the keys.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn decode_resolved(&self, csd_jwt: &CsdJwt) -> Result<(Map<String, Value>, JwsHeader), CsdError> {
+        let resolver = self.resolver
+            .as_ref()
+            .ok_or(CsdError::DidResolution(String::from("no resolver configured")))?;
+        let header = jwt::decode_header(&csd_jwt.jwt).map_err(|err| CsdError::Jws(err.to_string()))?;
+        let payload = unverified_payload(&csd_jwt.jwt)?;
+
+        let key_id = header.claim("kid").and_then(Value::as_str);
+        let issuer = payload.get(ISSUER_KEY).and_then(Value::as_str);
+        let did = match (key_id.map(|key_id| key_id.split('#').next().unwrap_or(key_id)), issuer) {
+            (Some(kid_did), Some(issuer)) if kid_did != issuer => {
+                return Err(CsdError::DidResolution(format!("kid {} does not belong to issuer {}", kid_did, issuer)))
+            }
+            (Some(did), _) | (None, Some(did)) => did,
+            (None, None) => return Err(CsdError::DidResolution(String::from("token has neither kid nor iss"))),
+        };
+        let document = resolver.resolve(did)?;
+
+        let method = match key_id {
+            Some(key_id) => document.verification_method(key_id),
+            None => document.verification_methods.iter().find(|method| accumulator_key::<C>(&method.jwk).is_none()),
+        }.ok_or(CsdError::DidResolution(format!("{} has no signing key", did)))?;
+        let alg = header
+            .claim("alg")
+            .and_then(Value::as_str)
+            .ok_or(CsdError::Jws(String::from("missing alg header")))?;
+        let verifier = verifier_for(alg, &method.jwk)?;
+        let (mut object, header) = self.decode_jwt(csd_jwt, &*verifier)?;
+
+        let pk = document.verification_methods
+            .iter()
+            .find_map(|method| accumulator_key::<C>(&method.jwk))
+            .ok_or(CsdError::DidResolution(format!("{} has no accumulator key", did)))?;
+        if let Some(embedded) = object.get(PK_KEY) {
+            let embedded = embedded.as_str().and_then(|embedded| deserialize_pk::<C>(embedded.to_owned()).ok());
+            if embedded.as_ref() != Some(&pk) {
+                return Err(CsdError::VerificationFailed(String::from("the token public key is not the issuer's")));
+            }
+        }
+        object.insert(String::from(PK_KEY), Value::String(serialize_pk::<C>(pk)));
+        Ok((object, header))
     }
 
     /// Decodes an SD-JWT `object` containing by Substituting the digests with their corresponding
@@ -27,8 +247,76 @@ impl<C: Pairing> CsdDecoder<'_, C> {
         &self,
         object: &Map<String, Value>,
     ) -> Result<Map<String, Value>, CsdError> {
-        // Decode the object recursively.
-        self.decode_object(object)
+        // Plain claims are kept as issued, the rest is decoded recursively.
+        let mut accumulated = object.clone();
+        remove_plain_claims(&mut accumulated)?;
+        let mut output = self.decode_object(&accumulated)?;
+        for (key, value) in object {
+            if !accumulated.contains_key(key) {
+                output.insert(key.to_owned(), value.clone());
+            }
+        }
+        Ok(output)
+    }
+
+    /// Decodes a CSD-JWT `object` and adds the claims of `disclosures` to it as `key::value`
+    /// entries mapped to their witnesses, so that [`CsdDecoder::validate_object`] verifies them.
+    pub fn decode_disclosures(
+        &self,
+        object: &Map<String, Value>,
+        disclosures: &[CsdDisclosure],
+    ) -> Result<Map<String, Value>, CsdError> {
+        let mut output = self.decode(object)?;
+        for disclosure in disclosures {
+            let (claim_key, witness) = match (disclosure.claim_key(), &disclosure.witness) {
+                (Some(claim_key), Some(witness)) => (claim_key, witness),
+                _ => return Err(CsdError::Core(CsdCoreError::InvalidDisclosure(format!("disclosure {} has no claim name or witness", disclosure))))
+            };
+            if output.contains_key(&claim_key) {
+                return Err(CsdError::ClaimCollisionError(claim_key));
+            }
+            output.insert(claim_key, Value::String(witness.to_owned()));
+        }
+        Ok(output)
+    }
+
+    /// Verifies `disclosures` against the decoded issuer `object` in one pass, the salted ones by
+    /// finding their digest in `_sd` and the others by their witnesses, and returns the disclosed
+    /// claims along with the always disclosed ones.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::VerificationFailed`] if a disclosed claim is neither accumulated nor
+    /// salted, and [`CsdError::DuplicateDigestError`] if a salted claim is disclosed twice.
+    pub fn verify_disclosures(&self, object: &Map<String, Value>, disclosures: Vec<CsdDisclosure>) -> CsdResult<Map<String, Value>> {
+        let digests = salted_digests(object)?;
+        let mut salted: Vec<CsdDisclosure> = vec![];
+        let mut accumulated: Vec<CsdDisclosure> = vec![];
+        for disclosure in disclosures {
+            let digest = disclosure.digest();
+            if !digests.contains(&digest) {
+                accumulated.push(disclosure);
+            } else if salted.iter().any(|salted| salted.digest() == digest) {
+                return Err(CsdError::DuplicateDigestError(digest));
+            } else {
+                salted.push(disclosure.into_salted());
+            }
+        }
+
+        let decoded = self.decode_disclosures(object, &accumulated)?;
+        if !self.validate_object(decoded)? {
+            return Err(CsdError::VerificationFailed(String::from("a disclosed claim is not accumulated")));
+        }
+
+        let mut claims = remove_plain_claims(&mut object.clone())?;
+        for disclosure in accumulated.into_iter().chain(salted) {
+            if let Some(name) = disclosure.claim_name {
+                if claims.contains_key(&name) {
+                    return Err(CsdError::ClaimCollisionError(name));
+                }
+                claims.insert(name, disclosure.claim_value);
+            }
+        }
+        Ok(claims)
     }
 
     fn decode_object(
@@ -54,7 +342,16 @@ impl<C: Pairing> CsdDecoder<'_, C> {
         Ok(output)
     }
 
-    pub(crate) fn validate_object(&self, mut object: Map<String, Value>) -> Result<bool, CsdError> {
+    /// Verifies the witness of every `key::value` claim of a decoded `object` against the
+    /// accumulator, public key and parameters it carries.
+    pub fn validate_object(&self, mut object: Map<String, Value>) -> Result<bool, CsdError> {
+        if let Some(registry) = &self.registry {
+            registry.enforce(&mut object)?;
+        }
+        if let Some((public_key, param_seed)) = &self.accumulator_key {
+            enforce_accumulator_key(&mut object, public_key, *param_seed)?;
+        }
+
         let (_, accumulator) = match object.get_key_value(ACCUMULATOR_KEY) {
             Some(result) => result,
             None => return Err(CsdError::Unspecified(String::from("No accumulator found!")))
@@ -75,7 +372,7 @@ impl<C: Pairing> CsdDecoder<'_, C> {
         };
         let params_seed = match params_seed {
             Ok(params_seed) => params_seed,
-            Err(err) => return Err(CsdError::Unspecified(String::from(format!("Param seed value is a string but can't be converted to u64! {:?}", err))))
+            Err(err) => return Err(CsdError::Unspecified(format!("Param seed value is a string but can't be converted to u64! {:?}", err)))
         };
         let params = generate_params::<C>(params_seed);
         object.remove(PARAM_SEED_KEY);
@@ -88,7 +385,7 @@ impl<C: Pairing> CsdDecoder<'_, C> {
             Value::String(val) => val.to_owned(),
             _ => return Err(CsdError::Unspecified(String::from("Public key value found is not a string!")))
         };
-        let pk = deserialize_pk::<C>(pk);
+        let pk = deserialize_pk::<C>(pk)?;
         object.remove(PK_KEY);
 
         let (_, _) = match object.get_key_value(SD_ALG) {
@@ -96,29 +393,118 @@ impl<C: Pairing> CsdDecoder<'_, C> {
             None => return Err(CsdError::Unspecified(String::from("No Selective Disclosure algorithm found!")))
         };
         object.remove(SD_ALG);
+        remove_plain_claims(&mut object)?;
 
-        let accumulator= deserialize_accumulator::<C>(accumulator);
-        let mut results:  Vec<JoinHandle<bool>> = vec![];
-        let mut i: usize = 0;
-        for (key, value) in object {
-            let thread_acc = accumulator.clone();
+        let accumulator= deserialize_accumulator::<C>(accumulator)?;
+        let mut members = vec![];
+        for (key, value) in object.into_iter() {
             let element = scalar_from_str::<C>(key.clone().as_str());
             let witness = deserialize_witness::<C>(match value {
                 Value::String(val) => val.to_owned(),
-                _ => return Err(CsdError::Unspecified(String::from(format!("Witness [{:?}] not a string", value))))
-            });
+                _ => return Err(CsdError::Unspecified(format!("Witness [{:?}] not a string", value)))
+            })?;
+            members.push((element, witness));
+        }
+
+        self.verify_members(&accumulator, members, &pk, &params)
+    }
+
+    /// Verifies the witnesses of `members` across [`CsdDecoder::set_threads`] threads.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn verify_members(
+        &self,
+        accumulator: &PositiveAccumulator<C>,
+        members: Vec<(C::ScalarField, MembershipWitness<C::G1Affine>)>,
+        pk: &PublicKey<C>,
+        params: &SetupParams<C>,
+    ) -> Result<bool, CsdError> {
+        let chunk_size = match self.threads {
+            Some(threads) => members.len().div_ceil(threads.max(1)).max(1),
+            None => 1,
+        };
+        let mut results: Vec<std::thread::JoinHandle<bool>> = vec![];
+        for chunk in members.chunks(chunk_size) {
+            let thread_acc = accumulator.clone();
+            let chunk = chunk.to_vec();
             let thread_pk = pk.clone();
             let thread_params = params.clone();
 
-            results.insert(i, thread::spawn(move || { thread_acc.verify_membership(&element, &witness, &thread_pk, &thread_params) }));
-            i += 1;
-            // println!("Key {} verified!", key);
+            results.push(std::thread::spawn(move || {
+                verify_witnesses(&thread_acc, &chunk, &thread_pk, &thread_params)
+            }));
         }
 
+        let mut valid = true;
         for result in results {
-            assert!(result.join().unwrap());
+            valid &= result.join().map_err(|_| CsdError::Unspecified(String::from("Witness verification thread panicked!")))?;
         }
 
-        Ok(true)
+        Ok(valid)
+    }
+
+    /// Verifies the witnesses of `members` one after the other, as wasm32 can't spawn threads.
+    #[cfg(target_arch = "wasm32")]
+    fn verify_members(
+        &self,
+        accumulator: &PositiveAccumulator<C>,
+        members: Vec<(C::ScalarField, MembershipWitness<C::G1Affine>)>,
+        pk: &PublicKey<C>,
+        params: &SetupParams<C>,
+    ) -> Result<bool, CsdError> {
+        Ok(verify_witnesses(accumulator, &members, pk, params))
+    }
+}
+
+/// The digests of the salted claims of a decoded issuer `object`, see [`SD_DIGESTS_KEY`].
+pub(crate) fn salted_digests(object: &Map<String, Value>) -> CsdResult<Vec<String>> {
+    match object.get(SD_DIGESTS_KEY) {
+        None => Ok(vec![]),
+        Some(Value::Array(digests)) => digests
+            .iter()
+            .map(|digest| digest.as_str().map(String::from))
+            .collect::<Option<Vec<String>>>()
+            .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("{} holds a non-string digest", SD_DIGESTS_KEY)))),
+        Some(_) => Err(CsdError::Core(CsdCoreError::DeserializationError(format!("{} is not an array", SD_DIGESTS_KEY)))),
+    }
+}
+
+/// The payload of a compact JWS, before its signature is verified.
+pub(crate) fn unverified_payload(jwt: &str) -> Result<Map<String, Value>, CsdError> {
+    let payload = jwt
+        .split('.')
+        .nth(1)
+        .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("JWT has no payload"))))?;
+    let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(err.to_string())))?;
+    serde_json::from_slice(&payload).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(err.to_string())))
+}
+
+/// Checks the `pk` and `param_seed` of a decoded `object` against the issuer's accumulator key,
+/// filling in `public_key` when the token doesn't carry one.
+fn enforce_accumulator_key<C: Pairing>(object: &mut Map<String, Value>, public_key: &PublicKey<C>, param_seed: u64) -> CsdResult<()> {
+    if let Some(embedded) = object.get(PK_KEY) {
+        let embedded = embedded.as_str().and_then(|embedded| deserialize_pk::<C>(embedded.to_owned()).ok());
+        if embedded.as_ref() != Some(public_key) {
+            return Err(CsdError::VerificationFailed(String::from("the token public key is not the issuer's")));
+        }
+    }
+    if object.get(PARAM_SEED_KEY).and_then(Value::as_str) != Some(param_seed.to_string().as_str()) {
+        return Err(CsdError::VerificationFailed(String::from("the token parameters are not the issuer's")));
+    }
+    object.insert(String::from(PK_KEY), Value::String(serialize_pk::<C>(public_key.clone())));
+    Ok(())
+}
+
+/// The accumulator public key on the curve `C` held by a JWK in the format of
+/// [`crate::csd::csd_issuer::CsdIssuer::accumulator_jwk`], if any.
+#[cfg(not(target_arch = "wasm32"))]
+fn accumulator_key<C: Pairing>(jwk: &Jwk) -> Option<PublicKey<C>> {
+    if !is_bls_jwk(jwk) || jwk.algorithm() != Some(std::any::type_name::<C>()) {
+        return None;
     }
+    let x = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(jwk.parameter("x")?.as_str()?)
+        .ok()?;
+    PublicKey::<C>::deserialize_compressed(&*x).ok()
 }
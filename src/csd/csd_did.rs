@@ -0,0 +1,379 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use josekit::jwk::Jwk;
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcPoint};
+use openssl::nid::Nid;
+use serde_json::{Map, Value};
+
+use crate::bls::is_bls_jwk;
+use crate::csd;
+use csd::csd_error::{CsdError, CsdResult};
+
+const DID_KEY_PREFIX: &str = "did:key:";
+const DID_JWK_PREFIX: &str = "did:jwk:";
+const DID_WEB_PREFIX: &str = "did:web:";
+
+/// Multicodec prefixes (unsigned varints) of the public keys supported by `did:key`, with the
+/// JWK `kty` and `crv` they map to.
+const MULTICODECS: [(&[u8], &str, &str); 4] = [
+    (&[0xed, 0x01], "OKP", "Ed25519"),
+    (&[0xeb, 0x01], "OKP", "BLS12381G2"),
+    (&[0x80, 0x24], "EC", "P-256"),
+    (&[0x81, 0x24], "EC", "P-384"),
+];
+
+/// A verification method of a DID document, with its key as a JWK.
+#[derive(Debug, Clone)]
+pub struct CsdVerificationMethod {
+    /// The absolute DID URL of the method, e.g. `did:web:example.com#key-1`.
+    pub id: String,
+    pub jwk: Jwk,
+}
+
+/// The part of a DID document needed to verify CSD-JWTs: its verification methods.
+#[derive(Debug, Clone)]
+pub struct CsdDidDocument {
+    pub id: String,
+    pub verification_methods: Vec<CsdVerificationMethod>,
+}
+
+impl CsdDidDocument {
+    /// Parses a JSON DID document. Verification methods need a `publicKeyJwk` or a
+    /// `publicKeyMultibase` in the `did:key` format; relative ids (`#key-1`) are made absolute.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::DidResolution`] if the document or one of its keys is malformed.
+    pub fn from_json(document: &Value) -> CsdResult<Self> {
+        let id = document
+            .get("id")
+            .and_then(Value::as_str)
+            .ok_or(CsdError::DidResolution(String::from("DID document has no id")))?
+            .to_string();
+
+        let mut verification_methods = vec![];
+        let methods = document.get("verificationMethod").and_then(Value::as_array).cloned().unwrap_or_default();
+        for method in methods {
+            let method_id = method
+                .get("id")
+                .and_then(Value::as_str)
+                .ok_or(CsdError::DidResolution(String::from("verification method has no id")))?;
+            let method_id = if method_id.starts_with('#') { format!("{}{}", id, method_id) } else { method_id.to_string() };
+
+            let jwk = match (method.get("publicKeyJwk"), method.get("publicKeyMultibase").and_then(Value::as_str)) {
+                (Some(Value::Object(jwk)), _) => Jwk::from_map(jwk.clone()).map_err(|err| CsdError::DidResolution(err.to_string()))?,
+                (_, Some(multibase)) => jwk_from_multibase(multibase)?,
+                _ => return Err(CsdError::DidResolution(format!("verification method {} has no supported key", method_id))),
+            };
+            verification_methods.push(CsdVerificationMethod { id: method_id, jwk });
+        }
+
+        Ok(Self { id, verification_methods })
+    }
+
+    /// Returns the verification method with the absolute or relative id `id`.
+    pub fn verification_method(&self, id: &str) -> Option<&CsdVerificationMethod> {
+        let id = if id.starts_with('#') { format!("{}{}", self.id, id) } else { id.to_string() };
+        self.verification_methods.iter().find(|method| method.id == id)
+    }
+}
+
+/// Resolves DIDs to their documents, so verifiers don't have to trust keys carried by tokens.
+pub trait CsdKeyResolver {
+    /// Resolves `did` (without fragment) to its DID document.
+    fn resolve(&self, did: &str) -> CsdResult<CsdDidDocument>;
+}
+
+/// Resolver for `did:key`, whose document is derived from the multibase key in the DID.
+#[derive(Debug, Clone, Default)]
+pub struct DidKeyResolver;
+
+impl CsdKeyResolver for DidKeyResolver {
+    fn resolve(&self, did: &str) -> CsdResult<CsdDidDocument> {
+        let multibase = did
+            .strip_prefix(DID_KEY_PREFIX)
+            .ok_or(CsdError::DidResolution(format!("{} is not a did:key", did)))?;
+        Ok(CsdDidDocument {
+            id: did.to_string(),
+            verification_methods: vec![CsdVerificationMethod {
+                id: format!("{}#{}", did, multibase),
+                jwk: jwk_from_multibase(multibase)?,
+            }],
+        })
+    }
+}
+
+/// Resolver for `did:jwk`, whose document is derived from the base64url JWK in the DID.
+#[derive(Debug, Clone, Default)]
+pub struct DidJwkResolver;
+
+impl CsdKeyResolver for DidJwkResolver {
+    fn resolve(&self, did: &str) -> CsdResult<CsdDidDocument> {
+        let encoded = did
+            .strip_prefix(DID_JWK_PREFIX)
+            .ok_or(CsdError::DidResolution(format!("{} is not a did:jwk", did)))?;
+        let decoded = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|err| CsdError::DidResolution(err.to_string()))?;
+        let jwk = Jwk::from_bytes(decoded).map_err(|err| CsdError::DidResolution(err.to_string()))?;
+        Ok(CsdDidDocument {
+            id: did.to_string(),
+            verification_methods: vec![CsdVerificationMethod { id: format!("{}#0", did), jwk }],
+        })
+    }
+}
+
+/// Resolver for `did:web`, reading the DID documents from local files instead of fetching them.
+#[derive(Debug, Clone, Default)]
+pub struct DidWebResolver {
+    /// Document URL, e.g. `https://example.com/.well-known/did.json`, to file.
+    documents: HashMap<String, PathBuf>,
+}
+
+impl DidWebResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves the document at `url` from `path`.
+    pub fn insert(&mut self, url: impl Into<String>, path: impl Into<PathBuf>) {
+        self.documents.insert(url.into(), path.into());
+    }
+
+    /// Loads a JSON object mapping document URLs to files; relative paths are relative to the
+    /// map file.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::DidResolution`] if the map can't be read or isn't such an object.
+    pub fn from_file_map(map: &Path) -> CsdResult<Self> {
+        let contents = fs::read_to_string(map).map_err(|err| CsdError::DidResolution(format!("{}: {}", map.display(), err)))?;
+        let entries: Map<String, Value> = serde_json::from_str(&contents).map_err(|err| CsdError::DidResolution(err.to_string()))?;
+        let base = map.parent().unwrap_or(Path::new(""));
+
+        let mut resolver = Self::new();
+        for (url, path) in entries {
+            let path = path.as_str().ok_or(CsdError::DidResolution(format!("file of {} is not a string", url)))?;
+            resolver.insert(url, base.join(path));
+        }
+        Ok(resolver)
+    }
+
+    /// The URL of the document of `did`, as defined by the did:web method.
+    pub fn document_url(did: &str) -> CsdResult<String> {
+        let id = did
+            .strip_prefix(DID_WEB_PREFIX)
+            .ok_or(CsdError::DidResolution(format!("{} is not a did:web", did)))?;
+        let mut segments = id.split(':').map(|segment| segment.replace("%3A", ":").replace("%3a", ":"));
+        let host = segments.next().filter(|host| !host.is_empty()).ok_or(CsdError::DidResolution(format!("{} has no host", did)))?;
+        let path = segments.collect::<Vec<String>>();
+
+        Ok(if path.is_empty() {
+            format!("https://{}/.well-known/did.json", host)
+        } else {
+            format!("https://{}/{}/did.json", host, path.join("/"))
+        })
+    }
+}
+
+impl CsdKeyResolver for DidWebResolver {
+    fn resolve(&self, did: &str) -> CsdResult<CsdDidDocument> {
+        let url = Self::document_url(did)?;
+        let path = self.documents.get(&url).ok_or(CsdError::DidResolution(format!("no local document for {}", url)))?;
+        let contents = fs::read_to_string(path).map_err(|err| CsdError::DidResolution(format!("{}: {}", path.display(), err)))?;
+        let document: Value = serde_json::from_str(&contents).map_err(|err| CsdError::DidResolution(err.to_string()))?;
+
+        let document = CsdDidDocument::from_json(&document)?;
+        if document.id != did {
+            return Err(CsdError::DidResolution(format!("document of {} has id {}", did, document.id)));
+        }
+        Ok(document)
+    }
+}
+
+/// Dispatches on the DID method: `did:key` and `did:jwk` are always supported, `did:web` once a
+/// [`DidWebResolver`] is set.
+#[derive(Debug, Clone, Default)]
+pub struct CsdDidResolver {
+    web: Option<DidWebResolver>,
+}
+
+impl CsdDidResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_web_resolver(&mut self, web: DidWebResolver) {
+        self.web = Some(web);
+    }
+}
+
+impl CsdKeyResolver for CsdDidResolver {
+    fn resolve(&self, did: &str) -> CsdResult<CsdDidDocument> {
+        if did.starts_with(DID_KEY_PREFIX) {
+            DidKeyResolver.resolve(did)
+        } else if did.starts_with(DID_JWK_PREFIX) {
+            DidJwkResolver.resolve(did)
+        } else if did.starts_with(DID_WEB_PREFIX) {
+            match &self.web {
+                Some(web) => web.resolve(did),
+                None => Err(CsdError::DidResolution(String::from("no did:web resolver configured"))),
+            }
+        } else {
+            Err(CsdError::DidResolution(format!("unsupported DID method: {}", did)))
+        }
+    }
+}
+
+///
+/// did_jwk(jwk: &Jwk) -> String
+///
+/// The `did:jwk` of the public part of `jwk`.
+///
+pub fn did_jwk(jwk: &Jwk) -> CsdResult<String> {
+    let public_jwk = public_jwk(jwk)?;
+    let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(public_jwk.to_string());
+    Ok(format!("{}{}", DID_JWK_PREFIX, encoded))
+}
+
+///
+/// did_key(jwk: &Jwk) -> CsdResult<String>
+///
+/// The `did:key` of `jwk`, for Ed25519, P-256, P-384 and BLS12-381 G2 keys.
+///
+pub fn did_key(jwk: &Jwk) -> CsdResult<String> {
+    let (prefix, _, _) = MULTICODECS
+        .iter()
+        .find(|(_, kty, crv)| jwk.key_type() == *kty && jwk.curve() == Some(*crv))
+        .ok_or(CsdError::DidResolution(format!("no did:key encoding for {:?} keys", jwk.curve())))?;
+
+    let x = jwk_coordinate(jwk, "x")?;
+    let mut key = prefix.to_vec();
+    if jwk.key_type() == "EC" {
+        // SEC1 compressed point: the parity of y, then x.
+        let y = jwk_coordinate(jwk, "y")?;
+        key.push(if y.last().is_some_and(|byte| byte & 1 == 1) { 0x03 } else { 0x02 });
+    }
+    key.extend(x);
+    Ok(format!("{}{}", DID_KEY_PREFIX, multibase::encode(multibase::Base::Base58Btc, key)))
+}
+
+/// Public part of a JWK; `josekit` only knows how to strip the private parameters of its own
+/// key types, so the BLS ones are left as they are.
+fn public_jwk(jwk: &Jwk) -> CsdResult<Jwk> {
+    match jwk.key_type() {
+        _ if is_bls_jwk(jwk) => {
+            let mut public_jwk = jwk.clone();
+            public_jwk.set_parameter("d", None).map_err(|err| CsdError::DidResolution(err.to_string()))?;
+            Ok(public_jwk)
+        }
+        _ => jwk.to_public_key().map_err(|err| CsdError::DidResolution(err.to_string())),
+    }
+}
+
+fn jwk_coordinate(jwk: &Jwk, name: &str) -> CsdResult<Vec<u8>> {
+    match jwk.parameter(name) {
+        Some(Value::String(value)) => base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(value)
+            .map_err(|err| CsdError::DidResolution(err.to_string())),
+        _ => Err(CsdError::DidResolution(format!("JWK has no {} parameter", name))),
+    }
+}
+
+/// Decodes a multicodec public key in multibase into a JWK.
+fn jwk_from_multibase(multibase: &str) -> CsdResult<Jwk> {
+    let (_, bytes) = multibase::decode(multibase).map_err(|err| CsdError::DidResolution(err.to_string()))?;
+    let (prefix, kty, crv) = MULTICODECS
+        .iter()
+        .find(|(prefix, _, _)| bytes.starts_with(prefix))
+        .ok_or(CsdError::DidResolution(String::from("unsupported multicodec key type")))?;
+    let key = &bytes[prefix.len()..];
+
+    let mut jwk = Jwk::new(kty);
+    jwk.set_curve(*crv);
+    let x = if *kty == "EC" {
+        let (x, y) = decompress_ec_point(crv, key)?;
+        jwk.set_parameter("y", Some(Value::String(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(y))))
+            .map_err(|err| CsdError::DidResolution(err.to_string()))?;
+        x
+    } else {
+        key.to_vec()
+    };
+    jwk.set_parameter("x", Some(Value::String(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(x))))
+        .map_err(|err| CsdError::DidResolution(err.to_string()))?;
+    Ok(jwk)
+}
+
+/// Affine coordinates of a SEC1 compressed point, padded to the field size.
+fn decompress_ec_point(crv: &str, compressed: &[u8]) -> CsdResult<(Vec<u8>, Vec<u8>)> {
+    let (nid, size) = match crv {
+        "P-256" => (Nid::X9_62_PRIME256V1, 32),
+        "P-384" => (Nid::SECP384R1, 48),
+        _ => return Err(CsdError::DidResolution(format!("unsupported curve {}", crv))),
+    };
+    let openssl_err = |err: openssl::error::ErrorStack| CsdError::DidResolution(err.to_string());
+
+    let group = EcGroup::from_curve_name(nid).map_err(openssl_err)?;
+    let mut ctx = BigNumContext::new().map_err(openssl_err)?;
+    let point = EcPoint::from_bytes(&group, compressed, &mut ctx).map_err(openssl_err)?;
+    let (mut x, mut y) = (BigNum::new().map_err(openssl_err)?, BigNum::new().map_err(openssl_err)?);
+    point.affine_coordinates(&group, &mut x, &mut y, &mut ctx).map_err(openssl_err)?;
+
+    Ok((x.to_vec_padded(size).map_err(openssl_err)?, y.to_vec_padded(size).map_err(openssl_err)?))
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use serde_json::json;
+
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+
+    use super::{did_jwk, did_key, CsdDidResolver, CsdKeyResolver, DidWebResolver};
+
+    #[test]
+    fn did_key_and_jwk_round_trip() {
+        let resolver = CsdDidResolver::new();
+        for alg in [CsdJwsAlgorithm::ES256, CsdJwsAlgorithm::ES384, CsdJwsAlgorithm::EdDSA] {
+            let jwk = alg.generate_jwk().unwrap();
+            let public_jwk = jwk.to_public_key().unwrap();
+
+            for did in [did_key(&jwk).unwrap(), did_jwk(&jwk).unwrap()] {
+                let document = resolver.resolve(&did).unwrap();
+                let method = &document.verification_methods[0];
+                assert!(method.id.starts_with(&did));
+                assert_eq!(method.jwk.parameter("x"), public_jwk.parameter("x"));
+                assert_eq!(method.jwk.parameter("y"), public_jwk.parameter("y"));
+            }
+        }
+    }
+
+    #[test]
+    fn did_web_file_map() {
+        assert_eq!(DidWebResolver::document_url("did:web:example.com").unwrap(), "https://example.com/.well-known/did.json");
+        assert_eq!(DidWebResolver::document_url("did:web:localhost%3A8443:issuers:1").unwrap(), "https://localhost:8443/issuers/1/did.json");
+
+        let dir = std::env::temp_dir().join(format!("csd-did-web-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let jwk = CsdJwsAlgorithm::EdDSA.generate_jwk().unwrap().to_public_key().unwrap();
+        let document = json!({
+            "id": "did:web:example.com",
+            "verificationMethod": [{"id": "#key-1", "type": "JsonWebKey2020", "publicKeyJwk": jwk.as_ref()}]
+        });
+        fs::write(dir.join("example.json"), document.to_string()).unwrap();
+        fs::write(dir.join("map.json"), json!({"https://example.com/.well-known/did.json": "example.json"}).to_string()).unwrap();
+
+        let mut resolver = CsdDidResolver::new();
+        assert!(matches!(resolver.resolve("did:web:example.com").unwrap_err(), CsdError::DidResolution(_)));
+        resolver.set_web_resolver(DidWebResolver::from_file_map(&dir.join("map.json")).unwrap());
+        let document = resolver.resolve("did:web:example.com").unwrap();
+        assert_eq!(document.verification_method("#key-1").unwrap().jwk.parameter("x"), jwk.parameter("x"));
+        assert!(resolver.resolve("did:web:other.com").is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,25 +1,42 @@
 use std::marker::PhantomData;
 use std::str::FromStr;
 use ark_ec::pairing::Pairing;
+use base64::Engine;
 use json_pointer::JsonPointer;
 use serde_json::{
     Map,
     Value
 };
 use vb_accumulator::positive::Accumulator;
+use vb_accumulator::setup::SecretKey;
 use csd::csd_disclosure::CsdDisclosure;
-use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+use csd::csd_issuance::{check_plain_claim_name, CsdIssuancePolicy, PLAIN_CLAIMS_KEY};
+use csd::csd_policy::JWT_CLAIMS;
 
 use crate::{csd, accumulator};
-use crate::accumulator::{scalar_from_str, serialize_accumulator, serialize_pk, serialize_witness};
+use crate::accumulator::{canonical_value, claim_key, generate_secret_key, scalar_from_str, serialize_accumulator_with, serialize_pk_with, serialize_witness_with, PointEncoding};
 
 pub(crate) const SD_ALG: &str = "_sd_alg";
-pub const HEADER_TYP: &str = "sd-jwt";
+pub const HEADER_TYP: &str = "csd-jwt";
 pub const ACCUMULATOR_KEY: &str = "accumulator";
 pub const PK_KEY: &str = "pk";
 pub const PARAM_SEED_KEY: &str = "param_seed";
-const DEFAULT_KEY_SEED: u64 = 0u64;
-const DEFAULT_PARAM_SEED: u64 = 1u64;
+/// The issuer DID, whose document holds the accumulator public key.
+pub const ISSUER_KEY: &str = "iss";
+/// The credential type, always disclosed.
+pub const CREDENTIAL_TYPE_KEY: &str = "vct";
+/// The SD-JWT digests of the salted claims, see [`CsdEncoder::set_salted_claims`].
+pub const SD_DIGESTS_KEY: &str = "_sd";
+/// Bytes of randomness in the salt of a salted claim, as SD-JWT recommends.
+const SALT_SIZE: usize = 16;
+
+/// An accumulated claim: its name, value and serialized witness.
+type AccumulatedClaim = (String, Value, String);
+/// Appended to the `_sd_alg` value of tokens whose witnesses can only be verified with the
+/// accumulator's secret key (see [`crate::csd::csd_keyed_decoder::CsdKeyedDecoder`]).
+pub const KEYED_SD_ALG_SUFFIX: &str = "+kv";
+pub(crate) const DEFAULT_PARAM_SEED: u64 = 1u64;
 
 
 /// Transforms a JSON object into an SD-JWT object by substituting selected values
@@ -28,6 +45,14 @@ pub struct CsdEncoder<'a, C: Pairing> {
     /// The object in JSON format.
     pub(crate) object: Value,
     final_object: Map<String, Value>,
+    secret_key: SecretKey<C::ScalarField>,
+    param_seed: u64,
+    issuer: Option<String>,
+    omit_public_key: bool,
+    credential_type: Option<String>,
+    issuance_policy: CsdIssuancePolicy,
+    point_encoding: PointEncoding,
+    salted_claims: Vec<String>,
     phantom: PhantomData<&'a C>,
 }
 
@@ -36,8 +61,8 @@ impl<C: Pairing> CsdEncoder<'_, C> {
     ///
     /// ## Error
     /// Returns [`Error::DeserializationError`] if `object` is not a valid JSON object.
-    pub fn new(object: &str) -> CsdResult<CsdEncoder<C>> {
-        let object: Value = serde_json::from_str(object).map_err(|e| CsdError::DeserializationError(e.to_string()))?;
+    pub fn new(object: &str) -> CsdResult<CsdEncoder<'_, C>> {
+        let object: Value = serde_json::from_str(object).map_err(|e| CsdError::Core(CsdCoreError::DeserializationError(e.to_string())))?;
         if !object.is_object() {
             return Err(CsdError::DataTypeMismatch("expected object".to_owned()));
         }
@@ -45,6 +70,14 @@ impl<C: Pairing> CsdEncoder<'_, C> {
         Ok(CsdEncoder {
             object,
             final_object: Map::new(),
+            secret_key: generate_secret_key::<C>(),
+            param_seed: DEFAULT_PARAM_SEED,
+            issuer: None,
+            omit_public_key: false,
+            credential_type: None,
+            issuance_policy: CsdIssuancePolicy::new(),
+            point_encoding: PointEncoding::default(),
+            salted_claims: Vec::new(),
             phantom: Default::default(),
         })
     }
@@ -54,7 +87,7 @@ impl<C: Pairing> CsdEncoder<'_, C> {
     /// ## Error
     /// Returns [`Error::DeserializationError`] if `object` can not be serialized into a valid JSON object.
     pub fn try_from_serializable<T: serde::Serialize>(object: T) -> std::result::Result<Self, CsdError> {
-        let object: Value = serde_json::to_value(&object).map_err(|e| CsdError::DeserializationError(e.to_string()))?;
+        let object: Value = serde_json::to_value(&object).map_err(|e| CsdError::Core(CsdCoreError::DeserializationError(e.to_string())))?;
         CsdEncoder::try_from(object)
     }
 }
@@ -69,6 +102,14 @@ impl<C: Pairing> TryFrom<Value> for CsdEncoder<'_, C> {
         Ok(CsdEncoder {
             object: value,
             final_object: Map::new(),
+            secret_key: generate_secret_key::<C>(),
+            param_seed: DEFAULT_PARAM_SEED,
+            issuer: None,
+            omit_public_key: false,
+            credential_type: None,
+            issuance_policy: CsdIssuancePolicy::new(),
+            point_encoding: PointEncoding::default(),
+            salted_claims: Vec::new(),
             phantom: Default::default(),
         })
     }
@@ -85,6 +126,65 @@ impl<C: Pairing> CsdEncoder<'_, C> {
         }
     }
 
+    /// Adds the `_sd_alg` property of the keyed-verification suite to the top level of the object.
+    /// Tokens of this suite are accumulated in a KB universal accumulator on keyed-verification
+    /// params and don't carry the accumulator public key, their witnesses are verified by the
+    /// issuer with the secret key and without any pairing.
+    pub fn add_keyed_sd_alg_property(&mut self) -> Option<Value> {
+        if let Some(object) = self.object.as_object_mut() {
+            object.insert(SD_ALG.to_string(), Value::String(format!("{}{}", std::any::type_name::<C>(), KEYED_SD_ALG_SUFFIX)))
+        } else {
+            None // Should be unreachable since the `self.object` is checked to be an object on creation.
+        }
+    }
+
+    /// Sets the accumulator secret key. Encoders otherwise draw a new one, which leaves only
+    /// the token's own public key to verify it with.
+    pub fn set_secret_key(&mut self, secret_key: SecretKey<C::ScalarField>) {
+        self.secret_key = secret_key;
+    }
+
+    /// Sets the seed of the accumulator setup parameters, which is published in the token.
+    pub fn set_param_seed(&mut self, param_seed: u64) {
+        self.param_seed = param_seed;
+    }
+
+    /// Sets the `iss` claim to the issuer, e.g. its DID.
+    pub fn set_issuer(&mut self, issuer: impl Into<String>) {
+        self.issuer = Some(issuer.into());
+    }
+
+    /// Leaves the accumulator public key out of the token, so that verifiers take it from the
+    /// issuer's DID document or their registry of trusted issuers instead of the token.
+    pub fn set_omit_public_key(&mut self, omit_public_key: bool) {
+        self.omit_public_key = omit_public_key;
+    }
+
+    /// Sets the `vct` claim, which verifiers match against the credential types their trusted
+    /// issuers may issue (see [`crate::csd::csd_registry::CsdIssuerRegistry`]).
+    pub fn set_credential_type(&mut self, credential_type: impl Into<String>) {
+        self.credential_type = Some(credential_type.into());
+    }
+
+    /// Sets which claims are accumulated and which stay plain in the signed object, see
+    /// [`CsdIssuancePolicy`].
+    pub fn set_issuance_policy(&mut self, issuance_policy: CsdIssuancePolicy) {
+        self.issuance_policy = issuance_policy;
+    }
+
+    /// Sets how the accumulator, public key and witnesses are encoded, base64url by default.
+    pub fn set_point_encoding(&mut self, point_encoding: PointEncoding) {
+        self.point_encoding = point_encoding;
+    }
+
+    /// Sets the top-level claims [`CsdEncoder::encode`] discloses with salted SHA-256 digests in
+    /// `_sd`, as SD-JWT does, instead of the accumulator. This suits low-entropy values, which
+    /// the salt hides, and large ones, which need no witness. Plain claims stay plain, and
+    /// [`CsdEncoder::object`] still accumulates every claim.
+    pub fn set_salted_claims(&mut self, salted_claims: Vec<String>) {
+        self.salted_claims = salted_claims;
+    }
+
     pub fn conceal(&mut self, path: &str) -> CsdResult<CsdDisclosure> {
         // Determine salt.
         let element_pointer = path
@@ -142,11 +242,38 @@ impl<C: Pairing> CsdEncoder<'_, C> {
             .map_err(|_e| CsdError::Unspecified("error while serializing internal object".to_string()))
     }
 
-    /// Returns a reference to the internal object.
+    /// Returns a reference to the internal object, with every claim embedded as a
+    /// `key::value` entry whose value is its witness.
     pub fn object(&mut self) -> CsdResult<&Map<String, Value>> {
+        let (claims, _) = self.accumulate(&[])?;
+        for (key, value, witness) in claims {
+            self.final_object.insert(claim_key(&key, &value), Value::String(witness));
+        }
+
+        Ok(&self.final_object)
+    }
+
+    /// Returns the object to be signed by the issuer, which only carries the accumulator and
+    /// its parameters, together with one [`CsdDisclosure`] per accumulated or salted claim.
+    /// Unlike [`CsdEncoder::object`], holders can choose which claims to present.
+    pub fn encode(&mut self) -> CsdResult<(Map<String, Value>, Vec<CsdDisclosure>)> {
+        let (claims, salted) = self.accumulate(&self.salted_claims.clone())?;
+        let disclosures = claims
+            .into_iter()
+            .map(|(key, value, witness)| CsdDisclosure::with_witness(witness, key, value))
+            .chain(salted)
+            .collect();
+
+        Ok((self.final_object.clone(), disclosures))
+    }
+
+    /// Accumulates every top-level claim but the `salted` ones, fills `final_object` with the
+    /// accumulator, its parameters and the digests of the salted claims, and returns the claims
+    /// along with their serialized witnesses, and the salted disclosures.
+    fn accumulate(&mut self, salted: &[String]) -> CsdResult<(Vec<AccumulatedClaim>, Vec<CsdDisclosure>)> {
         // Safety: encoder can be constructed from objects only.
 
-        let (_, keypair, accumulator, mut state) = accumulator::initialize_accumulator::<C>(DEFAULT_KEY_SEED, DEFAULT_PARAM_SEED);
+        self.final_object = Map::new();
 
         let mut map = match self.object.as_object() {
             Some(map) => map,
@@ -161,59 +288,109 @@ impl<C: Pairing> CsdEncoder<'_, C> {
             None => return Err(CsdError::Unspecified(String::from("No sd-alg field present.")))
         };
 
+        // Never-selective claims, by default the registered JWT claims, stay plain so verifiers
+        // can always check them.
+        let plain_names: Vec<String> = map.keys().filter(|name| self.issuance_policy.is_plain(name)).cloned().collect();
+        let mut plain_claims: Vec<(String, Value)> = Vec::new();
+        for name in plain_names {
+            check_plain_claim_name(&name)?;
+            let value = map.remove(&name).unwrap_or_default();
+            plain_claims.push((name, value));
+        }
+        let listed: Vec<Value> = plain_claims
+            .iter()
+            .filter(|(name, _)| !JWT_CLAIMS.contains(&name.as_str()))
+            .map(|(name, _)| Value::String(name.to_owned()))
+            .collect();
+
+        let mut salted_disclosures: Vec<CsdDisclosure> = Vec::new();
+        for name in salted {
+            if let Some(value) = map.remove(name) {
+                check_plain_claim_name(name)?;
+                let salt = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(rand::random::<[u8; SALT_SIZE]>());
+                salted_disclosures.push(CsdDisclosure::with_salt(salt, name.to_owned(), value));
+            }
+        }
+        // Sorted, so the digests don't give the order of the claims away.
+        let mut digests: Vec<String> = salted_disclosures.iter().map(CsdDisclosure::digest).collect();
+        digests.sort();
+
+        // Nested objects are hashed with their keys in CBOR canonical order, whatever the form.
+        let map: Map<String, Value> = map.into_iter().map(|(key, value)| (key, canonical_value(&value))).collect();
         for (key, value) in &map {
-            claims.push(format!("{}::{}", key, value.to_string()));
+            claims.push(claim_key(key, value));
         }
 
         let scalar_claims: Vec<C::ScalarField> = claims.iter().map(move |x| { scalar_from_str::<C>(x.as_str()) }).collect::<Vec<C::ScalarField>>();
 
-        let accumulator = match accumulator.add_batch(
-            scalar_claims.clone(),
-            &keypair.secret_key,
-            &mut state,
-        ) {
-            Ok(accumulator) => accumulator,
-            Err(err) => return Err(CsdError::AddBatch(format!("{:?}", err)))
+        // Keyed tokens are accumulated on the keyed-verification params, without any pairing setup
+        // or public key; the others on the pairing-based ones.
+        let keyed = sd_alg.as_str().is_some_and(|sd_alg| sd_alg.ends_with(KEYED_SD_ALG_SUFFIX));
+        let (accumulator, witnesses, public_key) = if keyed {
+            let (accumulator, witnesses) = accumulator::accumulate_keyed::<C>(&self.secret_key, self.param_seed, &scalar_claims)?;
+            (accumulator, witnesses, None)
+        } else {
+            let (_, keypair, accumulator, mut state) = accumulator::initialize_accumulator::<C>(&self.secret_key, self.param_seed);
+            let accumulator = match accumulator.add_batch(
+                scalar_claims.clone(),
+                &keypair.secret_key,
+                &mut state,
+            ) {
+                Ok(accumulator) => accumulator,
+                Err(err) => return Err(CsdError::AddBatch(format!("{:?}", err)))
+            };
+            let witnesses = match accumulator.get_membership_witnesses_for_batch(&scalar_claims, &keypair.secret_key, &state) {
+                Ok(witnesses) => witnesses,
+                Err(err) => return Err(CsdError::WitnessBatch(format!("{:?}", err)))
+            };
+            (accumulator, witnesses, Some(keypair.public_key.clone()))
         };
 
-        let serialized_accumulator: String = serialize_accumulator(accumulator.clone());
+        let serialized_accumulator: String = serialize_accumulator_with(accumulator.clone(), self.point_encoding);
         self.final_object.insert(String::from(ACCUMULATOR_KEY), Value::String(serialized_accumulator));
-        // The public key should be retrieved from the did of the issuer
-        let serialized_pk: String = serialize_pk(keypair.public_key.clone());
-        self.final_object.insert(String::from(PK_KEY), Value::String(serialized_pk));
-        self.final_object.insert(String::from(PARAM_SEED_KEY), Value::String(DEFAULT_PARAM_SEED.to_string()));
+        self.final_object.extend(plain_claims);
+        if !listed.is_empty() {
+            self.final_object.insert(String::from(PLAIN_CLAIMS_KEY), Value::Array(listed));
+        }
+        if !digests.is_empty() {
+            self.final_object.insert(String::from(SD_DIGESTS_KEY), Value::Array(digests.into_iter().map(Value::String).collect()));
+        }
+        if let Some(issuer) = &self.issuer {
+            self.final_object.insert(String::from(ISSUER_KEY), Value::String(issuer.to_owned()));
+        }
+        if let Some(public_key) = public_key.filter(|_| !self.omit_public_key) {
+            let serialized_pk: String = serialize_pk_with(public_key, self.point_encoding);
+            self.final_object.insert(String::from(PK_KEY), Value::String(serialized_pk));
+        }
+        if let Some(credential_type) = &self.credential_type {
+            self.final_object.insert(String::from(CREDENTIAL_TYPE_KEY), Value::String(credential_type.to_owned()));
+        }
+        self.final_object.insert(String::from(PARAM_SEED_KEY), Value::String(self.param_seed.to_string()));
         self.final_object.insert(String::from(SD_ALG), sd_alg);
 
-        let witnesses = match accumulator.get_membership_witnesses_for_batch(&scalar_claims, &keypair.secret_key, &state) {
-            Ok(witnesses) => witnesses,
-            Err(err) => return Err(CsdError::WitnessBatch(format!("{:?}", err)))
-        };
-
-        for i in 0..claims.len() {
-            let claim = match claims.get(i) {
-                Some(claim) => claim.to_owned(),
-                None => return Err(CsdError::Unspecified(format!("Claim ({i}) not found.")))
-            };
+        let mut accumulated: Vec<AccumulatedClaim> = Vec::new();
+        for (i, (key, value)) in map.into_iter().enumerate() {
             let witness = match witnesses.get(i) {
                 Some(witness) => witness.to_owned(),
                 None => return Err(CsdError::Unspecified(format!("Witness ({i}) not found.")))
             };
-            self.final_object.insert(claim, Value::String(serialize_witness::<C>(witness)));
+            accumulated.push((key, value, serialize_witness_with::<C>(witness, self.point_encoding)));
         }
 
-        Ok(&self.final_object)
+        Ok((accumulated, salted_disclosures))
     }
 }
 
 #[cfg(test)]
 mod test {
+    use ark_bn254::Bn254;
     use serde::Serialize;
     use serde_json::json;
     use serde_json::Value;
 
-    use crate::Error;
+    use crate::csd::csd_error::CsdError;
 
-    use super::CsdEncoder;
+    use super::{CsdEncoder, ACCUMULATOR_KEY};
 
     #[derive(Serialize)]
     struct TestStruct {
@@ -233,36 +410,37 @@ mod test {
 
     #[test]
     fn simple() {
-        let mut encoder = CsdEncoder::try_from(object()).unwrap();
+        let mut encoder = CsdEncoder::<Bn254>::try_from(object()).unwrap();
         encoder.conceal("/claim1/abc").unwrap();
         encoder.conceal("/id").unwrap();
-        encoder.add_decoys("", 10).unwrap();
-        encoder.add_decoys("/claim2", 10).unwrap();
+        encoder.add_sd_alg_property();
         assert!(encoder.object().unwrap().get("id").is_none());
-        assert_eq!(encoder.object.get("_sd").unwrap().as_array().unwrap().len(), 11);
-        assert_eq!(encoder.object.get("claim2").unwrap().as_array().unwrap().len(), 12);
+        assert!(encoder.object().unwrap().contains_key(ACCUMULATOR_KEY));
+        assert!(encoder.object().unwrap().contains_key("claim1::{}"));
+        assert!(encoder.object.get("id").is_none());
+        assert_eq!(encoder.object.get("claim2").unwrap().as_array().unwrap().len(), 2);
     }
 
     #[test]
     fn errors() {
-        let mut encoder = CsdEncoder::try_from(object()).unwrap();
+        let mut encoder = CsdEncoder::<Bn254>::try_from(object()).unwrap();
         encoder.conceal("/claim1/abc").unwrap();
         assert!(matches!(
       encoder.conceal("claim2/2").unwrap_err(),
-      Error::InvalidPath(_)
+      CsdError::InvalidPath(_)
     ));
     }
 
     #[test]
     fn test_wrong_path() {
-        let mut encoder = CsdEncoder::try_from(object()).unwrap();
+        let mut encoder = CsdEncoder::<Bn254>::try_from(object()).unwrap();
         assert!(matches!(
       encoder.conceal("/claim12").unwrap_err(),
-      Error::InvalidPath(_)
+      CsdError::InvalidPath(_)
     ));
         assert!(matches!(
       encoder.conceal("/claim12/0").unwrap_err(),
-      Error::InvalidPath(_)
+      CsdError::InvalidPath(_)
     ));
     }
 
@@ -272,12 +450,11 @@ mod test {
             id: "did:value".to_string(),
             claim2: vec!["arr-value1".to_string(), "arr-vlaue2".to_string()],
         };
-        let mut encoder = CsdEncoder::try_from_serializable(test_value).unwrap();
+        let mut encoder = CsdEncoder::<Bn254>::try_from_serializable(test_value).unwrap();
         encoder.conceal("/id").unwrap();
-        encoder.add_decoys("", 10).unwrap();
-        encoder.add_decoys("/claim2", 10).unwrap();
+        encoder.add_sd_alg_property();
         assert!(encoder.object.get("id").is_none());
-        assert_eq!(encoder.object.get("_sd").unwrap().as_array().unwrap().len(), 11);
-        assert_eq!(encoder.object.get("claim2").unwrap().as_array().unwrap().len(), 12);
+        assert_eq!(encoder.object.get("claim2").unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(encoder.object().unwrap().len(), 5);
     }
 }
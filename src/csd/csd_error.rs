@@ -1,15 +1,15 @@
 // Copyright 2020-2023 IOTA Stiftung
 // SPDX-License-Identifier: Apache-2.0
 
+/// The errors of the `no_std` core, which are raised by this crate too.
+pub use csd_jwt_core::error::CsdError as CsdCoreError;
+
 /// Alias for a `Result` with the error type [`CsdError`].
 pub type CsdResult<T> = Result<T, CsdError>;
 
 #[non_exhaustive]
 #[derive(Debug, thiserror::Error, strum::IntoStaticStr)]
 pub enum CsdError {
-    #[error("invalid input: {0}")]
-    InvalidDisclosure(String),
-
     #[error("no hasher can be specified for the hashing algorithm {0}")]
     MissingHasher(String),
 
@@ -28,9 +28,6 @@ pub enum CsdError {
     #[error("invalid path: {0}")]
     InvalidPath(String),
 
-    #[error("invalid input")]
-    DeserializationError(String),
-
     #[error("{0}")]
     Unspecified(String),
 
@@ -45,4 +42,37 @@ pub enum CsdError {
 
     #[error("Error in generating batch of witnesses [{0}]")]
     WitnessBatch(String),
+
+    #[error("Error in removing elements from accumulator [{0}]")]
+    RemoveBatch(String),
+
+    #[error("verification failed: {0}")]
+    VerificationFailed(String),
+
+    #[error("JWS error: {0}")]
+    Jws(String),
+
+    #[error("JWS algorithm {0} is not allowed")]
+    AlgorithmNotAllowed(String),
+
+    #[error("state snapshot failed the integrity check: {0}")]
+    StateIntegrity(String),
+
+    #[error("DID resolution failed: {0}")]
+    DidResolution(String),
+
+    #[error("untrusted issuer: {0}")]
+    UntrustedIssuer(String),
+
+    #[error("claim validation failed: {0}")]
+    ClaimValidation(String),
+
+    #[error("key binding failed: {0}")]
+    KeyBinding(String),
+
+    #[error("presentation requirement not met: {0}")]
+    UnmetRequirement(String),
+
+    #[error(transparent)]
+    Core(#[from] CsdCoreError),
 }
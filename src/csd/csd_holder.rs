@@ -0,0 +1,129 @@
+#[cfg(not(target_arch = "wasm32"))]
+use josekit::jws::JwsSigner;
+use json_pointer::JsonPointer;
+use serde_json::{Map, Value};
+
+use crate::csd;
+use csd::csd_decoder::unverified_payload;
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_issuance::plain_claim_names;
+use csd::csd_jwt::CsdJwt;
+use csd::csd_requirements::CsdPresentationRequirements;
+#[cfg(not(target_arch = "wasm32"))]
+use csd::csd_kb_jwt::issue_key_binding;
+
+/// Holds an issued CSD-JWT credential and derives presentations from it.
+pub struct CsdHolder {
+    credential: CsdJwt,
+    disclosures: Vec<CsdDisclosure>,
+    always_disclosed: Vec<String>,
+}
+
+impl CsdHolder {
+    /// Creates a new [`CsdHolder`] from a serialized credential.
+    ///
+    /// ## Error
+    /// Returns the [`CsdError::Core`] deserialization or invalid disclosure error if the credential
+    /// or one of its disclosures can't be parsed.
+    pub fn new(credential: &str) -> CsdResult<Self> {
+        let credential = CsdJwt::parse(credential)?;
+        let disclosures = credential.disclosures
+            .iter()
+            .map(|disclosure| CsdDisclosure::parse(disclosure.to_owned()))
+            .collect::<Result<Vec<CsdDisclosure>, _>>()?;
+        let always_disclosed = plain_claim_names(&unverified_payload(&credential.jwt)?)?;
+
+        Ok(Self { credential, disclosures, always_disclosed })
+    }
+
+    /// Returns the credential.
+    pub fn credential(&self) -> &CsdJwt {
+        &self.credential
+    }
+
+    /// Returns the disclosures of the credential.
+    pub fn disclosures(&self) -> &[CsdDisclosure] {
+        &self.disclosures
+    }
+
+    /// Returns the names of the claims the issuer put in plain, which every presentation discloses.
+    pub fn always_disclosed(&self) -> &[String] {
+        &self.always_disclosed
+    }
+
+    /// Derives a presentation disclosing only the claims at `pointers`, e.g. `"/name"`, besides
+    /// the [`CsdHolder::always_disclosed`] ones.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::InvalidPath`] if a pointer is malformed, is not a top-level claim or
+    /// doesn't match any disclosure.
+    pub fn present(&self, pointers: &[&str]) -> CsdResult<CsdJwt> {
+        let mut disclosures: Vec<String> = Vec::new();
+        for pointer in pointers {
+            let claim_name = Self::claim_name(pointer)?;
+            if self.always_disclosed.contains(&claim_name) {
+                continue;
+            }
+            let disclosure = self.disclosures
+                .iter()
+                .find(|disclosure| disclosure.claim_name.as_deref() == Some(claim_name.as_str()))
+                .ok_or(CsdError::InvalidPath(format!("{} is not disclosable", pointer)))?;
+            disclosures.push(disclosure.as_str().to_owned());
+        }
+
+        Ok(CsdJwt::new(self.credential.jwt.clone(), disclosures, None))
+    }
+
+    /// Returns every claim of the credential, the disclosable and the always disclosed ones.
+    pub fn claims(&self) -> CsdResult<Map<String, Value>> {
+        let mut claims = unverified_payload(&self.credential.jwt)?;
+        claims.retain(|name, _| self.always_disclosed.contains(name));
+        for disclosure in &self.disclosures {
+            if let Some(name) = &disclosure.claim_name {
+                claims.insert(name.to_owned(), disclosure.claim_value.clone());
+            }
+        }
+        Ok(claims)
+    }
+
+    /// Derives a presentation disclosing the claims a verifier asks for in `requirements`, see
+    /// [`CsdPresentationRequirements::select`].
+    ///
+    /// ## Error
+    /// Returns [`CsdError::UnmetRequirement`] if the credential lacks a required claim.
+    pub fn present_for(&self, requirements: &CsdPresentationRequirements) -> CsdResult<CsdJwt> {
+        let pointers = requirements.select(&self.claims()?)?;
+        self.present(&pointers.iter().map(String::as_str).collect::<Vec<&str>>())
+    }
+
+    /// Same as [`CsdHolder::present`], binding the presentation to the holder key of the
+    /// credential's `cnf` claim with a KB-JWT for `audience`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn present_with_key_binding(
+        &self,
+        pointers: &[&str],
+        signer: &dyn JwsSigner,
+        audience: &str,
+        nonce: &str,
+        issued_at: i64,
+    ) -> CsdResult<CsdJwt> {
+        let mut presentation = self.present(pointers)?;
+        presentation.key_binding_jwt = Some(issue_key_binding(&presentation, signer, audience, nonce, issued_at)?);
+        Ok(presentation)
+    }
+
+    /// Only top-level claims are accumulated, so only single-segment pointers are disclosable.
+    fn claim_name(pointer: &str) -> CsdResult<String> {
+        let mut pointer = pointer
+            .parse::<JsonPointer<_, _>>()
+            .map_err(|err| CsdError::InvalidPath(format!("{:?}", err)))?;
+        let claim_name = pointer
+            .pop()
+            .ok_or(CsdError::InvalidPath("path does not contain any values".to_string()))?;
+        if pointer.pop().is_some() {
+            return Err(CsdError::InvalidPath(format!("{} is not a top-level claim", claim_name)));
+        }
+        Ok(claim_name)
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::csd;
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_encoder::{ACCUMULATOR_KEY, CREDENTIAL_TYPE_KEY, PARAM_SEED_KEY, PK_KEY, SD_ALG, SD_DIGESTS_KEY};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+use csd::csd_policy::JWT_CLAIMS;
+
+/// Names of the claims an issuance policy placed in the issuer JWT payload, besides the
+/// registered JWT claims, so decoders tell them apart from the accumulated ones.
+pub const PLAIN_CLAIMS_KEY: &str = "_plain";
+
+/// How a top-level claim may be disclosed, after the `sd` property of SD-JWT VC type metadata,
+/// as which it (de)serializes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CsdSelectivity {
+    /// A plain claim of the issuer JWT, disclosed in every presentation.
+    #[serde(rename = "never")]
+    NeverSelective,
+    /// An accumulated claim, which verifiers reject in plain.
+    #[serde(rename = "always")]
+    AlwaysSelective,
+    /// An accumulated claim the holder may withhold, which verifiers also accept in plain.
+    #[serde(rename = "allowed")]
+    HolderChoice,
+}
+
+/// Where the encoder puts each top-level claim, and what a verifier expects of the issuer.
+///
+/// Claims without an explicit [`CsdSelectivity`] get the default one, [`CsdSelectivity::HolderChoice`]
+/// unless changed, except for the registered JWT claims which stay
+/// [`CsdSelectivity::NeverSelective`] so that the time and audience checks can always run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsdIssuancePolicy {
+    claims: HashMap<String, CsdSelectivity>,
+    default: CsdSelectivity,
+}
+
+impl Default for CsdIssuancePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsdIssuancePolicy {
+    pub fn new() -> Self {
+        Self { claims: HashMap::new(), default: CsdSelectivity::HolderChoice }
+    }
+
+    /// Sets the selectivity of the top-level claim `name`.
+    pub fn set(&mut self, name: impl Into<String>, selectivity: CsdSelectivity) {
+        self.claims.insert(name.into(), selectivity);
+    }
+
+    /// Sets the selectivity of the claims not named in the policy.
+    pub fn set_default(&mut self, selectivity: CsdSelectivity) {
+        self.default = selectivity;
+    }
+
+    pub fn selectivity(&self, name: &str) -> CsdSelectivity {
+        match self.claims.get(name) {
+            Some(selectivity) => *selectivity,
+            None if JWT_CLAIMS.contains(&name) => CsdSelectivity::NeverSelective,
+            None => self.default,
+        }
+    }
+
+    /// Whether the encoder leaves `name` out of the accumulator.
+    pub fn is_plain(&self, name: &str) -> bool {
+        self.selectivity(name) == CsdSelectivity::NeverSelective
+    }
+
+    /// Checks a verified issuer JWT `payload` against the policy: every claim named
+    /// [`CsdSelectivity::NeverSelective`] must be present in plain, and no
+    /// [`CsdSelectivity::AlwaysSelective`] claim may be.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::ClaimValidation`] naming the first claim that fails.
+    pub fn enforce(&self, payload: &Map<String, Value>) -> CsdResult<()> {
+        let plain = plain_claim_names(payload)?;
+        let is_plain = |name: &str| plain.iter().any(|plain| plain == name) || (JWT_CLAIMS.contains(&name) && payload.contains_key(name));
+
+        for (name, selectivity) in &self.claims {
+            if *selectivity == CsdSelectivity::NeverSelective && !is_plain(name) {
+                return Err(CsdError::ClaimValidation(format!("{} must always be disclosed", name)));
+            }
+        }
+        self.reject_plain(payload)
+    }
+
+    /// Checks that no [`CsdSelectivity::AlwaysSelective`] claim is in plain in a verified issuer
+    /// JWT `payload`, leaving out the presence check of [`CsdIssuancePolicy::enforce`].
+    ///
+    /// ## Error
+    /// Returns [`CsdError::ClaimValidation`] naming the first claim that fails.
+    pub fn reject_plain(&self, payload: &Map<String, Value>) -> CsdResult<()> {
+        let plain = plain_claim_names(payload)?;
+        let plain = plain
+            .iter()
+            .map(String::as_str)
+            .chain(JWT_CLAIMS.into_iter().filter(|name| payload.contains_key(*name)));
+        for name in plain {
+            if self.selectivity(name) == CsdSelectivity::AlwaysSelective {
+                return Err(CsdError::ClaimValidation(format!("{} must be selectively disclosable", name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that none of the `disclosures` holds a [`CsdSelectivity::NeverSelective`] claim.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::ClaimValidation`] naming the first claim that fails.
+    pub fn reject_disclosed(&self, disclosures: &[CsdDisclosure]) -> CsdResult<()> {
+        for name in disclosures.iter().filter_map(|disclosure| disclosure.claim_name.as_deref()) {
+            if self.selectivity(name) == CsdSelectivity::NeverSelective {
+                return Err(CsdError::ClaimValidation(format!("{} is selectively disclosed", name)));
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// plain_claim_names(payload: &Map<String, Value>) -> CsdResult<Vec<String>>
+///
+/// The names listed in the [`PLAIN_CLAIMS_KEY`] entry of an issuer JWT `payload`.
+///
+pub fn plain_claim_names(payload: &Map<String, Value>) -> CsdResult<Vec<String>> {
+    match payload.get(PLAIN_CLAIMS_KEY) {
+        None => Ok(vec![]),
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|name| name.as_str().map(String::from))
+            .collect::<Option<Vec<String>>>()
+            .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("{} holds a non-string name", PLAIN_CLAIMS_KEY)))),
+        Some(_) => Err(CsdError::Core(CsdCoreError::DeserializationError(format!("{} is not an array", PLAIN_CLAIMS_KEY)))),
+    }
+}
+
+///
+/// remove_plain_claims(object: &mut Map<String, Value>) -> CsdResult<Map<String, Value>>
+///
+/// Removes every claim that isn't accumulated from a decoded `object`, i.e. the `vct`, the
+/// registered JWT claims, the salted digests and the claims listed in [`PLAIN_CLAIMS_KEY`], and
+/// returns the latter.
+///
+pub(crate) fn remove_plain_claims(object: &mut Map<String, Value>) -> CsdResult<Map<String, Value>> {
+    let names = plain_claim_names(object)?;
+    object.remove(PLAIN_CLAIMS_KEY);
+    object.remove(SD_DIGESTS_KEY);
+    object.remove(CREDENTIAL_TYPE_KEY);
+    for name in JWT_CLAIMS {
+        object.remove(name);
+    }
+
+    let mut plain = Map::new();
+    for name in names {
+        let value = object
+            .remove(&name)
+            .ok_or(CsdError::ClaimValidation(format!("always disclosed claim {} is missing", name)))?;
+        plain.insert(name, value);
+    }
+    Ok(plain)
+}
+
+/// Plain and salted claims can't take the name of the accumulator metadata.
+pub(crate) fn check_plain_claim_name(name: &str) -> CsdResult<()> {
+    match name {
+        ACCUMULATOR_KEY | PK_KEY | PARAM_SEED_KEY | SD_ALG | CREDENTIAL_TYPE_KEY | PLAIN_CLAIMS_KEY | SD_DIGESTS_KEY => {
+            Err(CsdError::ClaimCollisionError(name.to_owned()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::{json, Value};
+
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_verifier::CsdVerifier;
+
+    use super::{CsdIssuancePolicy, CsdSelectivity};
+
+    #[test]
+    fn claim_selectivity() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let issue = |policy: CsdIssuancePolicy| {
+            let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+            issuer.set_issuance_policy(policy);
+            issuer.issue(json!({"name": "Alice", "age": 30, "country": "NL"})).unwrap().presentation()
+        };
+
+        let mut policy = CsdIssuancePolicy::new();
+        policy.set("country", CsdSelectivity::NeverSelective);
+        policy.set("age", CsdSelectivity::AlwaysSelective);
+        let credential = issue(policy.clone());
+        let holder = CsdHolder::new(&credential).unwrap();
+        assert_eq!(holder.disclosures().len(), 2);
+        assert_eq!(holder.always_disclosed(), ["country"]);
+
+        // The plain claim is in every presentation, whether the holder asks for it or not.
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        verifier.set_issuance_policy(policy);
+        let presentation = holder.present(&["/name", "/country"]).unwrap().presentation();
+        assert_eq!(Value::Object(verifier.verify(&presentation).unwrap()), json!({"name": "Alice", "country": "NL"}));
+        let presentation = holder.present(&[]).unwrap().presentation();
+        assert_eq!(Value::Object(verifier.verify(&presentation).unwrap()), json!({"country": "NL"}));
+
+        // Verifiers expecting the policy reject credentials issued without it.
+        let presentation = CsdHolder::new(&issue(CsdIssuancePolicy::new())).unwrap().present(&["/country"]).unwrap().presentation();
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::ClaimValidation(_)));
+        let mut plain_age = CsdIssuancePolicy::new();
+        plain_age.set("country", CsdSelectivity::NeverSelective);
+        plain_age.set("age", CsdSelectivity::NeverSelective);
+        let presentation = CsdHolder::new(&issue(plain_age)).unwrap().present(&[]).unwrap().presentation();
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::ClaimValidation(_)));
+
+        let mut reserved = CsdIssuancePolicy::new();
+        reserved.set_default(CsdSelectivity::NeverSelective);
+        let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        issuer.set_issuance_policy(reserved);
+        assert!(matches!(issuer.issue(json!({"pk": "forged"})).unwrap_err(), CsdError::ClaimCollisionError(_)));
+    }
+}
@@ -0,0 +1,278 @@
+use std::marker::PhantomData;
+use ark_ec::pairing::Pairing;
+use josekit::jwk::Jwk;
+use josekit::jws::JwsSigner;
+use serde_json::{json, Value};
+use vb_accumulator::setup::{Keypair, PublicKey, SecretKey};
+
+use crate::accumulator::{deserialize_pk, deserialize_secret_key, generate_params, generate_secret_key, serialize_pk, serialize_secret_key, PointEncoding};
+use crate::bls::{point_jwk, BlsCurve};
+use crate::csd;
+use csd::csd_encoder::{CsdEncoder, DEFAULT_PARAM_SEED};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+use csd::csd_issuance::CsdIssuancePolicy;
+use csd::csd_jws::issue_typed;
+use csd::csd_policy::{CsdClock, SystemClock, ISSUED_AT_KEY};
+use csd::csd_vc::{vc_issuance_policy, CsdProfile};
+use csd::csd_jwt::CsdJwt;
+
+/// The JWK member in which an issuer keeps its accumulator key next to its JWS key: the secret key
+/// in the private JWK, the public key and parameter seed in the public one.
+pub const ACCUMULATOR_JWK_MEMBER: &str = "csd_accumulator";
+
+/// Issues CSD-JWT credentials: accumulates the claims, signs the accumulator with `signer` and
+/// attaches one disclosure per claim.
+pub struct CsdIssuer<C: Pairing> {
+    signer: Box<dyn JwsSigner>,
+    secret_key: SecretKey<C::ScalarField>,
+    param_seed: u64,
+    issuer: Option<String>,
+    key_id: Option<String>,
+    omit_accumulator_key: bool,
+    credential_type: Option<String>,
+    issuance_policy: CsdIssuancePolicy,
+    profile: CsdProfile,
+    point_encoding: PointEncoding,
+    salted_claims: Vec<String>,
+    phantom: PhantomData<C>,
+}
+
+impl<C: Pairing> CsdIssuer<C> {
+    /// Creates a new [`CsdIssuer`] signing with `signer`, with a freshly generated accumulator
+    /// secret key and the default parameter seed.
+    pub fn new(signer: Box<dyn JwsSigner>) -> Self {
+        CsdIssuer::<C> {
+            signer,
+            secret_key: generate_secret_key::<C>(),
+            param_seed: DEFAULT_PARAM_SEED,
+            issuer: None,
+            key_id: None,
+            omit_accumulator_key: false,
+            credential_type: None,
+            issuance_policy: CsdIssuancePolicy::new(),
+            profile: CsdProfile::CsdJwt,
+            point_encoding: PointEncoding::default(),
+            salted_claims: Vec::new(),
+            phantom: Default::default(),
+        }
+    }
+
+    /// Sets the accumulator secret key, which issuers keep alongside their JWS key so that their
+    /// accumulator public key stays the same across credentials.
+    pub fn set_accumulator_secret_key(&mut self, secret_key: SecretKey<C::ScalarField>) {
+        self.secret_key = secret_key;
+    }
+
+    /// Sets the seed of the accumulator parameters, see [`CsdEncoder::set_param_seed`].
+    pub fn set_param_seed(&mut self, param_seed: u64) {
+        self.param_seed = param_seed;
+    }
+
+    /// The accumulator public key, to be registered by verifiers as in
+    /// [`csd::csd_registry::CsdTrustedIssuer`].
+    pub fn accumulator_public_key(&self) -> PublicKey<C> {
+        Keypair::<C>::public_key_from_secret_key(&self.secret_key, &generate_params(self.param_seed))
+    }
+
+    /// The seed of the accumulator parameters published in the credentials.
+    pub fn param_seed(&self) -> u64 {
+        self.param_seed
+    }
+
+    /// Identifies the issuer by its DID, and the signing key by the DID URL `key_id`, so verifiers
+    /// can resolve the keys (see [`csd::csd_decoder::CsdDecoder::decode_resolved`]). The DID
+    /// document must also hold the accumulator key, see [`CsdIssuer::accumulator_jwk`].
+    pub fn set_issuer(&mut self, issuer: impl Into<String>, key_id: Option<String>) {
+        self.issuer = Some(issuer.into());
+        self.key_id = key_id;
+    }
+
+    /// Leaves the accumulator public key out of the credentials, for verifiers which resolve it
+    /// from the issuer's DID or registry, see [`CsdEncoder::set_omit_public_key`].
+    pub fn set_omit_accumulator_key(&mut self, omit_accumulator_key: bool) {
+        self.omit_accumulator_key = omit_accumulator_key;
+    }
+
+    /// Sets the `vct` of the credentials, see [`CsdEncoder::set_credential_type`].
+    pub fn set_credential_type(&mut self, credential_type: impl Into<String>) {
+        self.credential_type = Some(credential_type.into());
+    }
+
+    /// Sets which claims are always disclosed, see [`CsdEncoder::set_issuance_policy`].
+    pub fn set_issuance_policy(&mut self, issuance_policy: CsdIssuancePolicy) {
+        self.issuance_policy = issuance_policy;
+    }
+
+    /// Sets the profile of the credentials. With [`CsdProfile::SdJwtVc`], the issuer and
+    /// credential type must be set, `iat` is added unless `claims` have one and the claims the
+    /// profile requires in plain are never selective.
+    pub fn set_profile(&mut self, profile: CsdProfile) {
+        self.profile = profile;
+    }
+
+    /// Sets how points are encoded in the credentials, see [`CsdEncoder::set_point_encoding`].
+    pub fn set_point_encoding(&mut self, point_encoding: PointEncoding) {
+        self.point_encoding = point_encoding;
+    }
+
+    /// Sets the claims disclosed with salted digests instead of the accumulator, see
+    /// [`CsdEncoder::set_salted_claims`].
+    pub fn set_salted_claims(&mut self, salted_claims: Vec<String>) {
+        self.salted_claims = salted_claims;
+    }
+
+    /// Issues a credential for the top-level claims of `claims`.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::DataTypeMismatch`] if `claims` is not a JSON object, or
+    /// [`CsdError::ClaimValidation`] if it doesn't fit the profile.
+    pub fn issue(&self, mut claims: Value) -> CsdResult<CsdJwt> {
+        let mut issuance_policy = self.issuance_policy.clone();
+        if self.profile == CsdProfile::SdJwtVc {
+            if self.issuer.is_none() || self.credential_type.is_none() {
+                return Err(CsdError::ClaimValidation(String::from("SD-JWT VCs need an issuer and a credential type")));
+            }
+            if let Some(claims) = claims.as_object_mut() {
+                claims.entry(ISSUED_AT_KEY).or_insert(Value::from(SystemClock.now()));
+            }
+            issuance_policy = vc_issuance_policy(&issuance_policy)?;
+        }
+
+        let mut encoder: CsdEncoder<C> = claims.try_into()?;
+        encoder.set_secret_key(self.secret_key.clone());
+        encoder.set_param_seed(self.param_seed);
+        if let Some(issuer) = &self.issuer {
+            encoder.set_issuer(issuer);
+        }
+        encoder.set_omit_public_key(self.omit_accumulator_key);
+        if let Some(credential_type) = &self.credential_type {
+            encoder.set_credential_type(credential_type);
+        }
+        encoder.set_issuance_policy(issuance_policy);
+        encoder.set_point_encoding(self.point_encoding);
+        encoder.set_salted_claims(self.salted_claims.clone());
+        encoder.add_sd_alg_property();
+        let (object, disclosures) = encoder.encode()?;
+
+        let mut credential = issue_typed(&object, &*self.signer, self.key_id.as_deref(), self.profile.header_typ())?;
+        credential.disclosures = disclosures.into_iter().map(|disclosure| disclosure.into_string()).collect();
+        Ok(credential)
+    }
+}
+
+impl<C: BlsCurve> CsdIssuer<C> {
+    /// The accumulator public key as a JWK, to be published in the issuer's DID document. It has
+    /// the form of a BLS signing key, see [`crate::bls::BlsJwsAlgorithm::to_jwk`], with the `_sd_alg` of the
+    /// curve as algorithm.
+    pub fn accumulator_jwk(&self) -> Jwk {
+        point_jwk::<C>(&self.accumulator_public_key(), std::any::type_name::<C>())
+    }
+}
+
+///
+/// set_jwk_accumulator_key(jwk: &mut Jwk, issuer: &CsdIssuer<C>, private: bool) -> CsdResult<()>
+///
+/// Stores the accumulator key of `issuer` in `jwk` under [`ACCUMULATOR_JWK_MEMBER`], tagged with the
+/// `_sd_alg` of the curve. Only private JWKs get the secret key, public ones get the public key and
+/// parameter seed verifiers check the credentials with.
+///
+pub fn set_jwk_accumulator_key<C: Pairing>(jwk: &mut Jwk, issuer: &CsdIssuer<C>, private: bool) -> CsdResult<()> {
+
+    let member = match private {
+        true => json!({"alg": std::any::type_name::<C>(), "sk": serialize_secret_key::<C>(&issuer.secret_key)}),
+        false => json!({
+            "alg": std::any::type_name::<C>(),
+            "pk": serialize_pk(issuer.accumulator_public_key()),
+            "param_seed": issuer.param_seed.to_string(),
+        }),
+    };
+    jwk.set_parameter(ACCUMULATOR_JWK_MEMBER, Some(member)).map_err(|err| CsdError::Jws(err.to_string()))
+
+}
+
+///
+/// jwk_accumulator_key(jwk: &Jwk) -> CsdResult<Option<SecretKey<Fr>>>
+///
+/// Reads back the accumulator secret key stored by [`set_jwk_accumulator_key`], if `jwk` has one.
+///
+/// ## Error
+/// Returns [`CsdError::DataTypeMismatch`] if the key belongs to another curve, or
+/// [`CsdCoreError::DeserializationError`] if it is malformed.
+///
+pub fn jwk_accumulator_key<C: Pairing>(jwk: &Jwk) -> CsdResult<Option<SecretKey<C::ScalarField>>> {
+
+    let member = match jwk.parameter(ACCUMULATOR_JWK_MEMBER) {
+        Some(member) => member,
+        None => return Ok(None),
+    };
+    if member["alg"].as_str() != Some(std::any::type_name::<C>()) {
+        return Err(CsdError::DataTypeMismatch(format!("the accumulator key of the JWK is not a {} key", std::any::type_name::<C>())));
+    }
+    match member["sk"].as_str() {
+        Some(secret_key) => Ok(Some(deserialize_secret_key::<C>(secret_key)?)),
+        None => Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("the JWK holds no accumulator secret key")))),
+    }
+
+}
+
+///
+/// jwk_accumulator_public_key(jwk: &Jwk) -> CsdResult<Option<(PublicKey<C>, u64)>>
+///
+/// Reads back the accumulator public key and parameter seed stored by [`set_jwk_accumulator_key`]
+/// in a public JWK, if `jwk` has them, for [`csd::csd_verifier::CsdVerifier::set_accumulator_key`].
+///
+/// ## Error
+/// Returns [`CsdError::DataTypeMismatch`] if the key belongs to another curve, or
+/// [`CsdCoreError::DeserializationError`] if it is malformed.
+///
+pub fn jwk_accumulator_public_key<C: Pairing>(jwk: &Jwk) -> CsdResult<Option<(PublicKey<C>, u64)>> {
+
+    let member = match jwk.parameter(ACCUMULATOR_JWK_MEMBER) {
+        Some(member) => member,
+        None => return Ok(None),
+    };
+    if member["alg"].as_str() != Some(std::any::type_name::<C>()) {
+        return Err(CsdError::DataTypeMismatch(format!("the accumulator key of the JWK is not a {} key", std::any::type_name::<C>())));
+    }
+    match (member["pk"].as_str(), member["param_seed"].as_str().and_then(|seed| seed.parse::<u64>().ok())) {
+        (Some(public_key), Some(param_seed)) => Ok(Some((deserialize_pk::<C>(public_key.to_owned())?, param_seed))),
+        _ => Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("the JWK holds no accumulator public key and parameter seed")))),
+    }
+
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use serde_json::json;
+
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+
+    use super::{jwk_accumulator_key, jwk_accumulator_public_key, set_jwk_accumulator_key, CsdIssuer};
+
+    #[test]
+    fn jwk_accumulator_key_round_trip() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let mut jwk = alg.generate_jwk().unwrap();
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let credential = issuer.issue(json!({"name": "Alice"})).unwrap();
+        assert!(jwk_accumulator_key::<Bn254>(&jwk).unwrap().is_none());
+
+        set_jwk_accumulator_key(&mut jwk, &issuer, true).unwrap();
+        let mut public_jwk = jwk.to_public_key().unwrap();
+        assert!(public_jwk.parameter(super::ACCUMULATOR_JWK_MEMBER).is_none());
+        set_jwk_accumulator_key(&mut public_jwk, &issuer, false).unwrap();
+        assert!(jwk_accumulator_key::<Bn254>(&public_jwk).is_err());
+        let public_key = jwk_accumulator_public_key::<Bn254>(&public_jwk).unwrap();
+        assert_eq!(public_key, Some((issuer.accumulator_public_key(), issuer.param_seed())));
+
+        // A reloaded issuer signs the same accumulator public key.
+        let mut reloaded = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        reloaded.set_accumulator_secret_key(jwk_accumulator_key::<Bn254>(&jwk).unwrap().unwrap());
+        assert_eq!(reloaded.accumulator_public_key(), issuer.accumulator_public_key());
+        assert_eq!(reloaded.issue(json!({"name": "Alice"})).unwrap().jwt.split('.').nth(1), credential.jwt.split('.').nth(1));
+        assert!(matches!(jwk_accumulator_key::<Bls12_381>(&jwk).unwrap_err(), CsdError::DataTypeMismatch(_)));
+    }
+}
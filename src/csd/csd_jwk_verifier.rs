@@ -0,0 +1,200 @@
+use base64::Engine;
+use p256::ecdsa::signature::Verifier;
+use serde_json::{Map, Value};
+
+use crate::csd;
+use csd::csd_encoder::HEADER_TYP;
+use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_vc::VC_HEADER_TYP;
+
+/// The header of a JWS as a JSON object.
+pub type JoseHeader = Map<String, Value>;
+
+/// A JWS verifier for a public JWK written in pure Rust, for targets OpenSSL doesn't build for,
+/// such as `wasm32-unknown-unknown`. Only the ES256, ES384 and EdDSA algorithms are supported.
+#[derive(Debug, Clone)]
+pub enum CsdJwkVerifier {
+    ES256(p256::ecdsa::VerifyingKey),
+    ES384(p384::ecdsa::VerifyingKey),
+    /// EdDSA over Ed25519.
+    EdDSA(ed25519_dalek::VerifyingKey),
+}
+
+impl CsdJwkVerifier {
+    pub const ALGORITHMS: [&'static str; 3] = ["ES256", "ES384", "EdDSA"];
+
+    /// Reads a public `EC` (P-256 or P-384) or `OKP` (Ed25519) JWK. Its `alg`, if any, must be
+    /// the one of the curve.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::Jws`] if the key is malformed, and [`CsdError::AlgorithmNotAllowed`]
+    /// if its algorithm isn't supported.
+    pub fn from_jwk(jwk: &Map<String, Value>) -> CsdResult<Self> {
+        let parameter = |name: &str| -> CsdResult<Vec<u8>> {
+            let value = jwk
+                .get(name)
+                .and_then(Value::as_str)
+                .ok_or(CsdError::Jws(format!("the JWK has no {name} parameter")))?;
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(value)
+                .map_err(|err| CsdError::Jws(format!("the JWK {name} parameter is not base64url: {err}")))
+        };
+        let kty = jwk.get("kty").and_then(Value::as_str).unwrap_or_default();
+        let crv = jwk.get("crv").and_then(Value::as_str).unwrap_or_default();
+        let verifier = match (kty, crv) {
+            ("EC", "P-256") => {
+                let point = p256::EncodedPoint::from_affine_coordinates(
+                    field_bytes::<32>(&parameter("x")?)?.as_slice().into(),
+                    field_bytes::<32>(&parameter("y")?)?.as_slice().into(),
+                    false,
+                );
+                let key = p256::ecdsa::VerifyingKey::from_encoded_point(&point)
+                    .map_err(|err| CsdError::Jws(format!("invalid P-256 key: {err}")))?;
+                CsdJwkVerifier::ES256(key)
+            }
+            ("EC", "P-384") => {
+                let point = p384::EncodedPoint::from_affine_coordinates(
+                    field_bytes::<48>(&parameter("x")?)?.as_slice().into(),
+                    field_bytes::<48>(&parameter("y")?)?.as_slice().into(),
+                    false,
+                );
+                let key = p384::ecdsa::VerifyingKey::from_encoded_point(&point)
+                    .map_err(|err| CsdError::Jws(format!("invalid P-384 key: {err}")))?;
+                CsdJwkVerifier::ES384(key)
+            }
+            ("OKP", "Ed25519") => {
+                let key = ed25519_dalek::VerifyingKey::from_bytes(&field_bytes::<32>(&parameter("x")?)?)
+                    .map_err(|err| CsdError::Jws(format!("invalid Ed25519 key: {err}")))?;
+                CsdJwkVerifier::EdDSA(key)
+            }
+            _ => return Err(CsdError::AlgorithmNotAllowed(format!("{kty} {crv} keys"))),
+        };
+
+        match jwk.get("alg").and_then(Value::as_str) {
+            Some(alg) if alg != verifier.algorithm() => Err(CsdError::AlgorithmNotAllowed(format!("{alg} with a {crv} key"))),
+            _ => Ok(verifier),
+        }
+    }
+
+    /// The JWS `alg` header value.
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            CsdJwkVerifier::ES256(_) => "ES256",
+            CsdJwkVerifier::ES384(_) => "ES384",
+            CsdJwkVerifier::EdDSA(_) => "EdDSA",
+        }
+    }
+
+    /// Checks a JWS `signature` (the raw `r || s` for ECDSA) of `message`.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> CsdResult<()> {
+        let invalid = |err: String| CsdError::Jws(format!("invalid signature: {err}"));
+        match self {
+            CsdJwkVerifier::ES256(key) => {
+                let signature = p256::ecdsa::Signature::from_slice(signature).map_err(|err| invalid(err.to_string()))?;
+                key.verify(message, &signature).map_err(|err| invalid(err.to_string()))
+            }
+            CsdJwkVerifier::ES384(key) => {
+                let signature = p384::ecdsa::Signature::from_slice(signature).map_err(|err| invalid(err.to_string()))?;
+                key.verify(message, &signature).map_err(|err| invalid(err.to_string()))
+            }
+            CsdJwkVerifier::EdDSA(key) => {
+                let signature = ed25519_dalek::Signature::from_slice(signature).map_err(|err| invalid(err.to_string()))?;
+                key.verify(message, &signature).map_err(|err| invalid(err.to_string()))
+            }
+        }
+    }
+}
+
+/// A coordinate of `N` bytes, as JWKs encode them.
+fn field_bytes<const N: usize>(bytes: &[u8]) -> CsdResult<[u8; N]> {
+    bytes
+        .try_into()
+        .map_err(|_| CsdError::Jws(format!("key coordinate has {} bytes instead of {N}", bytes.len())))
+}
+
+///
+/// verify_compact_jws(jwt: &str, verifier: &CsdJwkVerifier, allowed_algorithms: &[String]) -> CsdResult<(JoseHeader, Map<String, Value>)>
+///
+/// Same as [`csd::csd_jws::verify_jwt`] with a [`CsdJwkVerifier`]: checks the `typ` and `alg`
+/// headers and the signature of the issuer JWT, and returns its header and payload.
+///
+pub fn verify_compact_jws(jwt: &str, verifier: &CsdJwkVerifier, allowed_algorithms: &[String]) -> CsdResult<(JoseHeader, Map<String, Value>)> {
+    let parts: Vec<&str> = jwt.split('.').collect();
+    let [header, payload, signature] = parts[..] else {
+        return Err(CsdError::Jws(String::from("the JWT is not a compact JWS")));
+    };
+    let decode = |part: &str| -> CsdResult<Vec<u8>> {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(part)
+            .map_err(|err| CsdError::Jws(err.to_string()))
+    };
+    let json = |part: &str| -> CsdResult<Map<String, Value>> {
+        serde_json::from_slice(&decode(part)?).map_err(|err| CsdError::Jws(err.to_string()))
+    };
+    let header_object = json(header)?;
+
+    let alg = match header_object.get("alg") {
+        Some(Value::String(alg)) => alg.to_owned(),
+        _ => return Err(CsdError::Jws(String::from("missing alg header"))),
+    };
+    if !allowed_algorithms.contains(&alg) {
+        return Err(CsdError::AlgorithmNotAllowed(alg));
+    }
+    if alg != verifier.algorithm() {
+        return Err(CsdError::AlgorithmNotAllowed(format!("{} (verifier expects {})", alg, verifier.algorithm())));
+    }
+    match header_object.get("typ") {
+        Some(Value::String(typ)) if typ == HEADER_TYP || typ == VC_HEADER_TYP => {}
+        typ => return Err(CsdError::Jws(format!("unexpected typ header {:?}", typ))),
+    }
+
+    verifier.verify(format!("{header}.{payload}").as_bytes(), &decode(signature)?)?;
+    Ok((header_object, json(payload)?))
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::{json, Map, Value};
+
+    use crate::csd::csd_decoder::CsdDecoder;
+    use crate::csd::csd_disclosure::CsdDisclosure;
+    use crate::csd::csd_error::{CsdError, CsdResult};
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_jwt::CsdJwt;
+
+    use super::CsdJwkVerifier;
+
+    #[test]
+    fn josekit_interop() {
+        for alg in [CsdJwsAlgorithm::ES256, CsdJwsAlgorithm::ES384, CsdJwsAlgorithm::EdDSA] {
+            let jwk = alg.generate_jwk().unwrap();
+            let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+            let credential = issuer.issue(json!({"name": "Alice", "age": 30})).unwrap().presentation();
+            let presentation = CsdHolder::new(&credential).unwrap().present(&["/age"]).unwrap();
+
+            let public_jwk: Map<String, Value> = serde_json::from_str(&jwk.to_public_key().unwrap().to_string()).unwrap();
+            let verifier = CsdJwkVerifier::from_jwk(&public_jwk).unwrap();
+            assert_eq!(verifier.algorithm(), alg.name());
+            let decoder = CsdDecoder::<Bn254>::new();
+            let (object, header) = decoder.decode_jwt_with_jwk(&presentation, &verifier).unwrap();
+            assert_eq!(header["alg"], alg.name());
+            let disclosures = parse(&presentation).unwrap();
+            assert_eq!(Value::Object(decoder.verify_disclosures(&object, disclosures).unwrap()), json!({"age": 30}));
+
+            let mut tampered = presentation.jwt.clone();
+            tampered.insert(tampered.rfind('.').unwrap() + 1, 'A');
+            let tampered = CsdJwt::new(tampered, vec![], None);
+            assert!(matches!(decoder.decode_jwt_with_jwk(&tampered, &verifier).unwrap_err(), CsdError::Jws(_)));
+        }
+
+        let rsa: Map<String, Value> = serde_json::from_str(&CsdJwsAlgorithm::PS256.generate_jwk().unwrap().to_string()).unwrap();
+        assert!(matches!(CsdJwkVerifier::from_jwk(&rsa).unwrap_err(), CsdError::AlgorithmNotAllowed(_)));
+    }
+
+    fn parse(presentation: &CsdJwt) -> CsdResult<Vec<CsdDisclosure>> {
+        presentation.disclosures.iter().map(|disclosure| CsdDisclosure::parse(disclosure.to_owned())).collect::<Result<_, _>>().map_err(CsdError::from)
+    }
+}
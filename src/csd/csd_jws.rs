@@ -0,0 +1,255 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use josekit::{
+    jwk::{Jwk, alg::ec::EcCurve, alg::ed::EdCurve},
+    jws::{EdDSA, ES256, ES384, ES512, JwsHeader, JwsSigner, JwsVerifier, PS256},
+    jwt,
+    jwt::JwtPayload,
+};
+use serde_json::{Map, Value};
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+
+use crate::bls::{BlsCurve, BlsJwsAlgorithm};
+use crate::csd;
+use csd::csd_encoder::HEADER_TYP;
+use csd::csd_vc::VC_HEADER_TYP;
+use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_jwt::CsdJwt;
+
+/// RSA modulus size used when generating PS256 keys.
+const RSA_KEY_BITS: u32 = 2048;
+
+/// The `josekit` JWS algorithms CSD-JWTs can be issued with, besides the BLS ones in [`crate::bls`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsdJwsAlgorithm {
+    ES256,
+    ES384,
+    ES512,
+    /// EdDSA over Ed25519.
+    EdDSA,
+    PS256,
+}
+
+impl CsdJwsAlgorithm {
+    pub const ALL: [CsdJwsAlgorithm; 5] = [
+        CsdJwsAlgorithm::ES256,
+        CsdJwsAlgorithm::ES384,
+        CsdJwsAlgorithm::ES512,
+        CsdJwsAlgorithm::EdDSA,
+        CsdJwsAlgorithm::PS256,
+    ];
+
+    /// The JWS `alg` header value.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CsdJwsAlgorithm::ES256 => "ES256",
+            CsdJwsAlgorithm::ES384 => "ES384",
+            CsdJwsAlgorithm::ES512 => "ES512",
+            CsdJwsAlgorithm::EdDSA => "EdDSA",
+            CsdJwsAlgorithm::PS256 => "PS256",
+        }
+    }
+
+    /// Generates a new private JWK for this algorithm, with its `alg` parameter set.
+    pub fn generate_jwk(&self) -> CsdResult<Jwk> {
+        let mut jwk = match self {
+            CsdJwsAlgorithm::ES256 => Jwk::generate_ec_key(EcCurve::P256),
+            CsdJwsAlgorithm::ES384 => Jwk::generate_ec_key(EcCurve::P384),
+            CsdJwsAlgorithm::ES512 => Jwk::generate_ec_key(EcCurve::P521),
+            CsdJwsAlgorithm::EdDSA => Jwk::generate_ed_key(EdCurve::Ed25519),
+            CsdJwsAlgorithm::PS256 => Jwk::generate_rsa_key(RSA_KEY_BITS),
+        }.map_err(|err| CsdError::Jws(err.to_string()))?;
+        jwk.set_algorithm(self.name());
+        Ok(jwk)
+    }
+
+    pub fn signer_from_jwk(&self, jwk: &Jwk) -> CsdResult<Box<dyn JwsSigner>> {
+        let signer: Box<dyn JwsSigner> = match self {
+            CsdJwsAlgorithm::ES256 => Box::new(ES256.signer_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+            CsdJwsAlgorithm::ES384 => Box::new(ES384.signer_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+            CsdJwsAlgorithm::ES512 => Box::new(ES512.signer_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+            CsdJwsAlgorithm::EdDSA => Box::new(EdDSA.signer_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+            CsdJwsAlgorithm::PS256 => Box::new(PS256.signer_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+        };
+        Ok(signer)
+    }
+
+    pub fn verifier_from_jwk(&self, jwk: &Jwk) -> CsdResult<Box<dyn JwsVerifier>> {
+        let verifier: Box<dyn JwsVerifier> = match self {
+            CsdJwsAlgorithm::ES256 => Box::new(ES256.verifier_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+            CsdJwsAlgorithm::ES384 => Box::new(ES384.verifier_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+            CsdJwsAlgorithm::ES512 => Box::new(ES512.verifier_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+            CsdJwsAlgorithm::EdDSA => Box::new(EdDSA.verifier_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+            CsdJwsAlgorithm::PS256 => Box::new(PS256.verifier_from_jwk(jwk).map_err(|err| CsdError::Jws(err.to_string()))?),
+        };
+        Ok(verifier)
+    }
+}
+
+impl Display for CsdJwsAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for CsdJwsAlgorithm {
+    type Err = CsdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CsdJwsAlgorithm::ALL
+            .into_iter()
+            .find(|alg| alg.name() == s)
+            .ok_or(CsdError::AlgorithmNotAllowed(s.to_string()))
+    }
+}
+
+///
+/// issue(object: &Map<String, Value>, signer: &dyn JwsSigner) -> CsdResult<CsdJwt>
+///
+/// Signs an object produced by [`csd::csd_encoder::CsdEncoder::object`] with any `josekit` signer.
+/// The `alg` (and `kid`, if the signer has one) header is filled in from the signer and `typ` is
+/// always [`HEADER_TYP`].
+///
+pub fn issue(object: &Map<String, Value>, signer: &dyn JwsSigner) -> CsdResult<CsdJwt> {
+    issue_with_key_id(object, signer, None)
+}
+
+///
+/// issue_with_key_id(object: &Map<String, Value>, signer: &dyn JwsSigner, key_id: Option<&str>) -> CsdResult<CsdJwt>
+///
+/// Same as [`issue`], setting the `kid` header to `key_id` (e.g. a DID URL) unless the signer
+/// has a key id of its own.
+///
+pub fn issue_with_key_id(object: &Map<String, Value>, signer: &dyn JwsSigner, key_id: Option<&str>) -> CsdResult<CsdJwt> {
+    issue_typed(object, signer, key_id, HEADER_TYP)
+}
+
+///
+/// issue_typed(object: &Map<String, Value>, signer: &dyn JwsSigner, key_id: Option<&str>, typ: &str) -> CsdResult<CsdJwt>
+///
+/// Same as [`issue_with_key_id`] with the `typ` header of a profile, e.g. [`VC_HEADER_TYP`].
+///
+pub fn issue_typed(object: &Map<String, Value>, signer: &dyn JwsSigner, key_id: Option<&str>, typ: &str) -> CsdResult<CsdJwt> {
+    let mut header = JwsHeader::new();
+    header.set_token_type(typ);
+    if let Some(key_id) = key_id {
+        header.set_key_id(key_id);
+    }
+    let payload = JwtPayload::from_map(object.clone()).map_err(|err| CsdError::Jws(err.to_string()))?;
+    let jwt = jwt::encode_with_signer(&payload, &header, signer).map_err(|err| CsdError::Jws(err.to_string()))?;
+
+    Ok(CsdJwt::new(jwt, vec![], None))
+}
+
+/// Algorithms accepted by default: every [`CsdJwsAlgorithm`] and the BLS ones from [`crate::bls`].
+/// Symmetric and unsecured (`none`) algorithms are never accepted by default.
+pub fn default_allowed_algorithms() -> Vec<String> {
+    let mut allowed: Vec<String> = CsdJwsAlgorithm::ALL.iter().map(|alg| alg.name().to_string()).collect();
+    allowed.push(String::from(Bls12_381::JWS_ALG));
+    allowed.push(String::from(Bn254::JWS_ALG));
+    allowed
+}
+
+///
+/// verifier_for(alg: &str, jwk: &Jwk) -> CsdResult<Box<dyn JwsVerifier>>
+///
+/// A verifier for the `alg` header value and a public JWK, e.g. one resolved from the issuer's DID.
+///
+pub fn verifier_for(alg: &str, jwk: &Jwk) -> CsdResult<Box<dyn JwsVerifier>> {
+    if alg == Bls12_381::JWS_ALG {
+        let verifier = BlsJwsAlgorithm::<Bls12_381>::new()
+            .verifier_from_jwk(jwk)
+            .map_err(|err| CsdError::Jws(err.to_string()))?;
+        return Ok(Box::new(verifier));
+    }
+    if alg == Bn254::JWS_ALG {
+        let verifier = BlsJwsAlgorithm::<Bn254>::new()
+            .verifier_from_jwk(jwk)
+            .map_err(|err| CsdError::Jws(err.to_string()))?;
+        return Ok(Box::new(verifier));
+    }
+    CsdJwsAlgorithm::from_str(alg)?.verifier_from_jwk(jwk)
+}
+
+///
+/// verify_jwt(csd_jwt: &CsdJwt, verifier: &dyn JwsVerifier, allowed_algorithms: &[String]) -> CsdResult<(JwtPayload, JwsHeader)>
+///
+/// Checks the `typ` and `alg` headers of the issuer JWT against [`HEADER_TYP`] (or
+/// [`VC_HEADER_TYP`]) and the allow-list
+/// before verifying its signature. The `alg` must also be the verifier's, so a token can't pick a
+/// weaker algorithm than the one the verifier was configured with.
+///
+pub fn verify_jwt(csd_jwt: &CsdJwt, verifier: &dyn JwsVerifier, allowed_algorithms: &[String]) -> CsdResult<(JwtPayload, JwsHeader)> {
+    let header = jwt::decode_header(&csd_jwt.jwt).map_err(|err| CsdError::Jws(err.to_string()))?;
+
+    let alg = match header.claim("alg") {
+        Some(Value::String(alg)) => alg.to_owned(),
+        _ => return Err(CsdError::Jws(String::from("missing alg header"))),
+    };
+    if !allowed_algorithms.contains(&alg) {
+        return Err(CsdError::AlgorithmNotAllowed(alg));
+    }
+    if alg != verifier.algorithm().name() {
+        return Err(CsdError::AlgorithmNotAllowed(format!("{} (verifier expects {})", alg, verifier.algorithm().name())));
+    }
+    match header.claim("typ") {
+        Some(Value::String(typ)) if typ == HEADER_TYP || typ == VC_HEADER_TYP => {}
+        typ => return Err(CsdError::Jws(format!("unexpected typ header {:?}", typ))),
+    }
+
+    jwt::decode_with_verifier(&csd_jwt.jwt, verifier).map_err(|err| CsdError::Jws(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::json;
+
+    use crate::csd::csd_decoder::CsdDecoder;
+    use crate::csd::csd_encoder::CsdEncoder;
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_jwt::CsdJwt;
+
+    use super::{issue, CsdJwsAlgorithm};
+
+    fn object() -> serde_json::Map<String, serde_json::Value> {
+        let mut encoder = CsdEncoder::<Bn254>::try_from(json!({"name": "Alice", "age": 30})).unwrap();
+        encoder.add_sd_alg_property();
+        encoder.object().unwrap().clone()
+    }
+
+    #[test]
+    fn all_algorithms() {
+        for alg in CsdJwsAlgorithm::ALL {
+            let jwk = alg.generate_jwk().unwrap();
+            let csd_jwt = issue(&object(), &*alg.signer_from_jwk(&jwk).unwrap()).unwrap();
+            let csd_jwt = CsdJwt::parse(&csd_jwt.presentation()).unwrap();
+
+            let decoder = CsdDecoder::<Bn254>::new();
+            let (decoded, header) = decoder.decode_jwt(&csd_jwt, &*alg.verifier_from_jwk(&jwk).unwrap()).unwrap();
+            assert_eq!(header.algorithm(), Some(alg.name()));
+            assert!(decoder.validate_object(decoded).unwrap());
+        }
+    }
+
+    #[test]
+    fn allow_list() {
+        let jwk = CsdJwsAlgorithm::ES256.generate_jwk().unwrap();
+        let csd_jwt = issue(&object(), &*CsdJwsAlgorithm::ES256.signer_from_jwk(&jwk).unwrap()).unwrap();
+
+        let mut decoder = CsdDecoder::<Bn254>::new();
+        decoder.set_allowed_algorithms(vec![CsdJwsAlgorithm::ES384.name().to_string()]);
+        assert!(matches!(
+            decoder.decode_jwt(&csd_jwt, &*CsdJwsAlgorithm::ES256.verifier_from_jwk(&jwk).unwrap()).unwrap_err(),
+            CsdError::AlgorithmNotAllowed(_)
+        ));
+
+        let other = CsdJwsAlgorithm::ES384.generate_jwk().unwrap();
+        assert!(matches!(
+            decoder.decode_jwt(&csd_jwt, &*CsdJwsAlgorithm::ES384.verifier_from_jwk(&other).unwrap()).unwrap_err(),
+            CsdError::AlgorithmNotAllowed(_)
+        ));
+    }
+}
@@ -2,23 +2,30 @@ use crate::csd;
 
 use std::fmt::Display;
 use std::str::FromStr;
-use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
 
 /// Representation of an SD-JWT of the format
 /// `<Issuer-signed JWT>~<Disclosure 1>~<Disclosure 2>~...~<Disclosure N>~<optional KB-JWT>`.
+///
+/// Each disclosure carries an accumulated claim together with its witness (see
+/// [`csd::csd_disclosure::CsdDisclosure::with_witness`]), so holders can drop the ones they don't
+/// want to present.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct CsdJwt {
     /// The JWT part.
     pub jwt: String,
+    /// The disclosures part.
+    pub disclosures: Vec<String>,
     /// The optional key binding JWT.
     pub key_binding_jwt: Option<String>,
 }
 
 impl CsdJwt {
     /// Creates a new [`CsdJwt`] from its components.
-    pub fn new(jwt: String, key_binding_jwt: Option<String>) -> Self {
+    pub fn new(jwt: String, disclosures: Vec<String>, key_binding_jwt: Option<String>) -> Self {
         Self {
             jwt,
+            disclosures,
             key_binding_jwt,
         }
     }
@@ -28,8 +35,9 @@ impl CsdJwt {
     /// ## Error
     /// Returns [`Error::DeserializationError`] if parsing fails.
     pub fn presentation(&self) -> String {
+        let disclosures = self.disclosures.iter().map(|disclosure| format!("{}~", disclosure)).collect::<String>();
         let key_bindings = self.key_binding_jwt.as_deref().unwrap_or("");
-        format!("{}~{}", self.jwt, key_bindings)
+        format!("{}~{}{}", self.jwt, disclosures, key_bindings)
     }
 
     /// Parses an SD-JWT into its components as [`CsdJwt`].
@@ -37,24 +45,30 @@ impl CsdJwt {
         let sd_segments: Vec<&str> = sd_jwt.split('~').collect();
         let num_of_segments = sd_segments.len();
         if num_of_segments < 2 {
-            return Err(CsdError::DeserializationError(
+            return Err(CsdError::Core(CsdCoreError::DeserializationError(
                 "SD-JWT format is invalid, less than 2 segments".to_string(),
-            ));
+            )));
         }
 
         let includes_key_binding = sd_jwt.chars().next_back().is_some_and(|char| char != '~');
         if includes_key_binding && num_of_segments < 3 {
-            return Err(CsdError::DeserializationError(
+            return Err(CsdError::Core(CsdCoreError::DeserializationError(
                 "SD-JWT format is invalid, less than 3 segments with key binding jwt".to_string(),
-            ));
+            )));
         }
 
         let jwt = sd_segments.first().unwrap().to_string();
 
+        let disclosures: Vec<String> = sd_segments[1..num_of_segments - 1]
+            .iter()
+            .map(|disclosure| disclosure.to_string())
+            .collect();
+
         let key_binding = includes_key_binding.then(|| sd_segments[num_of_segments - 1].to_string());
 
         Ok(Self {
             jwt,
+            disclosures,
             key_binding_jwt: key_binding,
         })
     }
@@ -0,0 +1,147 @@
+use base64::Engine;
+use josekit::jwk::Jwk;
+use josekit::jws::{JwsHeader, JwsSigner};
+use josekit::jwt::{self, JwtPayload};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::csd;
+use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_jws::CsdJwsAlgorithm;
+use csd::csd_jwt::CsdJwt;
+use csd::csd_policy::{CsdValidationPolicy, AUDIENCE_KEY, CONFIRMATION_KEY, ISSUED_AT_KEY};
+
+/// Header `typ` of key binding JWTs.
+pub const KB_JWT_TYP: &str = "kb+jwt";
+pub const NONCE_KEY: &str = "nonce";
+/// Digest of the presentation the KB-JWT is bound to.
+pub const SD_HASH_KEY: &str = "sd_hash";
+
+///
+/// sd_hash(csd_jwt: &CsdJwt) -> String
+///
+/// base64url SHA-256 of the presentation without its KB-JWT, i.e. `<jwt>~<d1>~...~<dn>~`.
+///
+pub fn sd_hash(csd_jwt: &CsdJwt) -> String {
+    let presentation = CsdJwt::new(csd_jwt.jwt.clone(), csd_jwt.disclosures.clone(), None).presentation();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(presentation))
+}
+
+///
+/// issue_key_binding(csd_jwt: &CsdJwt, signer: &dyn JwsSigner, audience: &str, nonce: &str, issued_at: i64) -> CsdResult<String>
+///
+/// Signs a KB-JWT over `csd_jwt` with the holder key, for the verifier `audience`.
+///
+pub fn issue_key_binding(csd_jwt: &CsdJwt, signer: &dyn JwsSigner, audience: &str, nonce: &str, issued_at: i64) -> CsdResult<String> {
+    let mut header = JwsHeader::new();
+    header.set_token_type(KB_JWT_TYP);
+
+    let mut claims = Map::new();
+    claims.insert(String::from(ISSUED_AT_KEY), Value::from(issued_at));
+    claims.insert(String::from(AUDIENCE_KEY), Value::String(audience.to_string()));
+    claims.insert(String::from(NONCE_KEY), Value::String(nonce.to_string()));
+    claims.insert(String::from(SD_HASH_KEY), Value::String(sd_hash(csd_jwt)));
+    let payload = JwtPayload::from_map(claims).map_err(|err| CsdError::KeyBinding(err.to_string()))?;
+
+    jwt::encode_with_signer(&payload, &header, signer).map_err(|err| CsdError::KeyBinding(err.to_string()))
+}
+
+///
+/// verify_key_binding(csd_jwt: &CsdJwt, issuer_claims: &Map<String, Value>, policy: &CsdValidationPolicy) -> CsdResult<()>
+///
+/// Verifies the KB-JWT of `csd_jwt` against the holder key in the `cnf.jwk` claim of the
+/// (already verified) issuer JWT, checks that it's bound to this very presentation and applies
+/// `policy` to its claims. Presentations without KB-JWT pass unless the policy requires one.
+///
+pub fn verify_key_binding(csd_jwt: &CsdJwt, issuer_claims: &Map<String, Value>, policy: &CsdValidationPolicy) -> CsdResult<()> {
+    let key_binding_jwt = match &csd_jwt.key_binding_jwt {
+        Some(key_binding_jwt) => key_binding_jwt,
+        None if policy.requires_key_binding() => return Err(CsdError::KeyBinding(String::from("presentation has no KB-JWT"))),
+        None => return Ok(()),
+    };
+
+    let holder_key = match issuer_claims.get(CONFIRMATION_KEY).and_then(|cnf| cnf.get("jwk")) {
+        Some(Value::Object(jwk)) => Jwk::from_map(jwk.clone()).map_err(|err| CsdError::KeyBinding(err.to_string()))?,
+        _ => return Err(CsdError::KeyBinding(String::from("credential has no cnf.jwk holder key"))),
+    };
+    let header = jwt::decode_header(key_binding_jwt).map_err(|err| CsdError::KeyBinding(err.to_string()))?;
+    if header.claim("typ").and_then(Value::as_str) != Some(KB_JWT_TYP) {
+        return Err(CsdError::KeyBinding(format!("typ header is not {}", KB_JWT_TYP)));
+    }
+    let alg = header
+        .claim("alg")
+        .and_then(Value::as_str)
+        .ok_or(CsdError::KeyBinding(String::from("missing alg header")))?;
+    let verifier = alg.parse::<CsdJwsAlgorithm>()?.verifier_from_jwk(&holder_key)?;
+    let (payload, _) = jwt::decode_with_verifier(key_binding_jwt, &*verifier).map_err(|err| CsdError::KeyBinding(err.to_string()))?;
+
+    if payload.claim(SD_HASH_KEY).and_then(Value::as_str) != Some(sd_hash(csd_jwt).as_str()) {
+        return Err(CsdError::KeyBinding(String::from("sd_hash does not match the presentation")));
+    }
+    policy.validate_key_binding_claims(payload.claims_set())
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::{json, Value};
+
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_jwt::CsdJwt;
+    use crate::csd::csd_policy::{CsdValidationPolicy, FixedClock};
+    use crate::csd::csd_verifier::CsdVerifier;
+
+    const NOW: i64 = 1_700_000_000;
+    const AUDIENCE: &str = "https://verifier.example";
+    const NONCE: &str = "n-0S6_WzA2Mj";
+
+    fn verifier(jwk: &josekit::jwk::Jwk, now: i64) -> CsdVerifier<'static, Bn254> {
+        let mut policy = CsdValidationPolicy::new();
+        policy.set_clock(Box::new(FixedClock(now)));
+        policy.set_audience(Some(String::from(AUDIENCE)));
+        policy.set_nonce(Some(String::from(NONCE)));
+        policy.set_key_binding_max_age(Some(300));
+        policy.set_require_key_binding(true);
+
+        let mut verifier = CsdVerifier::<Bn254>::new(CsdJwsAlgorithm::ES256.verifier_from_jwk(jwk).unwrap());
+        verifier.set_policy(policy);
+        verifier
+    }
+
+    #[test]
+    fn key_bound_presentation() {
+        let issuer_jwk = CsdJwsAlgorithm::ES256.generate_jwk().unwrap();
+        let holder_jwk = CsdJwsAlgorithm::EdDSA.generate_jwk().unwrap();
+        let holder_signer = CsdJwsAlgorithm::EdDSA.signer_from_jwk(&holder_jwk).unwrap();
+
+        let issuer = CsdIssuer::<Bn254>::new(CsdJwsAlgorithm::ES256.signer_from_jwk(&issuer_jwk).unwrap());
+        let credential = issuer.issue(json!({
+            "name": "Alice",
+            "age": 30,
+            "iat": NOW - 3600,
+            "exp": NOW + 3600,
+            "cnf": {"jwk": holder_jwk.to_public_key().unwrap().as_ref()},
+        })).unwrap().presentation();
+
+        let holder = CsdHolder::new(&credential).unwrap();
+        // The JWT claims are in the issuer JWT, not in the disclosures.
+        assert_eq!(holder.disclosures().len(), 2);
+        let presentation = holder.present_with_key_binding(&["/name"], &*holder_signer, AUDIENCE, NONCE, NOW).unwrap();
+        let claims = verifier(&issuer_jwk, NOW).verify(&presentation.presentation()).unwrap();
+        assert_eq!(Value::Object(claims), json!({"name": "Alice"}));
+
+        // Expired credential, stale KB-JWT, missing KB-JWT.
+        assert!(matches!(verifier(&issuer_jwk, NOW + 7200).verify(&presentation.presentation()).unwrap_err(), CsdError::ClaimValidation(_)));
+        assert!(matches!(verifier(&issuer_jwk, NOW + 600).verify(&presentation.presentation()).unwrap_err(), CsdError::KeyBinding(_)));
+        assert!(matches!(verifier(&issuer_jwk, NOW).verify(&holder.present(&["/name"]).unwrap().presentation()).unwrap_err(), CsdError::KeyBinding(_)));
+
+        // Wrong audience, and a KB-JWT moved onto another presentation.
+        let other = holder.present_with_key_binding(&["/name"], &*holder_signer, "https://other.example", NONCE, NOW).unwrap();
+        assert!(matches!(verifier(&issuer_jwk, NOW).verify(&other.presentation()).unwrap_err(), CsdError::KeyBinding(_)));
+        let moved = CsdJwt::new(presentation.jwt.clone(), CsdJwt::parse(&credential).unwrap().disclosures, presentation.key_binding_jwt.clone());
+        assert!(matches!(verifier(&issuer_jwk, NOW).verify(&moved.presentation()).unwrap_err(), CsdError::KeyBinding(_)));
+    }
+}
@@ -0,0 +1,145 @@
+use std::str::FromStr;
+use ark_ec::pairing::Pairing;
+use ark_ff::UniformRand;
+use rand::rngs::OsRng;
+use serde_json::Map;
+use serde_json::Value;
+use vb_accumulator::kb_universal_accumulator::proofs_keyed_verification::KBUniversalAccumulatorMembershipProofProtocol;
+use vb_accumulator::kb_universal_accumulator::witness::KBUniversalAccumulatorMembershipWitness;
+use vb_accumulator::positive::Accumulator;
+use vb_accumulator::setup::SecretKey;
+use csd::csd_error::CsdError;
+
+use crate::csd;
+use csd::csd_decoder::CsdDecoder;
+use csd::csd_issuance::remove_plain_claims;
+use csd::csd_encoder::{ACCUMULATOR_KEY, KEYED_SD_ALG_SUFFIX, PARAM_SEED_KEY, PK_KEY, SD_ALG};
+use crate::accumulator::{deserialize_accumulator, deserialize_witness, scalar_from_str};
+
+/// Issuer-side decoder for tokens of the keyed-verification suite.
+///
+/// Witnesses of the KB universal accumulator are checked with vb_accumulator's keyed-verification
+/// membership proofs, which need the accumulator's secret key instead of a pairing. Verification is
+/// therefore restricted to whoever holds the key, i.e. the issuer itself.
+pub struct CsdKeyedDecoder<C: Pairing> {
+    secret_key: SecretKey<C::ScalarField>,
+}
+
+impl<C: Pairing> CsdKeyedDecoder<C> {
+    /// Creates a new [`CsdKeyedDecoder`] verifying with `secret_key`.
+    pub fn new(secret_key: SecretKey<C::ScalarField>) -> Self {
+        CsdKeyedDecoder::<C> { secret_key }
+    }
+
+    /// Decodes a CSD-JWT `object` the same way [`CsdDecoder::decode`] does.
+    pub fn decode(
+        &self,
+        object: &Map<String, Value>,
+    ) -> Result<Map<String, Value>, CsdError> {
+        CsdDecoder::<C>::new().decode(object)
+    }
+
+    /// Verifies every accumulated claim of a decoded keyed-verification `object`.
+    pub fn validate_object(&self, mut object: Map<String, Value>) -> Result<bool, CsdError> {
+        let sd_alg = match object.remove(SD_ALG) {
+            Some(Value::String(val)) => val,
+            Some(_) => return Err(CsdError::Unspecified(String::from("Selective Disclosure algorithm found is not a string!"))),
+            None => return Err(CsdError::Unspecified(String::from("No Selective Disclosure algorithm found!")))
+        };
+        if sd_alg != format!("{}{}", std::any::type_name::<C>(), KEYED_SD_ALG_SUFFIX) {
+            return Err(CsdError::Unspecified(format!("Selective Disclosure algorithm {} is not a keyed-verification suite for this curve!", sd_alg)))
+        }
+
+        let accumulator = match object.remove(ACCUMULATOR_KEY) {
+            Some(Value::String(val)) => deserialize_accumulator::<C>(val)?,
+            Some(_) => return Err(CsdError::Unspecified(String::from("Accumulator value found is not a string!"))),
+            None => return Err(CsdError::Unspecified(String::from("No accumulator found!")))
+        };
+
+        // The keyed setup params are only needed for issuance, but their seed is still part of the token.
+        match object.remove(PARAM_SEED_KEY) {
+            Some(Value::String(val)) => u64::from_str(val.as_str())
+                .map_err(|err| CsdError::Unspecified(format!("Param seed value is a string but can't be converted to u64! {:?}", err)))?,
+            Some(_) => return Err(CsdError::Unspecified(String::from("Param seed value found is not a string!"))),
+            None => return Err(CsdError::Unspecified(String::from("No param seed found!")))
+        };
+        remove_plain_claims(&mut object)?;
+        if object.contains_key(PK_KEY) {
+            return Err(CsdError::Unspecified(String::from("Keyed-verification tokens must not carry a public key!")))
+        }
+
+        for (key, value) in object {
+            let element = scalar_from_str::<C>(key.as_str());
+            let witness = deserialize_witness::<C>(match value {
+                Value::String(val) => val,
+                _ => return Err(CsdError::Unspecified(format!("Witness [{:?}] not a string", value)))
+            })?;
+            // Blinding the element with a known value binds the proof to this claim: its response
+            // for the element must then be that blinding plus the challenge times the element.
+            let blinding = C::ScalarField::rand(&mut OsRng);
+            let challenge = C::ScalarField::rand(&mut OsRng);
+            let proof = KBUniversalAccumulatorMembershipProofProtocol::init(
+                &mut OsRng,
+                element,
+                Some(blinding),
+                &KBUniversalAccumulatorMembershipWitness(witness),
+                accumulator.value(),
+            )
+                .gen_proof(&challenge)
+                .map_err(|err| CsdError::Unspecified(format!("{:?}", err)))?;
+            if proof.verify(accumulator.value(), &self.secret_key, &challenge).is_err()
+                || *proof.0.get_schnorr_response_for_element() != blinding + challenge * element {
+                return Ok(false)
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::{Bn254, Fr};
+    use serde_json::{json, Value};
+    use vb_accumulator::setup::SecretKey;
+
+    use crate::accumulator::generate_secret_key;
+    use crate::csd::csd_encoder::{CsdEncoder, PK_KEY};
+
+    use super::CsdKeyedDecoder;
+
+    fn encoded(secret_key: &SecretKey<Fr>) -> serde_json::Map<String, Value> {
+        let object = json!({
+            "employee": "Alice",
+            "building": "HQ",
+            "clearance": 3
+        });
+        let mut encoder = CsdEncoder::<Bn254>::try_from(object).unwrap();
+        encoder.set_secret_key(secret_key.clone());
+        encoder.set_param_seed(1u64);
+        encoder.add_keyed_sd_alg_property();
+        encoder.object().unwrap().clone()
+    }
+
+    #[test]
+    fn keyed_round_trip() {
+        let secret_key = generate_secret_key::<Bn254>();
+        let object = encoded(&secret_key);
+        assert!(!object.contains_key(PK_KEY));
+
+        let decoder = CsdKeyedDecoder::<Bn254>::new(secret_key);
+        let decoded = decoder.decode(&object).unwrap();
+        assert!(decoder.validate_object(decoded.clone()).unwrap());
+
+        let mut tampered = decoded;
+        let witness = tampered.remove("clearance::3").unwrap();
+        tampered.insert(String::from("clearance::5"), witness);
+        assert!(!decoder.validate_object(tampered).unwrap());
+    }
+
+    #[test]
+    fn wrong_key() {
+        let decoder = CsdKeyedDecoder::<Bn254>::new(generate_secret_key::<Bn254>());
+        assert!(!decoder.validate_object(encoded(&generate_secret_key::<Bn254>())).unwrap());
+    }
+}
@@ -0,0 +1,286 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Value};
+
+use crate::csd;
+use csd::csd_encoder::ISSUER_KEY;
+use csd::csd_error::{CsdError, CsdResult};
+
+pub const EXPIRATION_KEY: &str = "exp";
+pub const NOT_BEFORE_KEY: &str = "nbf";
+pub const ISSUED_AT_KEY: &str = "iat";
+pub const AUDIENCE_KEY: &str = "aud";
+/// Confirmation claim holding the holder key of key-bound credentials.
+pub const CONFIRMATION_KEY: &str = "cnf";
+/// Registered JWT claims, which are never accumulated and always sit in the issuer JWT payload.
+pub const JWT_CLAIMS: [&str; 6] = [ISSUER_KEY, AUDIENCE_KEY, EXPIRATION_KEY, NOT_BEFORE_KEY, ISSUED_AT_KEY, CONFIRMATION_KEY];
+/// Clock skew tolerated by default, in seconds.
+pub const DEFAULT_CLOCK_SKEW: i64 = 60;
+
+/// Source of the current time, in seconds since the Unix epoch.
+pub trait CsdClock {
+    fn now(&self) -> i64;
+}
+
+/// The system clock, or the JavaScript `Date` on wasm32, which has no system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl CsdClock for SystemClock {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn now(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn now(&self) -> i64 {
+        (js_sys::Date::now() / 1000.0) as i64
+    }
+}
+
+/// A clock stuck at a given time, for tests and for re-validating past presentations.
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub i64);
+
+impl CsdClock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
+
+/// Time, issuer and audience checks on the issuer JWT and the KB-JWT.
+///
+/// By default only the time claims present in a token are checked, against the system clock with
+/// [`DEFAULT_CLOCK_SKEW`]; the other checks are enabled by their setters.
+pub struct CsdValidationPolicy {
+    clock: Box<dyn CsdClock>,
+    clock_skew: i64,
+    max_age: Option<i64>,
+    key_binding_max_age: Option<i64>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    nonce: Option<String>,
+    require_key_binding: bool,
+}
+
+impl Default for CsdValidationPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CsdValidationPolicy {
+    pub fn new() -> Self {
+        Self {
+            clock: Box::new(SystemClock),
+            clock_skew: DEFAULT_CLOCK_SKEW,
+            max_age: None,
+            key_binding_max_age: None,
+            issuer: None,
+            audience: None,
+            nonce: None,
+            require_key_binding: false,
+        }
+    }
+
+    pub fn set_clock(&mut self, clock: Box<dyn CsdClock>) {
+        self.clock = clock;
+    }
+
+    /// Seconds of tolerance on every time comparison.
+    pub fn set_clock_skew(&mut self, clock_skew: i64) {
+        self.clock_skew = clock_skew;
+    }
+
+    /// Rejects credentials whose `iat` is older than `max_age` seconds, or that have no `iat`.
+    pub fn set_max_age(&mut self, max_age: Option<i64>) {
+        self.max_age = max_age;
+    }
+
+    /// Rejects KB-JWTs whose `iat` is older than `max_age` seconds.
+    pub fn set_key_binding_max_age(&mut self, max_age: Option<i64>) {
+        self.key_binding_max_age = max_age;
+    }
+
+    /// Requires the issuer JWT `iss` to be `issuer`.
+    pub fn set_issuer(&mut self, issuer: Option<String>) {
+        self.issuer = issuer;
+    }
+
+    /// The verifier's identifier: the KB-JWT `aud` must be it, and so must (one of) the issuer JWT
+    /// `aud`, if present. Tokens carrying an `aud` are rejected while no audience is set.
+    pub fn set_audience(&mut self, audience: Option<String>) {
+        self.audience = audience;
+    }
+
+    /// Requires the KB-JWT `nonce` to be `nonce`.
+    pub fn set_nonce(&mut self, nonce: Option<String>) {
+        self.nonce = nonce;
+    }
+
+    /// Rejects presentations without a KB-JWT.
+    pub fn set_require_key_binding(&mut self, require_key_binding: bool) {
+        self.require_key_binding = require_key_binding;
+    }
+
+    pub fn requires_key_binding(&self) -> bool {
+        self.require_key_binding
+    }
+
+    pub fn now(&self) -> i64 {
+        self.clock.now()
+    }
+
+    /// Checks the `exp`, `nbf`, `iat`, `iss` and `aud` claims of the issuer JWT payload.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::ClaimValidation`] naming the first claim that fails.
+    pub fn validate_claims(&self, claims: &Map<String, Value>) -> CsdResult<()> {
+        let now = self.now();
+        self.validate_times(claims, now, self.max_age)?;
+
+        if let Some(issuer) = &self.issuer {
+            if claims.get(ISSUER_KEY).and_then(Value::as_str) != Some(issuer.as_str()) {
+                return Err(CsdError::ClaimValidation(format!("iss is not {}", issuer)));
+            }
+        }
+        if claims.contains_key(AUDIENCE_KEY) && !self.audience_matches(claims) {
+            return Err(CsdError::ClaimValidation(format!("aud does not contain {:?}", self.audience)));
+        }
+        Ok(())
+    }
+
+    /// Checks the `iat`, `exp`, `nbf`, `aud` and `nonce` claims of a KB-JWT payload. Unlike the
+    /// issuer JWT, the `iat` is mandatory and the `aud` must be present.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::KeyBinding`] naming the first claim that fails.
+    pub fn validate_key_binding_claims(&self, claims: &Map<String, Value>) -> CsdResult<()> {
+        if !claims.contains_key(ISSUED_AT_KEY) {
+            return Err(CsdError::KeyBinding(String::from("KB-JWT has no iat")));
+        }
+        self.validate_times(claims, self.now(), self.key_binding_max_age)
+            .map_err(|err| CsdError::KeyBinding(err.to_string()))?;
+
+        if !self.audience_matches(claims) {
+            return Err(CsdError::KeyBinding(format!("KB-JWT aud is not {:?}", self.audience)));
+        }
+        if let Some(nonce) = &self.nonce {
+            if claims.get("nonce").and_then(Value::as_str) != Some(nonce.as_str()) {
+                return Err(CsdError::KeyBinding(String::from("KB-JWT nonce does not match")));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_times(&self, claims: &Map<String, Value>, now: i64, max_age: Option<i64>) -> CsdResult<()> {
+        if let Some(exp) = numeric_date(claims, EXPIRATION_KEY)? {
+            if now - self.clock_skew >= exp {
+                return Err(CsdError::ClaimValidation(format!("expired at {}", exp)));
+            }
+        }
+        if let Some(nbf) = numeric_date(claims, NOT_BEFORE_KEY)? {
+            if now + self.clock_skew < nbf {
+                return Err(CsdError::ClaimValidation(format!("not valid before {}", nbf)));
+            }
+        }
+        let iat = numeric_date(claims, ISSUED_AT_KEY)?;
+        if let Some(iat) = iat {
+            if iat > now + self.clock_skew {
+                return Err(CsdError::ClaimValidation(format!("issued in the future at {}", iat)));
+            }
+        }
+        if let Some(max_age) = max_age {
+            match iat {
+                Some(iat) if now - iat <= max_age + self.clock_skew => {}
+                Some(iat) => return Err(CsdError::ClaimValidation(format!("issued at {}, more than {}s ago", iat, max_age))),
+                None => return Err(CsdError::ClaimValidation(String::from("no iat to check the maximum age against"))),
+            }
+        }
+        Ok(())
+    }
+
+    /// `aud` is a string or an array of strings, one of which must be the verifier's audience.
+    fn audience_matches(&self, claims: &Map<String, Value>) -> bool {
+        let audience = match &self.audience {
+            Some(audience) => audience,
+            None => return false,
+        };
+        match claims.get(AUDIENCE_KEY) {
+            Some(Value::String(aud)) => aud == audience,
+            Some(Value::Array(auds)) => auds.iter().any(|aud| aud.as_str() == Some(audience.as_str())),
+            _ => false,
+        }
+    }
+}
+
+/// A NumericDate claim, in whole seconds.
+fn numeric_date(claims: &Map<String, Value>, name: &str) -> CsdResult<Option<i64>> {
+    match claims.get(name) {
+        None => Ok(None),
+        Some(Value::Number(date)) => date
+            .as_i64()
+            .or_else(|| date.as_f64().map(|date| date.floor() as i64))
+            .map(Some)
+            .ok_or(CsdError::ClaimValidation(format!("{} is not a NumericDate", name))),
+        Some(_) => Err(CsdError::ClaimValidation(format!("{} is not a NumericDate", name))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Map, Value};
+
+    use crate::csd::csd_error::CsdError;
+
+    use super::{CsdValidationPolicy, FixedClock};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn claims(claims: Value) -> Map<String, Value> {
+        claims.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn time_claims() {
+        let mut policy = CsdValidationPolicy::new();
+        policy.set_clock(Box::new(FixedClock(NOW)));
+
+        assert!(policy.validate_claims(&claims(json!({"exp": NOW + 10, "nbf": NOW + 30, "iat": NOW}))).is_ok());
+        assert!(matches!(policy.validate_claims(&claims(json!({"exp": NOW - 61}))), Err(CsdError::ClaimValidation(_))));
+        assert!(matches!(policy.validate_claims(&claims(json!({"nbf": NOW + 61}))), Err(CsdError::ClaimValidation(_))));
+        assert!(matches!(policy.validate_claims(&claims(json!({"iat": NOW + 61}))), Err(CsdError::ClaimValidation(_))));
+        assert!(matches!(policy.validate_claims(&claims(json!({"exp": "tomorrow"}))), Err(CsdError::ClaimValidation(_))));
+
+        policy.set_clock_skew(0);
+        policy.set_max_age(Some(3600));
+        assert!(policy.validate_claims(&claims(json!({"iat": NOW - 3600}))).is_ok());
+        assert!(policy.validate_claims(&claims(json!({"iat": NOW - 3601}))).is_err());
+        assert!(policy.validate_claims(&claims(json!({}))).is_err());
+    }
+
+    #[test]
+    fn issuer_and_audience() {
+        let mut policy = CsdValidationPolicy::new();
+        policy.set_clock(Box::new(FixedClock(NOW)));
+        policy.set_issuer(Some(String::from("did:example:issuer")));
+        assert!(policy.validate_claims(&claims(json!({"iss": "did:example:issuer"}))).is_ok());
+        assert!(policy.validate_claims(&claims(json!({"iss": "did:example:other"}))).is_err());
+
+        assert!(policy.validate_claims(&claims(json!({"iss": "did:example:issuer", "aud": "verifier"}))).is_err());
+        policy.set_audience(Some(String::from("verifier")));
+        assert!(policy.validate_claims(&claims(json!({"iss": "did:example:issuer", "aud": ["other", "verifier"]}))).is_ok());
+        assert!(policy.validate_claims(&claims(json!({"iss": "did:example:issuer", "aud": "other"}))).is_err());
+
+        policy.set_nonce(Some(String::from("n-0S6_WzA2Mj")));
+        let kb = |aud: &str, nonce: &str| claims(json!({"iat": NOW, "aud": aud, "nonce": nonce}));
+        assert!(policy.validate_key_binding_claims(&kb("verifier", "n-0S6_WzA2Mj")).is_ok());
+        assert!(matches!(policy.validate_key_binding_claims(&kb("other", "n-0S6_WzA2Mj")), Err(CsdError::KeyBinding(_))));
+        assert!(matches!(policy.validate_key_binding_claims(&kb("verifier", "replayed")), Err(CsdError::KeyBinding(_))));
+        assert!(matches!(policy.validate_key_binding_claims(&claims(json!({"aud": "verifier"}))), Err(CsdError::KeyBinding(_))));
+    }
+}
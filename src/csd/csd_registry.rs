@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use ark_ec::pairing::Pairing;
+use serde_json::{Map, Value};
+use vb_accumulator::setup::PublicKey;
+
+use crate::accumulator::{deserialize_pk, serialize_pk};
+use crate::csd;
+use csd::csd_encoder::{CREDENTIAL_TYPE_KEY, ISSUER_KEY, PARAM_SEED_KEY, PK_KEY};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+
+/// What a verifier trusts an issuer with: its accumulator key and parameters, and the credential
+/// types it may issue.
+#[derive(Debug, Clone)]
+pub struct CsdTrustedIssuer<C: Pairing> {
+    pub public_key: PublicKey<C>,
+    pub param_seed: u64,
+    /// Allowed `vct` values; empty to allow any type, including untyped tokens.
+    pub credential_types: Vec<String>,
+}
+
+/// Registry of trusted issuers, keyed by issuer ID (the `iss` claim).
+///
+/// Once set on a [`csd::csd_decoder::CsdDecoder`], tokens are only verified against the registered
+/// accumulator key, so a self-minted accumulator and keypair can't pass verification.
+#[derive(Debug, Clone)]
+pub struct CsdIssuerRegistry<C: Pairing> {
+    issuers: HashMap<String, CsdTrustedIssuer<C>>,
+}
+
+impl<C: Pairing> Default for CsdIssuerRegistry<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Pairing> CsdIssuerRegistry<C> {
+    pub fn new() -> Self {
+        Self { issuers: HashMap::new() }
+    }
+
+    /// Trusts `issuer`, replacing any previous entry.
+    pub fn insert(&mut self, issuer: impl Into<String>, trusted: CsdTrustedIssuer<C>) {
+        self.issuers.insert(issuer.into(), trusted);
+    }
+
+    pub fn get(&self, issuer: &str) -> Option<&CsdTrustedIssuer<C>> {
+        self.issuers.get(issuer)
+    }
+
+    /// Loads a registry from a JSON object of the form
+    /// `{"<iss>": {"pk": "<base64 key>", "param_seed": "1", "credential_types": ["..."]}}`,
+    /// with keys serialized as in the tokens.
+    ///
+    /// ## Error
+    /// Returns [`CsdCoreError::DeserializationError`] if an entry is malformed.
+    pub fn from_json(registry: &Value) -> CsdResult<Self> {
+        let entries = registry
+            .as_object()
+            .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("issuer registry is not an object"))))?;
+
+        let mut issuers = Self::new();
+        for (issuer, entry) in entries {
+            let field = |name: &str| {
+                entry
+                    .get(name)
+                    .and_then(Value::as_str)
+                    .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("issuer {} has no {}", issuer, name))))
+            };
+            let public_key = deserialize_pk::<C>(field(PK_KEY)?.to_string())?;
+            let param_seed = u64::from_str(field(PARAM_SEED_KEY)?)
+                .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(format!("issuer {}: {}", issuer, err))))?;
+            let credential_types = match entry.get("credential_types") {
+                Some(Value::Array(types)) => types
+                    .iter()
+                    .map(|credential_type| credential_type.as_str().map(String::from))
+                    .collect::<Option<Vec<String>>>()
+                    .ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("issuer {} has non-string credential types", issuer))))?,
+                None => vec![],
+                Some(_) => return Err(CsdError::Core(CsdCoreError::DeserializationError(format!("credential types of issuer {} are not an array", issuer)))),
+            };
+            issuers.insert(issuer.to_owned(), CsdTrustedIssuer { public_key, param_seed, credential_types });
+        }
+        Ok(issuers)
+    }
+
+    /// Checks the `iss`, `pk`, `param_seed` and `vct` claims of a decoded `object` against the
+    /// registry, and fills in the registered `pk` when the token doesn't carry one.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::UntrustedIssuer`] if the issuer isn't registered, or its key,
+    /// parameters or credential type don't match the registry.
+    pub fn enforce(&self, object: &mut Map<String, Value>) -> CsdResult<()> {
+        let issuer = object
+            .get(ISSUER_KEY)
+            .and_then(Value::as_str)
+            .ok_or(CsdError::UntrustedIssuer(String::from("token has no iss")))?;
+        let trusted = self
+            .get(issuer)
+            .ok_or(CsdError::UntrustedIssuer(format!("{} is not a trusted issuer", issuer)))?;
+
+        if let Some(pk) = object.get(PK_KEY) {
+            let embedded = pk.as_str().and_then(|pk| deserialize_pk::<C>(pk.to_owned()).ok());
+            if embedded.as_ref() != Some(&trusted.public_key) {
+                return Err(CsdError::UntrustedIssuer(format!("accumulator key is not the one of {}", issuer)));
+            }
+        }
+        let public_key = serialize_pk::<C>(trusted.public_key.clone());
+        if object.get(PARAM_SEED_KEY).and_then(Value::as_str) != Some(trusted.param_seed.to_string().as_str()) {
+            return Err(CsdError::UntrustedIssuer(format!("accumulator parameters are not the ones of {}", issuer)));
+        }
+        if !trusted.credential_types.is_empty() {
+            let credential_type = object.get(CREDENTIAL_TYPE_KEY).and_then(Value::as_str);
+            if !credential_type.is_some_and(|credential_type| trusted.credential_types.iter().any(|allowed| allowed == credential_type)) {
+                return Err(CsdError::UntrustedIssuer(format!("{} may not issue credentials of type {:?}", issuer, credential_type)));
+            }
+        }
+
+        object.insert(String::from(PK_KEY), Value::String(public_key));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::json;
+
+    use crate::accumulator::{generate_secret_key, serialize_pk};
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_verifier::CsdVerifier;
+
+    use super::{CsdIssuerRegistry, CsdTrustedIssuer};
+
+    const ISSUER: &str = "did:example:issuer";
+
+    #[test]
+    fn trusted_issuers_only() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let mut trusted = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let secret_key = generate_secret_key::<Bn254>();
+        trusted.set_accumulator_secret_key(secret_key.clone());
+        // Issuers without the trusted secret key mint their own accumulator.
+        let issue = |issuer: Option<&str>, credential_type: &str, trusted_key: bool| {
+            let mut csd_issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+            if trusted_key {
+                csd_issuer.set_accumulator_secret_key(secret_key.clone());
+            }
+            csd_issuer.set_credential_type(credential_type);
+            // The accumulator key is taken from the registry.
+            if let Some(issuer) = issuer {
+                csd_issuer.set_issuer(issuer, None);
+                csd_issuer.set_omit_accumulator_key(true);
+            }
+            csd_issuer.issue(json!({"name": "Alice"})).unwrap().presentation()
+        };
+
+        let registry = CsdIssuerRegistry::<Bn254>::from_json(&json!({
+            ISSUER: {
+                "pk": serialize_pk::<Bn254>(trusted.accumulator_public_key()),
+                "param_seed": trusted.param_seed().to_string(),
+                "credential_types": ["Identity"],
+            }
+        })).unwrap();
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        verifier.set_registry(registry.clone());
+
+        assert!(verifier.verify(&issue(Some(ISSUER), "Identity", true)).is_ok());
+        // Self-minted accumulator: without iss, under an unknown iss, or claiming to be the issuer.
+        assert!(matches!(verifier.verify(&issue(None, "Identity", false)).unwrap_err(), CsdError::UntrustedIssuer(_)));
+        assert!(matches!(verifier.verify(&issue(Some("did:example:other"), "Identity", false)).unwrap_err(), CsdError::UntrustedIssuer(_)));
+        assert!(matches!(verifier.verify(&issue(Some(ISSUER), "Identity", false)).unwrap_err(), CsdError::VerificationFailed(_)));
+        assert!(matches!(verifier.verify(&issue(Some(ISSUER), "Diploma", true)).unwrap_err(), CsdError::UntrustedIssuer(_)));
+
+        let mut registry = registry;
+        registry.insert(ISSUER, CsdTrustedIssuer { public_key: trusted.accumulator_public_key(), param_seed: 2, credential_types: vec![] });
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        verifier.set_registry(registry);
+        assert!(matches!(verifier.verify(&issue(Some(ISSUER), "Diploma", true)).unwrap_err(), CsdError::UntrustedIssuer(_)));
+    }
+}
@@ -0,0 +1,371 @@
+use json_pointer::JsonPointer;
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::csd;
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+
+/// A condition on the value of a claim, after the JSON Schema keywords of DIF Presentation
+/// Exchange filters.
+#[derive(Debug, Clone)]
+pub enum CsdFilter {
+    /// `const`: the value must be equal to the given one.
+    Equals(Value),
+    /// `enum`, or the `values` of a DCQL claim: the value must be one of the given ones.
+    OneOf(Vec<Value>),
+    /// `pattern`: the value must be a string matching the regular expression.
+    Pattern(Regex),
+    /// `minimum` and `maximum`: the value must be a number within the inclusive bounds.
+    Range { minimum: Option<f64>, maximum: Option<f64> },
+}
+
+impl CsdFilter {
+    pub fn matches(&self, value: &Value) -> bool {
+        match self {
+            CsdFilter::Equals(expected) => value == expected,
+            CsdFilter::OneOf(expected) => expected.contains(value),
+            CsdFilter::Pattern(pattern) => value.as_str().is_some_and(|value| pattern.is_match(value)),
+            CsdFilter::Range { minimum, maximum } => value.as_f64().is_some_and(|value| {
+                minimum.is_none_or(|minimum| value >= minimum) && maximum.is_none_or(|maximum| value <= maximum)
+            }),
+        }
+    }
+}
+
+/// A claim a verifier asks for, by JSON pointer into the credential claims.
+#[derive(Debug, Clone)]
+pub struct CsdFieldRequirement {
+    pub pointer: String,
+    /// Optional claims are disclosed when the holder has them and they match the filters.
+    pub optional: bool,
+    pub filters: Vec<CsdFilter>,
+}
+
+impl CsdFieldRequirement {
+    /// The value of the claim in `claims`, if it's there and matches every filter.
+    fn matching<'a>(&self, claims: &'a Value) -> CsdResult<Option<&'a Value>> {
+        let pointer = self.pointer
+            .parse::<JsonPointer<_, _>>()
+            .map_err(|err| CsdError::InvalidPath(format!("{:?}", err)))?;
+        Ok(pointer.get(claims).ok().filter(|value| self.filters.iter().all(|filter| filter.matches(value))))
+    }
+}
+
+/// The claims a verifier needs from a presentation, see [`CsdPresentationRequirements::select`]
+/// and [`CsdPresentationRequirements::check`].
+#[derive(Debug, Clone, Default)]
+pub struct CsdPresentationRequirements {
+    fields: Vec<CsdFieldRequirement>,
+}
+
+impl CsdPresentationRequirements {
+    pub fn new() -> Self {
+        Self { fields: vec![] }
+    }
+
+    /// Requires the claim at `pointer`, matching every filter of `filters`.
+    pub fn require(&mut self, pointer: impl Into<String>, filters: Vec<CsdFilter>) {
+        self.fields.push(CsdFieldRequirement { pointer: pointer.into(), optional: false, filters });
+    }
+
+    /// Asks for the claim at `pointer` if the holder has it, matching every filter of `filters`.
+    pub fn request(&mut self, pointer: impl Into<String>, filters: Vec<CsdFilter>) {
+        self.fields.push(CsdFieldRequirement { pointer: pointer.into(), optional: true, filters });
+    }
+
+    pub fn fields(&self) -> &[CsdFieldRequirement] {
+        &self.fields
+    }
+
+    /// Reads either a DIF Presentation Exchange definition (an object with `input_descriptors`)
+    /// or a DCQL query (an object with `credentials`), see
+    /// [`CsdPresentationRequirements::from_presentation_definition`] and
+    /// [`CsdPresentationRequirements::from_dcql`].
+    pub fn from_json(json: &Value) -> CsdResult<Self> {
+        if json.get("input_descriptors").is_some() {
+            Self::from_presentation_definition(json)
+        } else if json.get("credentials").is_some() {
+            Self::from_dcql(json)
+        } else {
+            Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("neither a presentation definition nor a DCQL query"))))
+        }
+    }
+
+    /// Reads the fields of a DIF Presentation Exchange definition. Every input descriptor is
+    /// applied to the one credential; of each field, the first `path` is used, and the `const`,
+    /// `enum`, `pattern`, `minimum` and `maximum` keywords of its `filter`.
+    ///
+    /// ## Error
+    /// Returns [`CsdCoreError::DeserializationError`] if the definition is malformed, or
+    /// [`CsdError::InvalidPath`] if a path isn't a plain JSONPath such as `$.address.city`.
+    pub fn from_presentation_definition(definition: &Value) -> CsdResult<Self> {
+        let descriptors = definition
+            .get("input_descriptors")
+            .and_then(Value::as_array)
+            .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("input_descriptors is not an array"))))?;
+
+        let mut requirements = Self::new();
+        for descriptor in descriptors {
+            let fields = descriptor.pointer("/constraints/fields").and_then(Value::as_array).cloned().unwrap_or_default();
+            for field in fields {
+                let path = field
+                    .get("path")
+                    .and_then(Value::as_array)
+                    .and_then(|paths| paths.first())
+                    .and_then(Value::as_str)
+                    .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("field has no path"))))?;
+                let filters = match field.get("filter") {
+                    Some(Value::Object(filter)) => schema_filters(filter)?,
+                    Some(_) => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("filter is not an object")))),
+                    None => vec![],
+                };
+                requirements.fields.push(CsdFieldRequirement {
+                    pointer: json_path_pointer(path)?,
+                    optional: field.get("optional").and_then(Value::as_bool).unwrap_or(false),
+                    filters,
+                });
+            }
+        }
+        Ok(requirements)
+    }
+
+    /// Reads the claims of a DCQL query with a single credential query. Each claim `path` of
+    /// names becomes a required pointer, and its `values` a [`CsdFilter::OneOf`].
+    ///
+    /// ## Error
+    /// Returns [`CsdCoreError::DeserializationError`] if the query is malformed or has other than one
+    /// credential query.
+    pub fn from_dcql(query: &Value) -> CsdResult<Self> {
+        let credential = match query.get("credentials").and_then(Value::as_array).map(Vec::as_slice) {
+            Some([credential]) => credential,
+            _ => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("DCQL query must have exactly one credential query")))),
+        };
+
+        let mut requirements = Self::new();
+        for claim in credential.get("claims").and_then(Value::as_array).cloned().unwrap_or_default() {
+            let path = claim
+                .get("path")
+                .and_then(Value::as_array)
+                .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("claim has no path"))))?;
+            let mut pointer = String::new();
+            for name in path {
+                let name = name
+                    .as_str()
+                    .ok_or(CsdError::Core(CsdCoreError::DeserializationError(String::from("only claim names are supported in paths"))))?;
+                pointer.push_str(&format!("/{}", escape(name)));
+            }
+            let filters = match claim.get("values") {
+                Some(Value::Array(values)) => vec![CsdFilter::OneOf(values.clone())],
+                Some(_) => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("values is not an array")))),
+                None => vec![],
+            };
+            requirements.require(pointer, filters);
+        }
+        Ok(requirements)
+    }
+
+    /// Holder side: the pointers of the requested `claims` to disclose, i.e. every required one
+    /// and the optional ones the holder has, if they match their filters. Only whole top-level
+    /// claims can be disclosed, so a request for `/address/city` selects `/address`, once.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::UnmetRequirement`] if a required claim is missing or doesn't match.
+    pub fn select(&self, claims: &Map<String, Value>) -> CsdResult<Vec<String>> {
+        let claims = Value::Object(claims.clone());
+        let mut pointers = vec![];
+        for field in &self.fields {
+            match field.matching(&claims)? {
+                Some(_) => {
+                    let pointer = top_level(&field.pointer);
+                    if !pointers.contains(&pointer) {
+                        pointers.push(pointer);
+                    }
+                }
+                None if field.optional => {}
+                None => return Err(CsdError::UnmetRequirement(format!("no claim {} matching the filters", field.pointer))),
+            }
+        }
+        Ok(pointers)
+    }
+
+    /// Verifier side: checks that the `disclosed` claims hold every required claim, and that
+    /// each disclosed claim asked for matches its filters.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::UnmetRequirement`] naming the first claim that fails.
+    pub fn check(&self, disclosed: &Map<String, Value>) -> CsdResult<()> {
+        let disclosed = Value::Object(disclosed.clone());
+        for field in &self.fields {
+            let pointer = field.pointer
+                .parse::<JsonPointer<_, _>>()
+                .map_err(|err| CsdError::InvalidPath(format!("{:?}", err)))?;
+            let present = pointer.get(&disclosed).is_ok();
+            if (present || !field.optional) && field.matching(&disclosed)?.is_none() {
+                return Err(CsdError::UnmetRequirement(format!("no claim {} matching the filters", field.pointer)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The pointer of the top-level claim holding the one at `pointer`, e.g. `/address` for
+/// `/address/city`. Slashes in names are escaped as `~1`, so the first one ends the name.
+fn top_level(pointer: &str) -> String {
+    match pointer.strip_prefix('/').and_then(|name| name.find('/')) {
+        Some(end) => String::from(&pointer[..end + 1]),
+        None => String::from(pointer),
+    }
+}
+
+/// The filters of the JSON Schema `filter` of a Presentation Exchange field.
+fn schema_filters(filter: &Map<String, Value>) -> CsdResult<Vec<CsdFilter>> {
+    let bound = |name: &str| match filter.get(name) {
+        None => Ok(None),
+        Some(bound) => bound.as_f64().map(Some).ok_or(CsdError::Core(CsdCoreError::DeserializationError(format!("{} is not a number", name)))),
+    };
+
+    let mut filters = vec![];
+    if let Some(value) = filter.get("const") {
+        filters.push(CsdFilter::Equals(value.clone()));
+    }
+    match filter.get("enum") {
+        Some(Value::Array(values)) => filters.push(CsdFilter::OneOf(values.clone())),
+        Some(_) => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("enum is not an array")))),
+        None => {}
+    }
+    match filter.get("pattern") {
+        Some(Value::String(pattern)) => filters.push(CsdFilter::Pattern(
+            Regex::new(pattern).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(err.to_string())))?,
+        )),
+        Some(_) => return Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("pattern is not a string")))),
+        None => {}
+    }
+    let (minimum, maximum) = (bound("minimum")?, bound("maximum")?);
+    if minimum.is_some() || maximum.is_some() {
+        filters.push(CsdFilter::Range { minimum, maximum });
+    }
+    Ok(filters)
+}
+
+/// Converts a JSONPath of names, e.g. `$.address.city` or `$['address']['city']`, to a JSON pointer.
+fn json_path_pointer(path: &str) -> CsdResult<String> {
+    let invalid = || CsdError::InvalidPath(format!("unsupported JSONPath {}", path));
+    let mut rest = path.strip_prefix('$').ok_or_else(invalid)?;
+    let mut pointer = String::new();
+    while !rest.is_empty() {
+        let name;
+        if let Some(dotted) = rest.strip_prefix('.') {
+            let end = dotted.find(['.', '[']).unwrap_or(dotted.len());
+            (name, rest) = dotted.split_at(end);
+        } else if let Some(bracketed) = rest.strip_prefix("['").or_else(|| rest.strip_prefix("[\"")) {
+            let end = bracketed.find(['\'', '"']).ok_or_else(invalid)?;
+            name = &bracketed[..end];
+            rest = bracketed[end + 1..].strip_prefix(']').ok_or_else(invalid)?;
+        } else {
+            return Err(invalid());
+        }
+        if name.is_empty() || name == "*" {
+            return Err(invalid());
+        }
+        pointer.push_str(&format!("/{}", escape(name)));
+    }
+    if pointer.is_empty() {
+        return Err(invalid());
+    }
+    Ok(pointer)
+}
+
+fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::{json, Value};
+
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_verifier::CsdVerifier;
+
+    use super::{json_path_pointer, CsdPresentationRequirements};
+
+    #[test]
+    fn json_paths() {
+        assert_eq!(json_path_pointer("$.name").unwrap(), "/name");
+        assert_eq!(json_path_pointer("$['address'].city").unwrap(), "/address/city");
+        assert_eq!(json_path_pointer("$[\"a/b\"]").unwrap(), "/a~1b");
+        assert!(json_path_pointer("$.nationalities[*]").is_err());
+        assert!(json_path_pointer("name").is_err());
+    }
+
+    #[test]
+    fn requirements() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let credential = issuer.issue(json!({
+            "name": "Alice",
+            "age": 30,
+            "email": "alice@example.com",
+            "country": "NL",
+        })).unwrap().presentation();
+        let holder = CsdHolder::new(&credential).unwrap();
+
+        let definition = json!({
+            "id": "age-check",
+            "input_descriptors": [{
+                "id": "identity",
+                "constraints": {
+                    "limit_disclosure": "required",
+                    "fields": [
+                        {"path": ["$.age"], "filter": {"type": "number", "minimum": 18}},
+                        {"path": ["$.country"], "filter": {"type": "string", "enum": ["NL", "BE"]}},
+                        {"path": ["$.email"], "optional": true, "filter": {"type": "string", "pattern": "@example\\.org$"}},
+                        {"path": ["$.phone"], "optional": true},
+                    ]
+                }
+            }]
+        });
+        let requirements = CsdPresentationRequirements::from_json(&definition).unwrap();
+        let presentation = holder.present_for(&requirements).unwrap().presentation();
+
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        verifier.set_requirements(requirements.clone());
+        assert_eq!(Value::Object(verifier.verify(&presentation).unwrap()), json!({"age": 30, "country": "NL"}));
+        let presentation = holder.present(&["/age", "/name"]).unwrap().presentation();
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::UnmetRequirement(_)));
+        // Optional claims must match their filters once disclosed.
+        let presentation = holder.present(&["/age", "/country", "/email"]).unwrap().presentation();
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::UnmetRequirement(_)));
+
+        let query = json!({"credentials": [{"id": "pid", "claims": [{"path": ["name"]}, {"path": ["age"], "values": [21]}]}]});
+        let requirements = CsdPresentationRequirements::from_json(&query).unwrap();
+        assert!(matches!(holder.present_for(&requirements).unwrap_err(), CsdError::UnmetRequirement(_)));
+    }
+
+    #[test]
+    fn nested_requirements() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let address = json!({"street": "Main St", "city": "Berlin", "zip": "10115"});
+        let credential = issuer.issue(json!({"name": "Alice", "age": 30, "address": address})).unwrap().presentation();
+        let holder = CsdHolder::new(&credential).unwrap();
+
+        let query = json!({"credentials": [{"id": "pid", "claims": [
+            {"path": ["address", "city"], "values": ["Berlin", "Hamburg"]},
+            {"path": ["address", "zip"]},
+            {"path": ["name"]},
+        ]}]});
+        let requirements = CsdPresentationRequirements::from_json(&query).unwrap();
+        assert_eq!(requirements.select(&holder.claims().unwrap()).unwrap(), vec!["/address", "/name"]);
+        let presentation = holder.present_for(&requirements).unwrap().presentation();
+
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        verifier.set_requirements(requirements);
+        assert_eq!(Value::Object(verifier.verify(&presentation).unwrap()), json!({"address": address, "name": "Alice"}));
+    }
+}
@@ -0,0 +1,284 @@
+use base64::Engine;
+use sd_jwt_payload::SdObjectEncoder;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::csd;
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_encoder::{ACCUMULATOR_KEY, PARAM_SEED_KEY, PK_KEY, SD_ALG, SD_DIGESTS_KEY};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+use csd::csd_issuance::PLAIN_CLAIMS_KEY;
+use csd::csd_jwt::CsdJwt;
+
+/// Header `typ` of the SD-JWTs built for comparison, as in the benchmark.
+const SD_JWT_TYP: &str = "sd-jwt";
+
+/// Bytes of a serialized CSD-JWT spent on each of its parts.
+///
+/// The parts are base64url-encoded inside the token, so the size of a JSON entry of the payload or
+/// of a disclosure is its JSON size times 4/3; `overhead` holds whatever is left, i.e. the `.`
+/// and `~` separators and the JSON punctuation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CsdSizeReport {
+    pub header: usize,
+    pub signature: usize,
+    pub accumulator: usize,
+    pub public_key: usize,
+    /// The parameter seed, `_sd_alg` and `_plain` entries.
+    pub params: usize,
+    pub witnesses: usize,
+    /// The `_sd` digests of the salted claims.
+    pub digests: usize,
+    /// The salts of the disclosed salted claims, told from witnesses by their digest as in
+    /// [`CsdDecoder::verify_disclosures`](csd::csd_decoder::CsdDecoder::verify_disclosures).
+    pub salts: usize,
+    /// Claim names and values, disclosed or always present in the payload.
+    pub claim_values: usize,
+    pub overhead: usize,
+    pub total: usize,
+}
+
+/// Bytes of a serialized SD-JWT spent on each of its parts, see [`CsdSizeReport`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SdJwtSizeReport {
+    pub header: usize,
+    pub signature: usize,
+    /// The `_sd` digests and the `_sd_alg` entry.
+    pub digests: usize,
+    pub salts: usize,
+    pub claim_values: usize,
+    pub overhead: usize,
+    pub total: usize,
+}
+
+/// A CSD-JWT presentation and the SD-JWT presentation of the same credential and claims.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CsdSizeComparison {
+    pub csd_jwt: CsdSizeReport,
+    pub sd_jwt: SdJwtSizeReport,
+}
+
+impl CsdSizeComparison {
+    /// Ratio of the CSD-JWT to the SD-JWT presentation size, below 1 when CSD-JWT is smaller.
+    pub fn ratio(&self) -> f64 {
+        self.csd_jwt.total as f64 / self.sd_jwt.total as f64
+    }
+}
+
+///
+/// analyze(presentation: &str) -> CsdResult<CsdSizeReport>
+///
+/// Breaks a serialized CSD-JWT credential or presentation down into a [`CsdSizeReport`].
+///
+pub fn analyze(presentation: &str) -> CsdResult<CsdSizeReport> {
+    let csd_jwt = CsdJwt::parse(presentation)?;
+    let (header, payload, signature) = jwt_segments(&csd_jwt.jwt)?;
+
+    let mut report = CsdSizeReport {
+        header: header.len(),
+        signature: signature.len(),
+        total: presentation.len(),
+        ..Default::default()
+    };
+    let payload = decode_segment(payload)?;
+    let digests: Vec<&str> = payload
+        .get(SD_DIGESTS_KEY)
+        .and_then(Value::as_array)
+        .map(|digests| digests.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    for (key, value) in &payload {
+        let size = encoded_len(entry_len(key, value));
+        match key.as_str() {
+            ACCUMULATOR_KEY => report.accumulator += size,
+            PK_KEY => report.public_key += size,
+            PARAM_SEED_KEY | SD_ALG | PLAIN_CLAIMS_KEY => report.params += size,
+            SD_DIGESTS_KEY => report.digests += size,
+            _ => report.claim_values += size,
+        }
+    }
+    for disclosure in &csd_jwt.disclosures {
+        let disclosure = CsdDisclosure::parse(disclosure.to_owned())?;
+        // A salted disclosure parses as a witness one, with its salt as the witness.
+        let size = encoded_len(disclosure.witness.as_ref().map_or(0, |witness| json_len(witness) + 1));
+        if digests.contains(&disclosure.digest().as_str()) {
+            report.salts += size;
+        } else {
+            report.witnesses += size;
+        }
+        report.claim_values += encoded_len(claim_len(disclosure.claim_name.as_deref(), &disclosure.claim_value));
+    }
+
+    report.overhead = report.total.saturating_sub(
+        report.header + report.signature + report.accumulator + report.public_key + report.params
+            + report.witnesses + report.digests + report.salts + report.claim_values,
+    );
+    Ok(report)
+}
+
+///
+/// compare(credential: &str, presentation: &str) -> CsdResult<CsdSizeComparison>
+///
+/// Analyzes `presentation`, derived from `credential`, and the SD-JWT presentation disclosing the
+/// same claims of an SD-JWT which conceals every claim of `credential`. The SD-JWT is signed with
+/// the same algorithm, so it is given the same header `alg` and signature size.
+///
+pub fn compare(credential: &str, presentation: &str) -> CsdResult<CsdSizeComparison> {
+    let csd_jwt = analyze(presentation)?;
+
+    let credential = CsdJwt::parse(credential)?;
+    let disclosed = CsdJwt::parse(presentation)?
+        .disclosures
+        .into_iter()
+        .map(CsdDisclosure::parse)
+        .collect::<Result<Vec<CsdDisclosure>, _>>()?;
+    let (header, _, signature) = jwt_segments(&credential.jwt)?;
+    let header = decode_segment(header)?;
+
+    let mut claims: Map<String, Value> = Map::new();
+    for disclosure in credential.disclosures {
+        let disclosure = CsdDisclosure::parse(disclosure)?;
+        if let Some(name) = disclosure.claim_name {
+            claims.insert(name, disclosure.claim_value);
+        }
+    }
+    let mut encoder: SdObjectEncoder = Value::Object(claims.clone())
+        .try_into()
+        .map_err(|err: sd_jwt_payload::Error| CsdError::Unspecified(err.to_string()))?;
+    let mut sd_disclosures = vec![];
+    for name in claims.keys() {
+        sd_disclosures.push(
+            encoder
+                .conceal(&format!("/{}", name.replace('~', "~0").replace('/', "~1")), None)
+                .map_err(|err| CsdError::Unspecified(err.to_string()))?,
+        );
+    }
+    encoder.add_sd_alg_property();
+
+    let mut sd_header: Map<String, Value> = Map::new();
+    sd_header.insert(String::from("typ"), Value::String(String::from(SD_JWT_TYP)));
+    if let Some(alg) = header.get("alg") {
+        sd_header.insert(String::from("alg"), alg.clone());
+    }
+    let sd_payload = encoder.object().map_err(|err| CsdError::Unspecified(err.to_string()))?;
+
+    let mut sd_jwt = SdJwtSizeReport {
+        header: encoded_len(Value::Object(sd_header).to_string().len()),
+        signature: signature.len(),
+        ..Default::default()
+    };
+    let payload_len = encoded_len(Value::Object(sd_payload.clone()).to_string().len());
+    for (key, value) in sd_payload {
+        let size = encoded_len(entry_len(key, value));
+        if key == SD_DIGESTS_KEY || key == SD_ALG {
+            sd_jwt.digests += size;
+        } else {
+            sd_jwt.claim_values += size;
+        }
+    }
+    // <header>.<payload>.<signature>~<disclosures>~ without a key binding JWT
+    sd_jwt.total = sd_jwt.header + payload_len + sd_jwt.signature + 3;
+    for disclosure in sd_disclosures {
+        if disclosed.iter().any(|csd| csd.claim_name == disclosure.claim_name) {
+            sd_jwt.salts += encoded_len(json_len(&disclosure.salt) + 1);
+            sd_jwt.claim_values += encoded_len(claim_len(disclosure.claim_name.as_deref(), &disclosure.claim_value));
+            sd_jwt.total += disclosure.as_str().len() + 1;
+        }
+    }
+    sd_jwt.overhead = sd_jwt.total.saturating_sub(
+        sd_jwt.header + sd_jwt.signature + sd_jwt.digests + sd_jwt.salts + sd_jwt.claim_values,
+    );
+
+    Ok(CsdSizeComparison { csd_jwt, sd_jwt })
+}
+
+/// Splits a compact JWS into its header, payload and signature segments.
+fn jwt_segments(jwt: &str) -> CsdResult<(&str, &str, &str)> {
+    let mut segments = jwt.split('.');
+    match (segments.next(), segments.next(), segments.next(), segments.next()) {
+        (Some(header), Some(payload), Some(signature), None) => Ok((header, payload, signature)),
+        _ => Err(CsdError::Core(CsdCoreError::DeserializationError(String::from("JWT does not have 3 segments")))),
+    }
+}
+
+fn decode_segment(segment: &str) -> CsdResult<Map<String, Value>> {
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(segment)
+        .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(err.to_string())))?;
+    serde_json::from_slice(&bytes).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(err.to_string())))
+}
+
+/// Length of `n` bytes once base64url-encoded without padding.
+fn encoded_len(n: usize) -> usize {
+    (4 * n).div_ceil(3)
+}
+
+fn json_len(string: &str) -> usize {
+    Value::String(string.to_owned()).to_string().len()
+}
+
+/// Length of a `"key":value,` entry of a JSON object.
+fn entry_len(key: &str, value: &Value) -> usize {
+    json_len(key) + 1 + value.to_string().len() + 1
+}
+
+/// Length of the `"name",value` part of a disclosure array.
+fn claim_len(name: Option<&str>, value: &Value) -> usize {
+    name.map_or(0, |name| json_len(name) + 1) + value.to_string().len()
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::json;
+
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+
+    use super::{analyze, compare};
+
+    #[test]
+    fn size_breakdown() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let credential = issuer.issue(json!({"name": "Alice", "age": 30, "email": "alice@example.com"})).unwrap().presentation();
+        let presentation = CsdHolder::new(&credential).unwrap().present(&["/name"]).unwrap().presentation();
+
+        let report = analyze(&presentation).unwrap();
+        assert_eq!(report.total, presentation.len());
+        // ES256 signatures are 64 bytes.
+        assert_eq!(report.signature, 86);
+        assert!(report.accumulator > 0 && report.public_key > 0 && report.params > 0);
+        assert!(analyze(&credential).unwrap().witnesses > report.witnesses);
+        assert_eq!((report.digests, report.salts), (0, 0));
+        assert_eq!(
+            report.header + report.signature + report.accumulator + report.public_key + report.params
+                + report.witnesses + report.digests + report.salts + report.claim_values + report.overhead,
+            report.total
+        );
+
+        let comparison = compare(&credential, &presentation).unwrap();
+        assert_eq!(comparison.csd_jwt, report);
+        assert_eq!(comparison.sd_jwt.signature, report.signature);
+        assert!(comparison.sd_jwt.digests > 0 && comparison.sd_jwt.salts > 0);
+        assert!(comparison.ratio() > 0.0);
+
+        // Hybrid: the salted age is disclosed with a salt, which isn't a witness.
+        let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        issuer.set_salted_claims(vec![String::from("age")]);
+        let credential = issuer.issue(json!({"name": "Alice", "age": 30, "email": "alice@example.com"})).unwrap().presentation();
+        let holder = CsdHolder::new(&credential).unwrap();
+        let name = analyze(&holder.present(&["/name"]).unwrap().presentation()).unwrap();
+        let presentation = holder.present(&["/name", "/age"]).unwrap().presentation();
+        let hybrid = analyze(&presentation).unwrap();
+        assert_eq!(hybrid.witnesses, name.witnesses);
+        assert_eq!(hybrid.digests, name.digests);
+        assert!(hybrid.digests > 0 && hybrid.salts > 0 && name.salts == 0);
+        assert_eq!(
+            hybrid.header + hybrid.signature + hybrid.accumulator + hybrid.public_key + hybrid.params
+                + hybrid.witnesses + hybrid.digests + hybrid.salts + hybrid.claim_values + hybrid.overhead,
+            presentation.len()
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::csd;
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_encoder::{CREDENTIAL_TYPE_KEY, ISSUER_KEY};
+use csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+use csd::csd_issuance::{CsdIssuancePolicy, CsdSelectivity};
+use csd::csd_policy::{CONFIRMATION_KEY, EXPIRATION_KEY, ISSUED_AT_KEY, NOT_BEFORE_KEY};
+
+/// Header `typ` of credentials issued with the [`CsdProfile::SdJwtVc`] profile.
+pub const VC_HEADER_TYP: &str = "vc+csd-jwt";
+/// The status mechanism of the credential, e.g. a `status_list` reference.
+pub const STATUS_KEY: &str = "status";
+/// The subject, selectively disclosable unless the issuance policy says otherwise.
+pub const SUBJECT_KEY: &str = "sub";
+/// Claims which the SD-JWT VC draft forbids to disclose selectively.
+pub const VC_PLAIN_CLAIMS: [&str; 6] = [ISSUER_KEY, NOT_BEFORE_KEY, EXPIRATION_KEY, CONFIRMATION_KEY, CREDENTIAL_TYPE_KEY, STATUS_KEY];
+
+/// The shape of the issued tokens and what verifiers expect of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsdProfile {
+    /// Plain CSD-JWT: typed `csd-jwt`, with only the accumulator metadata required.
+    #[default]
+    CsdJwt,
+    /// SD-JWT VC with accumulator disclosures: typed `vc+csd-jwt`, with required `vct`, `iss` and
+    /// `iat` claims, and `cnf`, `status` and `sub` claims as in the IETF draft.
+    SdJwtVc,
+}
+
+impl CsdProfile {
+    /// The `typ` header of the tokens of the profile.
+    pub fn header_typ(&self) -> &'static str {
+        match self {
+            CsdProfile::CsdJwt => csd::csd_encoder::HEADER_TYP,
+            CsdProfile::SdJwtVc => VC_HEADER_TYP,
+        }
+    }
+}
+
+/// Metadata of a top-level claim in SD-JWT VC type metadata.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsdClaimMetadata {
+    /// Path of the claim; only paths of a single name apply to the accumulated top-level claims.
+    pub path: Vec<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sd: Option<CsdSelectivity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<Value>,
+}
+
+/// SD-JWT VC type metadata of a credential type, identified by its `vct`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsdTypeMetadata {
+    pub vct: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The `vct` of the parent type, whose claim metadata this type inherits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub claims: Vec<CsdClaimMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<Value>,
+}
+
+/// Type metadata of the credential types a party knows, looked up by `vct`.
+#[derive(Debug, Clone, Default)]
+pub struct CsdTypeMetadataStore {
+    types: HashMap<String, CsdTypeMetadata>,
+}
+
+impl CsdTypeMetadataStore {
+    pub fn new() -> Self {
+        Self { types: HashMap::new() }
+    }
+
+    /// Adds `metadata`, replacing any previous metadata of its `vct`.
+    pub fn insert(&mut self, metadata: CsdTypeMetadata) {
+        self.types.insert(metadata.vct.clone(), metadata);
+    }
+
+    pub fn get(&self, vct: &str) -> Option<&CsdTypeMetadata> {
+        self.types.get(vct)
+    }
+
+    /// Loads a store from a JSON array of type metadata documents.
+    ///
+    /// ## Error
+    /// Returns [`CsdCoreError::DeserializationError`] if a document is malformed.
+    pub fn from_json(documents: &Value) -> CsdResult<Self> {
+        let documents: Vec<CsdTypeMetadata> =
+            serde_json::from_value(documents.clone()).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(err.to_string())))?;
+        let mut store = Self::new();
+        for metadata in documents {
+            store.insert(metadata);
+        }
+        Ok(store)
+    }
+
+    /// The issuance policy of the type `vct`: the `sd` of its top-level claims, those of the
+    /// types it `extends` included, with the child types taking precedence.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::ClaimValidation`] if `vct`, or a type it extends, is unknown or the
+    /// `extends` chain loops.
+    pub fn issuance_policy(&self, vct: &str) -> CsdResult<CsdIssuancePolicy> {
+        let mut chain: Vec<&CsdTypeMetadata> = vec![];
+        let mut next = Some(vct);
+        while let Some(vct) = next {
+            if chain.iter().any(|metadata| metadata.vct == vct) {
+                return Err(CsdError::ClaimValidation(format!("type {} extends itself", vct)));
+            }
+            let metadata = self.get(vct).ok_or(CsdError::ClaimValidation(format!("unknown credential type {}", vct)))?;
+            chain.push(metadata);
+            next = metadata.extends.as_deref();
+        }
+
+        let mut policy = CsdIssuancePolicy::new();
+        for metadata in chain.into_iter().rev() {
+            for claim in &metadata.claims {
+                if let ([Value::String(name)], Some(sd)) = (claim.path.as_slice(), claim.sd) {
+                    policy.set(name.to_owned(), sd);
+                }
+            }
+        }
+        Ok(policy)
+    }
+}
+
+///
+/// vc_issuance_policy(policy: &CsdIssuancePolicy) -> CsdResult<CsdIssuancePolicy>
+///
+/// `policy` with the [`VC_PLAIN_CLAIMS`] made never selective.
+///
+/// ## Error
+/// Returns [`CsdError::ClaimValidation`] if `policy` makes one of them always selective.
+///
+pub fn vc_issuance_policy(policy: &CsdIssuancePolicy) -> CsdResult<CsdIssuancePolicy> {
+    let mut policy = policy.clone();
+    for name in VC_PLAIN_CLAIMS {
+        if policy.selectivity(name) == CsdSelectivity::AlwaysSelective {
+            return Err(CsdError::ClaimValidation(format!("{} can't be selectively disclosable in an SD-JWT VC", name)));
+        }
+        policy.set(name, CsdSelectivity::NeverSelective);
+    }
+    Ok(policy)
+}
+
+///
+/// validate_vc(typ: Option<&str>, payload: &Map<String, Value>, type_metadata: Option<&CsdTypeMetadataStore>) -> CsdResult<()>
+///
+/// Checks a verified issuer JWT against the SD-JWT VC profile: its `typ` header is [`VC_HEADER_TYP`],
+/// it has a string `vct` and `iss` and a numeric `iat`, and its `status` and `cnf`, if any, are
+/// objects. With `type_metadata`, the `vct` must be known and none of its always selective claims
+/// may be in plain.
+///
+/// ## Error
+/// Returns [`CsdError::Jws`] for a wrong `typ` and [`CsdError::ClaimValidation`] otherwise.
+///
+pub fn validate_vc(typ: Option<&str>, payload: &Map<String, Value>, type_metadata: Option<&CsdTypeMetadataStore>) -> CsdResult<()> {
+    if typ != Some(VC_HEADER_TYP) {
+        return Err(CsdError::Jws(format!("unexpected typ header {:?}, expected {}", typ, VC_HEADER_TYP)));
+    }
+    let vct = payload
+        .get(CREDENTIAL_TYPE_KEY)
+        .and_then(Value::as_str)
+        .ok_or(CsdError::ClaimValidation(String::from("SD-JWT VC has no vct")))?;
+    if !payload.get(ISSUER_KEY).is_some_and(Value::is_string) {
+        return Err(CsdError::ClaimValidation(String::from("SD-JWT VC has no iss")));
+    }
+    if !payload.get(ISSUED_AT_KEY).is_some_and(Value::is_number) {
+        return Err(CsdError::ClaimValidation(String::from("SD-JWT VC has no iat")));
+    }
+    for name in [STATUS_KEY, CONFIRMATION_KEY] {
+        if payload.get(name).is_some_and(|value| !value.is_object()) {
+            return Err(CsdError::ClaimValidation(format!("{} is not an object", name)));
+        }
+    }
+
+    if let Some(type_metadata) = type_metadata {
+        type_metadata.issuance_policy(vct)?.reject_plain(payload)?;
+    }
+    Ok(())
+}
+
+///
+/// validate_vc_disclosures(payload: &Map<String, Value>, disclosures: &[CsdDisclosure], type_metadata: Option<&CsdTypeMetadataStore>) -> CsdResult<()>
+///
+/// Rejects disclosures of the [`VC_PLAIN_CLAIMS`], which an SD-JWT VC must carry in plain, and of
+/// the claims its type metadata makes never selective.
+///
+/// ## Error
+/// Returns [`CsdError::ClaimValidation`] naming the first claim that fails.
+///
+pub fn validate_vc_disclosures(payload: &Map<String, Value>, disclosures: &[CsdDisclosure], type_metadata: Option<&CsdTypeMetadataStore>) -> CsdResult<()> {
+    let policy = match (type_metadata, payload.get(CREDENTIAL_TYPE_KEY).and_then(Value::as_str)) {
+        (Some(type_metadata), Some(vct)) => type_metadata.issuance_policy(vct)?,
+        _ => CsdIssuancePolicy::new(),
+    };
+    vc_issuance_policy(&policy)?.reject_disclosed(disclosures)
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::{json, Value};
+
+    use crate::accumulator::generate_secret_key;
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuance::CsdSelectivity;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_verifier::CsdVerifier;
+
+    use super::{CsdProfile, CsdTypeMetadataStore};
+
+    const ISSUER: &str = "https://issuer.example";
+    const VCT: &str = "https://credentials.example/identity";
+
+    fn type_metadata() -> CsdTypeMetadataStore {
+        CsdTypeMetadataStore::from_json(&json!([
+            {"vct": "https://credentials.example/base", "claims": [{"path": ["country"], "sd": "never"}, {"path": ["name"], "sd": "never"}]},
+            {
+                "vct": VCT,
+                "name": "Identity",
+                "extends": "https://credentials.example/base",
+                "claims": [{"path": ["name"], "sd": "always"}, {"path": ["address", "city"], "sd": "never"}]
+            }
+        ])).unwrap()
+    }
+
+    #[test]
+    fn sd_jwt_vc() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let store = type_metadata();
+        let policy = store.issuance_policy(VCT).unwrap();
+        assert_eq!(policy.selectivity("country"), CsdSelectivity::NeverSelective);
+        assert_eq!(policy.selectivity("name"), CsdSelectivity::AlwaysSelective);
+
+        let secret_key = generate_secret_key::<Bn254>();
+        let issuer = |vct: Option<&str>| {
+            let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+            issuer.set_accumulator_secret_key(secret_key.clone());
+            issuer.set_profile(CsdProfile::SdJwtVc);
+            issuer.set_issuer(ISSUER, None);
+            if let Some(vct) = vct {
+                issuer.set_credential_type(vct);
+            }
+            issuer.set_issuance_policy(policy.clone());
+            issuer
+        };
+        let claims = json!({"name": "Alice", "country": "NL", "sub": "user-42", "status": {"status_list": {"idx": 3, "uri": "https://issuer.example/status"}}});
+        assert!(matches!(issuer(None).issue(claims.clone()).unwrap_err(), CsdError::ClaimValidation(_)));
+        let credential = issuer(Some(VCT)).issue(claims.clone()).unwrap().presentation();
+
+        // `iat` was added, `status` is plain, `sub` is selectively disclosable.
+        let holder = CsdHolder::new(&credential).unwrap();
+        assert_eq!(holder.always_disclosed(), ["country", "status"]);
+        let presentation = holder.present(&["/name"]).unwrap().presentation();
+
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        verifier.set_profile(CsdProfile::SdJwtVc);
+        verifier.set_type_metadata(store.clone());
+        let verified = verifier.verify(&presentation).unwrap();
+        assert_eq!(verified.get("name"), Some(&Value::from("Alice")));
+        assert!(verified.contains_key("status"));
+        // A `country` disclosure, which its type metadata forbids.
+        let mut country = issuer(Some(VCT));
+        country.set_issuance_policy(Default::default());
+        let presentation = CsdHolder::new(&country.issue(claims.clone()).unwrap().presentation()).unwrap().present(&["/country"]).unwrap().presentation();
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::ClaimValidation(_)));
+
+        // Plain CSD-JWTs and tokens of unknown types don't pass.
+        let mut plain = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        plain.set_issuer(ISSUER, None);
+        plain.set_credential_type(VCT);
+        let presentation = plain.issue(json!({"name": "Alice", "iat": 0})).unwrap().presentation();
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::Jws(_)));
+        let mut unknown = issuer(Some("https://credentials.example/other"));
+        unknown.set_issuance_policy(Default::default());
+        let presentation = unknown.issue(claims).unwrap().presentation();
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::ClaimValidation(_)));
+    }
+}
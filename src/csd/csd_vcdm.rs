@@ -0,0 +1,270 @@
+use ark_ec::pairing::Pairing;
+use serde_json::{Map, Value};
+
+use crate::csd;
+use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_holder::CsdHolder;
+use csd::csd_issuance::{CsdIssuancePolicy, CsdSelectivity};
+use csd::csd_issuer::CsdIssuer;
+use csd::csd_jwt::CsdJwt;
+use csd::csd_verifier::CsdVerifier;
+
+/// The base context every VCDM 2.0 credential starts with.
+pub const VCDM_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+pub const VCDM_TYPE: &str = "VerifiableCredential";
+pub const CREDENTIAL_SUBJECT_KEY: &str = "credentialSubject";
+
+///
+/// vcdm_claims(credential: &Value) -> CsdResult<Map<String, Value>>
+///
+/// Flattens a VCDM 2.0 `credential` into CSD-JWT claims: the envelope fields (`@context`, `type`,
+/// `issuer`, `validFrom`, ...) as they are, and one claim per leaf of `credentialSubject`, named by
+/// its JSON pointer in the credential, e.g. `/credentialSubject/address/city`.
+///
+/// ## Error
+/// Returns [`CsdError::ClaimValidation`] if `credential` lacks the VCDM 2.0 context, the
+/// `VerifiableCredential` type, an `issuer` or a `credentialSubject`.
+///
+pub fn vcdm_claims(credential: &Value) -> CsdResult<Map<String, Value>> {
+    let credential = credential
+        .as_object()
+        .ok_or(CsdError::ClaimValidation(String::from("credential is not an object")))?;
+    if credential.get("@context").and_then(Value::as_array).and_then(|context| context.first()).and_then(Value::as_str) != Some(VCDM_CONTEXT) {
+        return Err(CsdError::ClaimValidation(format!("@context does not start with {}", VCDM_CONTEXT)));
+    }
+    let typed = match credential.get("type") {
+        Some(Value::String(credential_type)) => credential_type == VCDM_TYPE,
+        Some(Value::Array(types)) => types.iter().any(|credential_type| credential_type.as_str() == Some(VCDM_TYPE)),
+        _ => false,
+    };
+    if !typed {
+        return Err(CsdError::ClaimValidation(format!("type does not contain {}", VCDM_TYPE)));
+    }
+    if !credential.get("issuer").is_some_and(|issuer| issuer.is_string() || issuer.get("id").is_some_and(Value::is_string)) {
+        return Err(CsdError::ClaimValidation(String::from("issuer is neither a URL nor an object with an id")));
+    }
+    let subject = credential
+        .get(CREDENTIAL_SUBJECT_KEY)
+        .filter(|subject| subject.is_object() || subject.is_array())
+        .ok_or(CsdError::ClaimValidation(String::from("credentialSubject is missing")))?;
+
+    let mut claims: Map<String, Value> = credential
+        .iter()
+        .filter(|(name, _)| *name != CREDENTIAL_SUBJECT_KEY)
+        .map(|(name, value)| (name.to_owned(), value.clone()))
+        .collect();
+    flatten(&format!("/{}", CREDENTIAL_SUBJECT_KEY), subject, &mut claims);
+    Ok(claims)
+}
+
+///
+/// vcdm_issuance_policy(claims: &Map<String, Value>) -> CsdIssuancePolicy
+///
+/// Keeps the envelope fields of [`vcdm_claims`] always disclosed.
+///
+pub fn vcdm_issuance_policy(claims: &Map<String, Value>) -> CsdIssuancePolicy {
+    let mut policy = CsdIssuancePolicy::new();
+    for name in claims.keys().filter(|name| !name.starts_with('/')) {
+        policy.set(name.to_owned(), CsdSelectivity::NeverSelective);
+    }
+    policy
+}
+
+///
+/// issue_vcdm(issuer: &mut CsdIssuer<C>, credential: &Value) -> CsdResult<CsdJwt>
+///
+/// Issues a VCDM 2.0 `credential` with the claims of [`vcdm_claims`] and the
+/// [`vcdm_issuance_policy`], which replaces the issuer's issuance policy.
+///
+pub fn issue_vcdm<C: Pairing>(issuer: &mut CsdIssuer<C>, credential: &Value) -> CsdResult<CsdJwt> {
+    let claims = vcdm_claims(credential)?;
+    issuer.set_issuance_policy(vcdm_issuance_policy(&claims));
+    issuer.issue(Value::Object(claims))
+}
+
+///
+/// present_vcdm(holder: &CsdHolder, pointers: &[&str]) -> CsdResult<CsdJwt>
+///
+/// Derives a presentation of a credential issued by [`issue_vcdm`] disclosing the
+/// `credentialSubject` parts at `pointers`, e.g. `/credentialSubject/address` for every leaf of
+/// the address. The envelope is always disclosed.
+///
+/// ## Error
+/// Returns [`CsdError::InvalidPath`] if a pointer matches no leaf.
+///
+pub fn present_vcdm(holder: &CsdHolder, pointers: &[&str]) -> CsdResult<CsdJwt> {
+    let mut selected: Vec<String> = vec![];
+    for pointer in pointers {
+        let prefix = format!("{}/", pointer.trim_end_matches('/'));
+        let leaves: Vec<String> = holder
+            .disclosures()
+            .iter()
+            .filter_map(|disclosure| disclosure.claim_name.as_deref())
+            .filter(|name| name == pointer || name.starts_with(&prefix))
+            .map(|name| format!("/{}", name.replace('~', "~0").replace('/', "~1")))
+            .collect();
+        if leaves.is_empty() {
+            return Err(CsdError::InvalidPath(format!("{} is not disclosable", pointer)));
+        }
+        selected.extend(leaves);
+    }
+    holder.present(&selected.iter().map(String::as_str).collect::<Vec<&str>>())
+}
+
+///
+/// rebuild_vcdm(claims: &Map<String, Value>) -> CsdResult<Value>
+///
+/// Rebuilds a VCDM 2.0 credential from the verified `claims` of a presentation of [`issue_vcdm`]:
+/// the envelope as issued, and a `credentialSubject` holding the disclosed leaves only.
+///
+/// Containers whose keys are all indexes are rebuilt as arrays, in order and without the gaps of
+/// withheld elements.
+///
+/// ## Error
+/// Returns [`CsdError::InvalidPath`] if a leaf pointer is malformed or conflicts with another.
+///
+pub fn rebuild_vcdm(claims: &Map<String, Value>) -> CsdResult<Value> {
+    let mut credential = Map::new();
+    let mut subject = Value::Object(Map::new());
+    for (name, value) in claims {
+        match name.strip_prefix(&format!("/{}", CREDENTIAL_SUBJECT_KEY)) {
+            Some(pointer) => insert(&mut subject, pointer, value.clone())?,
+            None if name.starts_with('/') => return Err(CsdError::InvalidPath(format!("{} is not in the credentialSubject", name))),
+            None => {
+                credential.insert(name.to_owned(), value.clone());
+            }
+        }
+    }
+    credential.insert(String::from(CREDENTIAL_SUBJECT_KEY), arrays(subject));
+    Ok(Value::Object(credential))
+}
+
+///
+/// verify_vcdm(verifier: &CsdVerifier<C>, presentation: &str) -> CsdResult<Value>
+///
+/// Verifies `presentation` and rebuilds the VCDM 2.0 credential it discloses, see [`rebuild_vcdm`].
+///
+pub fn verify_vcdm<C: Pairing>(verifier: &CsdVerifier<C>, presentation: &str) -> CsdResult<Value> {
+    rebuild_vcdm(&verifier.verify(presentation)?)
+}
+
+/// Adds a claim per leaf of `value`, empty objects and arrays included, named by its pointer.
+pub(crate) fn flatten(pointer: &str, value: &Value, claims: &mut Map<String, Value>) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                flatten(&format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1")), value, claims);
+            }
+        }
+        Value::Array(array) if !array.is_empty() => {
+            for (index, value) in array.iter().enumerate() {
+                flatten(&format!("{}/{}", pointer, index), value, claims);
+            }
+        }
+        _ => {
+            claims.insert(pointer.to_owned(), value.clone());
+        }
+    }
+}
+
+/// Sets `value` at `pointer` in `target`, creating the objects on the way.
+pub(crate) fn insert(target: &mut Value, pointer: &str, value: Value) -> CsdResult<()> {
+    if pointer.is_empty() {
+        *target = value;
+        return Ok(());
+    }
+    let mut current = target;
+    let segments: Vec<String> = pointer
+        .strip_prefix('/')
+        .ok_or(CsdError::InvalidPath(format!("{} is not a JSON pointer", pointer)))?
+        .split('/')
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    for (i, segment) in segments.iter().enumerate() {
+        let object = current
+            .as_object_mut()
+            .ok_or(CsdError::InvalidPath(format!("{} conflicts with another claim", pointer)))?;
+        if i == segments.len() - 1 {
+            object.insert(segment.to_owned(), value);
+            return Ok(());
+        }
+        current = object.entry(segment.to_owned()).or_insert(Value::Object(Map::new()));
+    }
+    Ok(())
+}
+
+/// Turns the non-empty objects whose keys are all indexes into arrays sorted by index.
+pub(crate) fn arrays(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let indexed = !object.is_empty() && object.keys().all(|key| key.parse::<usize>().is_ok_and(|index| index.to_string() == *key));
+            let object: Map<String, Value> = object.into_iter().map(|(key, value)| (key, arrays(value))).collect();
+            if indexed {
+                let mut elements: Vec<(usize, Value)> = object
+                    .into_iter()
+                    .filter_map(|(key, value)| key.parse::<usize>().ok().map(|index| (index, value)))
+                    .collect();
+                elements.sort_by_key(|(index, _)| *index);
+                Value::Array(elements.into_iter().map(|(_, value)| value).collect())
+            } else {
+                Value::Object(object)
+            }
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use serde_json::{json, Value};
+
+    use crate::csd::csd_error::CsdError;
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_verifier::CsdVerifier;
+
+    use super::{issue_vcdm, present_vcdm, vcdm_claims, verify_vcdm};
+
+    fn credential() -> Value {
+        json!({
+            "@context": ["https://www.w3.org/ns/credentials/v2", "https://www.w3.org/ns/credentials/examples/v2"],
+            "id": "urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33",
+            "type": ["VerifiableCredential", "ExampleAlumniCredential"],
+            "issuer": {"id": "did:example:issuer", "name": "Example University"},
+            "validFrom": "2023-01-01T00:00:00Z",
+            "credentialSubject": {
+                "id": "did:example:subject",
+                "name": "Alice",
+                "alumniOf": {"name": "Example University", "degrees": ["BSc", "MSc"]},
+                "awards": [],
+            }
+        })
+    }
+
+    #[test]
+    fn round_trip() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let issued = issue_vcdm(&mut issuer, &credential()).unwrap().presentation();
+        let holder = CsdHolder::new(&issued).unwrap();
+        assert_eq!(holder.always_disclosed().len(), 5);
+        assert_eq!(holder.disclosures().len(), 6);
+
+        let verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        let everything = present_vcdm(&holder, &["/credentialSubject"]).unwrap().presentation();
+        assert_eq!(verify_vcdm(&verifier, &everything).unwrap(), credential());
+
+        let some = present_vcdm(&holder, &["/credentialSubject/alumniOf/degrees/1"]).unwrap().presentation();
+        let mut expected = credential();
+        expected["credentialSubject"] = json!({"alumniOf": {"degrees": ["MSc"]}});
+        assert_eq!(verify_vcdm(&verifier, &some).unwrap(), expected);
+
+        assert!(matches!(present_vcdm(&holder, &["/credentialSubject/age"]).unwrap_err(), CsdError::InvalidPath(_)));
+        let mut untyped = credential();
+        untyped["type"] = json!(["ExampleAlumniCredential"]);
+        assert!(matches!(vcdm_claims(&untyped).unwrap_err(), CsdError::ClaimValidation(_)));
+    }
+}
@@ -0,0 +1,354 @@
+use ark_ec::pairing::Pairing;
+use josekit::jws::JwsVerifier;
+use serde_json::{Map, Value};
+use vb_accumulator::setup::PublicKey;
+
+use crate::csd;
+use csd::csd_cwt::CsdCwt;
+use csd::csd_decoder::CsdDecoder;
+use csd::csd_did::CsdKeyResolver;
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_jwt::CsdJwt;
+use csd::csd_issuance::CsdIssuancePolicy;
+use csd::csd_kb_jwt::verify_key_binding;
+use csd::csd_policy::CsdValidationPolicy;
+use csd::csd_registry::CsdIssuerRegistry;
+use csd::csd_requirements::CsdPresentationRequirements;
+use csd::csd_vc::{validate_vc_disclosures, CsdProfile, CsdTypeMetadataStore};
+
+/// Verifies CSD-JWT presentations against the issuer's JWS key.
+pub struct CsdVerifier<'a, C: Pairing> {
+    decoder: CsdDecoder<'a, C>,
+    /// `None` when the issuer keys are resolved from DIDs.
+    verifier: Option<Box<dyn JwsVerifier>>,
+    requirements: Option<CsdPresentationRequirements>,
+}
+
+impl<C: Pairing> CsdVerifier<'_, C> {
+    /// Creates a new [`CsdVerifier`] checking issuer signatures with `verifier`.
+    pub fn new(verifier: Box<dyn JwsVerifier>) -> Self {
+        Self { decoder: CsdDecoder::new(), verifier: Some(verifier), requirements: None }
+    }
+
+    /// Creates a new [`CsdVerifier`] resolving the issuer keys of each presentation with
+    /// `resolver`, see [`CsdDecoder::decode_resolved`].
+    pub fn with_resolver(resolver: Box<dyn CsdKeyResolver>) -> Self {
+        let mut decoder = CsdDecoder::new();
+        decoder.set_resolver(resolver);
+        Self { decoder, verifier: None, requirements: None }
+    }
+
+    /// Restricts the accepted JWS algorithms, see [`CsdDecoder::set_allowed_algorithms`].
+    pub fn set_allowed_algorithms(&mut self, allowed_algorithms: Vec<String>) {
+        self.decoder.set_allowed_algorithms(allowed_algorithms);
+    }
+
+    /// Only accepts presentations of trusted issuers, see [`CsdDecoder::set_registry`].
+    pub fn set_registry(&mut self, registry: CsdIssuerRegistry<C>) {
+        self.decoder.set_registry(registry);
+    }
+
+    /// Only accepts presentations accumulated under the issuer's key, see
+    /// [`CsdDecoder::set_accumulator_key`].
+    pub fn set_accumulator_key(&mut self, public_key: PublicKey<C>, param_seed: u64) {
+        self.decoder.set_accumulator_key(public_key, param_seed);
+    }
+
+    /// Sets the time, issuer, audience and key binding checks, see [`CsdValidationPolicy`].
+    pub fn set_policy(&mut self, policy: CsdValidationPolicy) {
+        self.decoder.set_policy(policy);
+    }
+
+    /// Requires the credentials to follow `issuance_policy`, see [`CsdDecoder::set_issuance_policy`].
+    pub fn set_issuance_policy(&mut self, issuance_policy: CsdIssuancePolicy) {
+        self.decoder.set_issuance_policy(issuance_policy);
+    }
+
+    /// Rejects presentations which don't disclose the claims of `requirements`, see
+    /// [`CsdPresentationRequirements::check`].
+    pub fn set_requirements(&mut self, requirements: CsdPresentationRequirements) {
+        self.requirements = Some(requirements);
+    }
+
+    /// Only accepts presentations of the `profile`, see [`CsdDecoder::set_profile`].
+    pub fn set_profile(&mut self, profile: CsdProfile) {
+        self.decoder.set_profile(profile);
+    }
+
+    /// Looks the type of SD-JWT VCs up in `type_metadata`, see [`CsdDecoder::set_type_metadata`].
+    pub fn set_type_metadata(&mut self, type_metadata: CsdTypeMetadataStore) {
+        self.decoder.set_type_metadata(type_metadata);
+    }
+
+    /// Sets the number of witness verification threads, see [`CsdDecoder::set_threads`].
+    pub fn set_threads(&mut self, threads: Option<usize>) {
+        self.decoder.set_threads(threads);
+    }
+
+    /// Verifies the issuer signature of `presentation`, its claims and KB-JWT against the policy
+    /// and the witness of every disclosed claim, returning the disclosed claims along with the
+    /// always disclosed ones of the issuance policy.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::VerificationFailed`] if a disclosed claim is not in the accumulator, or
+    /// the error of the failing parsing or signature check otherwise.
+    pub fn verify(&self, presentation: &str) -> CsdResult<Map<String, Value>> {
+        let csd_jwt = CsdJwt::parse(presentation)?;
+        let (object, _header) = match &self.verifier {
+            Some(verifier) => self.decoder.decode_jwt(&csd_jwt, &**verifier)?,
+            None => self.decoder.decode_resolved(&csd_jwt)?,
+        };
+        verify_key_binding(&csd_jwt, &object, self.decoder.policy())?;
+        let disclosures = csd_jwt.disclosures
+            .iter()
+            .map(|disclosure| CsdDisclosure::parse(disclosure.to_owned()))
+            .collect::<Result<Vec<CsdDisclosure>, _>>()?;
+        if self.decoder.profile() == CsdProfile::SdJwtVc {
+            validate_vc_disclosures(&object, &disclosures, self.decoder.type_metadata())?;
+        }
+        self.verify_disclosures(&object, disclosures)
+    }
+
+    /// Same as [`CsdVerifier::verify`] for a [`CsdCwt`] presentation, which can't be key bound.
+    ///
+    /// ## Error
+    /// Returns [`CsdError::KeyBinding`] if the policy requires key binding, and
+    /// [`CsdError::DidResolution`] if the issuer keys are resolved from DIDs.
+    pub fn verify_cwt(&self, cwt: &CsdCwt) -> CsdResult<Map<String, Value>> {
+        if self.decoder.policy().requires_key_binding() {
+            return Err(CsdError::KeyBinding(String::from("CWT presentations have no KB-JWT")));
+        }
+        let verifier = self.verifier
+            .as_ref()
+            .ok_or(CsdError::DidResolution(String::from("CWT issuer keys can't be resolved")))?;
+        let object = self.decoder.decode_cwt(cwt, &**verifier)?;
+        self.verify_disclosures(&object, cwt.disclosures()?)
+    }
+
+    /// Verifies the witnesses of `disclosures` against the decoded issuer `object`, see
+    /// [`CsdDecoder::verify_disclosures`], and the requirements.
+    fn verify_disclosures(&self, object: &Map<String, Value>, disclosures: Vec<CsdDisclosure>) -> CsdResult<Map<String, Value>> {
+        let claims = self.decoder.verify_disclosures(object, disclosures)?;
+        if let Some(requirements) = &self.requirements {
+            requirements.check(&claims)?;
+        }
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::Field;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use rand::{rngs::StdRng, SeedableRng};
+    use serde_json::{json, Value};
+    use vb_accumulator::positive::Accumulator;
+    use vb_accumulator::prelude::MembershipWitness;
+    use vb_accumulator::setup::Keypair;
+
+    use crate::accumulator::{
+        deserialize_accumulator, deserialize_pk, generate_params, point_to_bytes, scalar_from_str, serialize_witness, PointEncoding,
+    };
+    use crate::csd::csd_compact::{from_compact_text, to_compact_text};
+    use crate::csd::csd_did::{did_jwk, CsdDidDocument, CsdDidResolver, CsdKeyResolver};
+    use crate::csd::csd_disclosure::CsdDisclosure;
+    use crate::csd::csd_encoder::{ACCUMULATOR_KEY, PK_KEY};
+    use crate::csd::csd_error::{CsdError, CsdResult};
+    use crate::csd::csd_holder::CsdHolder;
+    use crate::csd::csd_issuer::CsdIssuer;
+    use crate::csd::csd_jws::CsdJwsAlgorithm;
+    use crate::csd::csd_jwt::CsdJwt;
+
+    use super::CsdVerifier;
+
+    #[test]
+    fn issue_present_verify() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let credential = issuer
+            .issue(json!({"name": "Alice", "age": 30, "address": {"country": "DE"}}))
+            .unwrap()
+            .presentation();
+
+        let holder = CsdHolder::new(&credential).unwrap();
+        assert_eq!(holder.disclosures().len(), 3);
+        let presentation = holder.present(&["/name", "/address"]).unwrap().presentation();
+
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        let claims = verifier.verify(&presentation).unwrap();
+        assert_eq!(Value::Object(claims), json!({"name": "Alice", "address": {"country": "DE"}}));
+        verifier.set_threads(Some(1));
+        assert!(verifier.verify(&presentation).is_ok());
+
+        assert!(matches!(holder.present(&["/address/country"]).unwrap_err(), CsdError::InvalidPath(_)));
+        assert!(matches!(holder.present(&["/email"]).unwrap_err(), CsdError::InvalidPath(_)));
+    }
+
+    #[test]
+    fn resolved_keys() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let did = did_jwk(&jwk).unwrap();
+        let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        issuer.set_issuer(did.clone(), Some(format!("{}#0", did)));
+        issuer.set_omit_accumulator_key(true);
+        let credential = issuer.issue(json!({"name": "Alice", "age": 30})).unwrap();
+        let presentation = CsdHolder::new(&credential.presentation()).unwrap().present(&["/age"]).unwrap().presentation();
+
+        // did:jwk documents have no accumulator key.
+        let verifier = CsdVerifier::<Bn254>::with_resolver(Box::new(CsdDidResolver::new()));
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::DidResolution(_)));
+
+        let document = json!({
+            "id": did,
+            "verificationMethod": [
+                {"id": "#0", "type": "JsonWebKey2020", "publicKeyJwk": jwk.to_public_key().unwrap().as_ref()},
+                {"id": "#accumulator", "type": "JsonWebKey2020", "publicKeyJwk": issuer.accumulator_jwk().as_ref()},
+            ]
+        });
+        let resolver = StaticResolver(CsdDidDocument::from_json(&document).unwrap());
+        let verifier = CsdVerifier::<Bn254>::with_resolver(Box::new(resolver.clone()));
+        assert_eq!(Value::Object(verifier.verify(&presentation).unwrap()), json!({"age": 30}));
+
+        // A token minted with another accumulator key doesn't verify against the issuer's.
+        let mut forger = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        forger.set_issuer(did.clone(), Some(format!("{}#0", did)));
+        let forged = forger.issue(json!({"name": "Alice", "age": 30})).unwrap().presentation();
+        assert!(matches!(verifier.verify(&forged).unwrap_err(), CsdError::VerificationFailed(_)));
+    }
+
+    #[test]
+    fn point_encodings() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        for encoding in PointEncoding::ALL {
+            let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+            issuer.set_point_encoding(encoding);
+            let credential = issuer.issue(json!({"name": "Alice", "age": 30})).unwrap();
+            let payload = URL_SAFE_NO_PAD.decode(credential.jwt.split('.').nth(1).unwrap()).unwrap();
+            let payload: Value = serde_json::from_slice(&payload).unwrap();
+            let pk = payload["pk"].as_str().unwrap();
+            assert_eq!(pk, encoding.encode(&point_to_bytes(&issuer.accumulator_public_key())));
+            assert_eq!(deserialize_pk::<Bn254>(pk.to_string()).unwrap(), issuer.accumulator_public_key());
+
+            let presentation = CsdHolder::new(&credential.presentation()).unwrap().present(&["/age"]).unwrap();
+            assert_eq!(Value::Object(verifier.verify(&presentation.presentation()).unwrap()), json!({"age": 30}));
+            let compact = from_compact_text(&to_compact_text(&presentation).unwrap()).unwrap();
+            assert_eq!(compact.presentation(), presentation.presentation());
+        }
+    }
+
+    #[test]
+    fn hybrid() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        issuer.set_salted_claims(vec![String::from("age_over_18"), String::from("photo")]);
+        let credential = issuer.issue(json!({"name": "Alice", "age_over_18": true, "photo": "iVBORw0KGgo"})).unwrap();
+        let payload = URL_SAFE_NO_PAD.decode(credential.jwt.split('.').nth(1).unwrap()).unwrap();
+        let payload: Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(payload["_sd"].as_array().unwrap().len(), 2);
+
+        let holder = CsdHolder::new(&credential.presentation()).unwrap();
+        let presentation = holder.present(&["/name", "/age_over_18"]).unwrap();
+        let verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        assert_eq!(Value::Object(verifier.verify(&presentation.presentation()).unwrap()), json!({"name": "Alice", "age_over_18": true}));
+        let compact = from_compact_text(&to_compact_text(&presentation).unwrap()).unwrap();
+        assert_eq!(verifier.verify(&compact.presentation()).unwrap(), verifier.verify(&presentation.presentation()).unwrap());
+
+        // A changed value changes the digest, and the salt is no witness.
+        let salted = holder.disclosures().iter().find(|disclosure| disclosure.claim_name.as_deref() == Some("age_over_18")).unwrap();
+        let forged = CsdDisclosure::with_salt(salted.witness.clone().unwrap(), String::from("age_over_18"), json!(false));
+        let tampered = CsdJwt::new(credential.jwt.clone(), vec![forged.into_string()], None);
+        assert!(verifier.verify(&tampered.presentation()).is_err());
+        let duplicated = CsdJwt::new(credential.jwt.clone(), vec![salted.to_string(), salted.to_string()], None);
+        assert!(matches!(verifier.verify(&duplicated.presentation()).unwrap_err(), CsdError::DuplicateDigestError(_)));
+    }
+
+    /// Serves a single document, as a registry or cache would.
+    #[derive(Clone)]
+    struct StaticResolver(CsdDidDocument);
+
+    impl CsdKeyResolver for StaticResolver {
+        fn resolve(&self, did: &str) -> CsdResult<CsdDidDocument> {
+            match did == self.0.id {
+                true => Ok(self.0.clone()),
+                false => Err(CsdError::DidResolution(did.to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_claim() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let credential = issuer.issue(json!({"name": "Alice", "age": 30})).unwrap();
+
+        let holder = CsdHolder::new(&credential.presentation()).unwrap();
+        let age = &holder.disclosures()[0];
+        let forged = CsdDisclosure::with_witness(age.witness.clone().unwrap(), String::from("age"), json!(21));
+        let presentation = CsdJwt::new(credential.jwt.clone(), vec![forged.into_string()], None).presentation();
+
+        let verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::VerificationFailed(_)));
+    }
+
+    #[test]
+    fn unguessable_accumulator_key() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let payload = |credential: &CsdJwt| -> Value {
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(credential.jwt.split('.').nth(1).unwrap()).unwrap()).unwrap()
+        };
+        let issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let credential = issuer.issue(json!({"name": "Alice", "age_over_18": false})).unwrap();
+
+        // Knowing the key, anyone computes the witness V * (y + sk)^-1 of a claim y that was never
+        // issued. Issuers must not fall back to the key of the public seed 0.
+        let guessed = Keypair::<Bn254>::generate_using_rng(&mut StdRng::seed_from_u64(0), &generate_params::<Bn254>(1));
+        let accumulator = deserialize_accumulator::<Bn254>(payload(&credential)[ACCUMULATOR_KEY].as_str().unwrap().to_owned()).unwrap();
+        let element = scalar_from_str::<Bn254>("age_over_18::true");
+        let witness = accumulator.value().into_group() * (element + guessed.secret_key.0).inverse().unwrap();
+        let forged = CsdDisclosure::with_witness(serialize_witness::<Bn254>(MembershipWitness(witness.into_affine())), String::from("age_over_18"), json!(true));
+        let presentation = CsdJwt::new(credential.jwt.clone(), vec![forged.into_string()], None).presentation();
+
+        let verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        assert!(matches!(verifier.verify(&presentation).unwrap_err(), CsdError::VerificationFailed(_)));
+
+        let other = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap()).issue(json!({"name": "Alice"})).unwrap();
+        assert_ne!(payload(&credential)[PK_KEY], payload(&other)[PK_KEY]);
+    }
+
+    #[test]
+    fn accumulator_key() {
+        let alg = CsdJwsAlgorithm::ES256;
+        let jwk = alg.generate_jwk().unwrap();
+        let mut issuer = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        let present = |issuer: &CsdIssuer<Bn254>| {
+            let credential = issuer.issue(json!({"name": "Alice", "age": 30})).unwrap();
+            CsdHolder::new(&credential.presentation()).unwrap().present(&["/age"]).unwrap().presentation()
+        };
+
+        let mut verifier = CsdVerifier::<Bn254>::new(alg.verifier_from_jwk(&jwk).unwrap());
+        verifier.set_accumulator_key(issuer.accumulator_public_key(), issuer.param_seed());
+        assert_eq!(Value::Object(verifier.verify(&present(&issuer)).unwrap()), json!({"age": 30}));
+        // Without `pk` in the token, the witnesses are checked against the issuer's key.
+        issuer.set_omit_accumulator_key(true);
+        assert_eq!(Value::Object(verifier.verify(&present(&issuer)).unwrap()), json!({"age": 30}));
+
+        // Another accumulator key or other parameters, with or without `pk`.
+        let mut forger = CsdIssuer::<Bn254>::new(alg.signer_from_jwk(&jwk).unwrap());
+        assert!(matches!(verifier.verify(&present(&forger)).unwrap_err(), CsdError::VerificationFailed(_)));
+        forger.set_omit_accumulator_key(true);
+        assert!(matches!(verifier.verify(&present(&forger)).unwrap_err(), CsdError::VerificationFailed(_)));
+        issuer.set_param_seed(2);
+        assert!(matches!(verifier.verify(&present(&issuer)).unwrap_err(), CsdError::VerificationFailed(_)));
+    }
+}
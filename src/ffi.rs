@@ -0,0 +1,442 @@
+//!
+//! C API of the library, built into the `cdylib`. Issuer keys, credentials and presentations are
+//! opaque handles owned by the caller, and every fallible function returns a [`CsdErrorCode`],
+//! with the message of the last error of the thread in [`csd_last_error_message`].
+//!
+//! The header `include/csd_jwt.h` is generated from this module with cbindgen.
+//!
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::str::FromStr;
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use josekit::jwk::Jwk;
+use serde_json::Value;
+
+use crate::csd;
+use csd::csd_error::{CsdCoreError, CsdError};
+use csd::csd_holder::CsdHolder;
+use csd::csd_issuer::{jwk_accumulator_key, set_jwk_accumulator_key, CsdIssuer, ACCUMULATOR_JWK_MEMBER};
+use csd::csd_jws::CsdJwsAlgorithm;
+use csd::csd_jwt::CsdJwt;
+use csd::csd_verifier::CsdVerifier;
+
+/// The result of a C API call: [`CsdErrorCode::Ok`], a [`CsdError`] variant, or a misuse of the API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsdErrorCode {
+    Ok = 0,
+    InvalidDisclosure = 1,
+    MissingHasher = 2,
+    DataTypeMismatch = 3,
+    ClaimCollision = 4,
+    DuplicateDigest = 5,
+    InvalidArrayDisclosureObject = 6,
+    InvalidPath = 7,
+    Deserialization = 8,
+    Unspecified = 9,
+    InvalidSaltSize = 10,
+    UnusedDisclosures = 11,
+    AddBatch = 12,
+    WitnessBatch = 13,
+    RemoveBatch = 14,
+    VerificationFailed = 15,
+    Jws = 16,
+    AlgorithmNotAllowed = 17,
+    StateIntegrity = 18,
+    DidResolution = 19,
+    UntrustedIssuer = 20,
+    ClaimValidation = 21,
+    KeyBinding = 22,
+    UnmetRequirement = 23,
+    /// A required pointer argument was null.
+    NullPointer = 100,
+    /// A string argument was not valid UTF-8.
+    InvalidUtf8 = 101,
+    /// The library panicked; the handles passed to the call must not be used again.
+    Panic = 102,
+}
+
+impl From<&CsdError> for CsdErrorCode {
+    fn from(error: &CsdError) -> Self {
+        match error {
+            CsdError::Core(CsdCoreError::InvalidDisclosure(_)) => CsdErrorCode::InvalidDisclosure,
+            CsdError::MissingHasher(_) => CsdErrorCode::MissingHasher,
+            CsdError::DataTypeMismatch(_) => CsdErrorCode::DataTypeMismatch,
+            CsdError::ClaimCollisionError(_) => CsdErrorCode::ClaimCollision,
+            CsdError::DuplicateDigestError(_) => CsdErrorCode::DuplicateDigest,
+            CsdError::InvalidArrayDisclosureObject => CsdErrorCode::InvalidArrayDisclosureObject,
+            CsdError::InvalidPath(_) => CsdErrorCode::InvalidPath,
+            CsdError::Core(CsdCoreError::DeserializationError(_)) => CsdErrorCode::Deserialization,
+            CsdError::Unspecified(_) => CsdErrorCode::Unspecified,
+            CsdError::InvalidSaltSize => CsdErrorCode::InvalidSaltSize,
+            CsdError::UnusedDisclosures(_) => CsdErrorCode::UnusedDisclosures,
+            CsdError::AddBatch(_) => CsdErrorCode::AddBatch,
+            CsdError::WitnessBatch(_) => CsdErrorCode::WitnessBatch,
+            CsdError::RemoveBatch(_) => CsdErrorCode::RemoveBatch,
+            CsdError::VerificationFailed(_) => CsdErrorCode::VerificationFailed,
+            CsdError::Jws(_) => CsdErrorCode::Jws,
+            CsdError::AlgorithmNotAllowed(_) => CsdErrorCode::AlgorithmNotAllowed,
+            CsdError::StateIntegrity(_) => CsdErrorCode::StateIntegrity,
+            CsdError::DidResolution(_) => CsdErrorCode::DidResolution,
+            CsdError::UntrustedIssuer(_) => CsdErrorCode::UntrustedIssuer,
+            CsdError::ClaimValidation(_) => CsdErrorCode::ClaimValidation,
+            CsdError::KeyBinding(_) => CsdErrorCode::KeyBinding,
+            CsdError::UnmetRequirement(_) => CsdErrorCode::UnmetRequirement,
+        }
+    }
+}
+
+/// The pairing curve of the accumulator.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsdCurve {
+    Bn254 = 0,
+    Bls12381 = 1,
+}
+
+/// A JWS key of an issuer, with the curve of its accumulator. The accumulator secret key is kept
+/// in the JWK, see [`set_jwk_accumulator_key`]. A key made from a public JWK can only verify.
+pub struct CsdIssuerKey {
+    alg: CsdJwsAlgorithm,
+    jwk: Jwk,
+    curve: CsdCurve,
+}
+
+/// An issued credential, from which presentations are derived.
+pub struct CsdCredential(CsdHolder);
+
+/// A presentation disclosing some of the claims of a credential.
+pub struct CsdPresentation(CsdJwt);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// An error of a C API call, before it is reported through [`CsdErrorCode`] and [`LAST_ERROR`].
+struct FfiError {
+    code: CsdErrorCode,
+    message: String,
+}
+
+impl From<CsdError> for FfiError {
+    fn from(error: CsdError) -> Self {
+        FfiError { code: CsdErrorCode::from(&error), message: error.to_string() }
+    }
+}
+
+type FfiResult<T> = Result<T, FfiError>;
+
+/// Runs the body of a C API call, recording its error and turning panics into [`CsdErrorCode::Panic`].
+fn ffi_call(call: impl FnOnce() -> FfiResult<()>) -> CsdErrorCode {
+    let error = match catch_unwind(AssertUnwindSafe(call)) {
+        Ok(Ok(())) => return CsdErrorCode::Ok,
+        Ok(Err(error)) => error,
+        Err(_) => FfiError { code: CsdErrorCode::Panic, message: String::from("the library panicked") },
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(error.message.replace('\0', "")).ok());
+    error.code
+}
+
+/// Borrows the object behind a handle argument.
+///
+/// # Safety
+/// `pointer` must be null or point to a live `T`.
+unsafe fn borrow<'a, T>(pointer: *const T, name: &str) -> FfiResult<&'a T> {
+    pointer.as_ref().ok_or(FfiError { code: CsdErrorCode::NullPointer, message: format!("{name} is null") })
+}
+
+/// Reads a string argument.
+///
+/// # Safety
+/// `pointer` must be null or point to a NUL-terminated string.
+unsafe fn read_str<'a>(pointer: *const c_char, name: &str) -> FfiResult<&'a str> {
+    if pointer.is_null() {
+        return Err(FfiError { code: CsdErrorCode::NullPointer, message: format!("{name} is null") });
+    }
+    CStr::from_ptr(pointer)
+        .to_str()
+        .map_err(|err| FfiError { code: CsdErrorCode::InvalidUtf8, message: format!("{name} is not UTF-8: {err}") })
+}
+
+/// Hands `value` over to the caller through the output argument `out`.
+///
+/// # Safety
+/// `out` must be null or valid for writes.
+unsafe fn write_out<T>(out: *mut *mut T, value: T) -> FfiResult<()> {
+    if out.is_null() {
+        return Err(FfiError { code: CsdErrorCode::NullPointer, message: String::from("out is null") });
+    }
+    *out = Box::into_raw(Box::new(value));
+    Ok(())
+}
+
+/// Hands a string over to the caller through the output argument `out`.
+///
+/// # Safety
+/// `out` must be null or valid for writes.
+unsafe fn write_string(out: *mut *mut c_char, value: String) -> FfiResult<()> {
+    if out.is_null() {
+        return Err(FfiError { code: CsdErrorCode::NullPointer, message: String::from("out is null") });
+    }
+    let value = CString::new(value)
+        .map_err(|err| FfiError { code: CsdErrorCode::InvalidUtf8, message: err.to_string() })?;
+    *out = value.into_raw();
+    Ok(())
+}
+
+/// Frees a handle returned by the library, ignoring null.
+///
+/// # Safety
+/// `pointer` must be null or a handle of type `T` that hasn't been freed.
+unsafe fn free<T>(pointer: *mut T) {
+    if !pointer.is_null() {
+        drop(Box::from_raw(pointer));
+    }
+}
+
+/// The message of the last error on the calling thread, or null if there was none. The string is
+/// owned by the library and valid until the next failing call on the thread.
+#[no_mangle]
+pub extern "C" fn csd_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Frees a string returned by the library.
+///
+/// # Safety
+/// `string` must be null or a string returned by the library that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn csd_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Generates an issuer key for the JWS algorithm `alg` (ES256, ES384, ES512, EdDSA or PS256),
+/// along with a random accumulator secret key on `curve`.
+///
+/// # Safety
+/// `alg` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_issuer_key_generate(alg: *const c_char, curve: CsdCurve, out: *mut *mut CsdIssuerKey) -> CsdErrorCode {
+    ffi_call(|| {
+        let alg = CsdJwsAlgorithm::from_str(read_str(alg, "alg")?)?;
+        let mut jwk = alg.generate_jwk()?;
+        match curve {
+            CsdCurve::Bn254 => add_accumulator_key::<Bn254>(&alg, &mut jwk)?,
+            CsdCurve::Bls12381 => add_accumulator_key::<Bls12_381>(&alg, &mut jwk)?,
+        }
+        write_out(out, CsdIssuerKey { alg, jwk, curve })
+    })
+}
+
+fn add_accumulator_key<C: Pairing>(alg: &CsdJwsAlgorithm, jwk: &mut Jwk) -> FfiResult<()> {
+    let issuer = CsdIssuer::<C>::new(alg.signer_from_jwk(jwk)?);
+    Ok(set_jwk_accumulator_key(jwk, &issuer, true)?)
+}
+
+/// Reads an issuer key from a private or public JWK, whose `alg` parameter names the JWS algorithm.
+/// Only private JWKs written by [`csd_issuer_key_jwk`] hold the accumulator key needed to issue.
+///
+/// # Safety
+/// `jwk` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_issuer_key_from_jwk(jwk: *const c_char, curve: CsdCurve, out: *mut *mut CsdIssuerKey) -> CsdErrorCode {
+    ffi_call(|| {
+        let jwk = Jwk::from_bytes(read_str(jwk, "jwk")?.as_bytes())
+            .map_err(|err| CsdError::Jws(err.to_string()))?;
+        let alg = jwk
+            .algorithm()
+            .ok_or(CsdError::Jws(String::from("the JWK has no alg parameter")))?;
+        let alg = CsdJwsAlgorithm::from_str(alg)?;
+        write_out(out, CsdIssuerKey { alg, jwk, curve })
+    })
+}
+
+/// Writes the public JWK of `key` to `out`, to be freed with [`csd_string_free`]. It holds the
+/// accumulator public key if `key` has an accumulator secret key.
+///
+/// # Safety
+/// `key` must be a live issuer key and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_issuer_key_public_jwk(key: *const CsdIssuerKey, out: *mut *mut c_char) -> CsdErrorCode {
+    ffi_call(|| {
+        let key = borrow(key, "key")?;
+        let mut public_jwk = key.jwk.to_public_key().map_err(|err| CsdError::Jws(err.to_string()))?;
+        public_jwk.set_algorithm(key.alg.name());
+        match key.curve {
+            CsdCurve::Bn254 => add_accumulator_public_key::<Bn254>(key, &mut public_jwk)?,
+            CsdCurve::Bls12381 => add_accumulator_public_key::<Bls12_381>(key, &mut public_jwk)?,
+        }
+        write_string(out, public_jwk.to_string())
+    })
+}
+
+fn add_accumulator_public_key<C: Pairing>(key: &CsdIssuerKey, public_jwk: &mut Jwk) -> FfiResult<()> {
+    match key.jwk.parameter(ACCUMULATOR_JWK_MEMBER) {
+        Some(member) if member.get("sk").is_some() => set_jwk_accumulator_key(public_jwk, &accumulator_issuer::<C>(key)?, false)?,
+        // Keys made from a public JWK already hold the accumulator public key.
+        Some(member) => public_jwk
+            .set_parameter(ACCUMULATOR_JWK_MEMBER, Some(member.clone()))
+            .map_err(|err| CsdError::Jws(err.to_string()))?,
+        None => {}
+    }
+    Ok(())
+}
+
+/// An issuer signing with the JWS key of `key` and accumulating with its accumulator key.
+fn accumulator_issuer<C: Pairing>(key: &CsdIssuerKey) -> FfiResult<CsdIssuer<C>> {
+    let mut issuer = CsdIssuer::<C>::new(key.alg.signer_from_jwk(&key.jwk)?);
+    let secret_key = jwk_accumulator_key::<C>(&key.jwk)?
+        .ok_or(CsdError::Unspecified(String::from("the issuer key has no accumulator key")))?;
+    issuer.set_accumulator_secret_key(secret_key);
+    Ok(issuer)
+}
+
+/// Writes the JWK of `key`, private along with its accumulator key if it is, to `out`, to be freed
+/// with [`csd_string_free`].
+///
+/// # Safety
+/// `key` must be a live issuer key and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_issuer_key_jwk(key: *const CsdIssuerKey, out: *mut *mut c_char) -> CsdErrorCode {
+    ffi_call(|| write_string(out, borrow(key, "key")?.jwk.to_string()))
+}
+
+/// Frees an issuer key.
+///
+/// # Safety
+/// `key` must be null or an issuer key that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn csd_issuer_key_free(key: *mut CsdIssuerKey) {
+    free(key)
+}
+
+/// Issues a credential for the JSON object `claims`, with one disclosure per claim.
+///
+/// # Safety
+/// `key` must be a live issuer key, `claims` a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_issue(key: *const CsdIssuerKey, claims: *const c_char, out: *mut *mut CsdCredential) -> CsdErrorCode {
+    ffi_call(|| {
+        let key = borrow(key, "key")?;
+        let claims: Value = serde_json::from_str(read_str(claims, "claims")?)
+            .map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(err.to_string())))?;
+        let credential = match key.curve {
+            CsdCurve::Bn254 => issue_on::<Bn254>(key, claims)?,
+            CsdCurve::Bls12381 => issue_on::<Bls12_381>(key, claims)?,
+        };
+        write_out(out, CsdCredential(CsdHolder::new(&credential.presentation())?))
+    })
+}
+
+fn issue_on<C: Pairing>(key: &CsdIssuerKey, claims: Value) -> FfiResult<CsdJwt> {
+    Ok(accumulator_issuer::<C>(key)?.issue(claims)?)
+}
+
+/// Reads a serialized credential.
+///
+/// # Safety
+/// `credential` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_credential_parse(credential: *const c_char, out: *mut *mut CsdCredential) -> CsdErrorCode {
+    ffi_call(|| write_out(out, CsdCredential(CsdHolder::new(read_str(credential, "credential")?)?)))
+}
+
+/// Writes the serialized `credential` to `out`, to be freed with [`csd_string_free`].
+///
+/// # Safety
+/// `credential` must be a live credential and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_credential_serialize(credential: *const CsdCredential, out: *mut *mut c_char) -> CsdErrorCode {
+    ffi_call(|| write_string(out, borrow(credential, "credential")?.0.credential().presentation()))
+}
+
+/// Frees a credential.
+///
+/// # Safety
+/// `credential` must be null or a credential that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn csd_credential_free(credential: *mut CsdCredential) {
+    free(credential)
+}
+
+/// Derives a presentation of `credential` disclosing the `count` claims at `pointers`, e.g. `"/name"`.
+///
+/// # Safety
+/// `credential` must be a live credential, `pointers` an array of `count` NUL-terminated strings
+/// (or null if `count` is 0) and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_derive_presentation(
+    credential: *const CsdCredential,
+    pointers: *const *const c_char,
+    count: usize,
+    out: *mut *mut CsdPresentation,
+) -> CsdErrorCode {
+    ffi_call(|| {
+        let holder = &borrow(credential, "credential")?.0;
+        let pointers = match count {
+            0 => vec![],
+            _ => std::slice::from_raw_parts(borrow(pointers, "pointers")?, count)
+                .iter()
+                .map(|pointer| read_str(*pointer, "pointer"))
+                .collect::<FfiResult<Vec<&str>>>()?,
+        };
+        write_out(out, CsdPresentation(holder.present(&pointers)?))
+    })
+}
+
+/// Reads a serialized presentation.
+///
+/// # Safety
+/// `presentation` must be a NUL-terminated string and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_presentation_parse(presentation: *const c_char, out: *mut *mut CsdPresentation) -> CsdErrorCode {
+    ffi_call(|| write_out(out, CsdPresentation(CsdJwt::parse(read_str(presentation, "presentation")?)?)))
+}
+
+/// Writes the serialized `presentation` to `out`, to be freed with [`csd_string_free`].
+///
+/// # Safety
+/// `presentation` must be a live presentation and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_presentation_serialize(presentation: *const CsdPresentation, out: *mut *mut c_char) -> CsdErrorCode {
+    ffi_call(|| write_string(out, borrow(presentation, "presentation")?.0.presentation()))
+}
+
+/// Frees a presentation.
+///
+/// # Safety
+/// `presentation` must be null or a presentation that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn csd_presentation_free(presentation: *mut CsdPresentation) {
+    free(presentation)
+}
+
+/// Verifies `presentation` against the issuer `key` and writes the disclosed claims, as a JSON
+/// object, to `out`, to be freed with [`csd_string_free`].
+///
+/// # Safety
+/// `key` must be a live issuer key, `presentation` a live presentation and `out` valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn csd_verify(key: *const CsdIssuerKey, presentation: *const CsdPresentation, out: *mut *mut c_char) -> CsdErrorCode {
+    ffi_call(|| {
+        let key = borrow(key, "key")?;
+        let presentation = borrow(presentation, "presentation")?.0.presentation();
+        let claims = match key.curve {
+            CsdCurve::Bn254 => verify_on::<Bn254>(key, &presentation)?,
+            CsdCurve::Bls12381 => verify_on::<Bls12_381>(key, &presentation)?,
+        };
+        write_string(out, claims.to_string())
+    })
+}
+
+fn verify_on<C: Pairing>(key: &CsdIssuerKey, presentation: &str) -> FfiResult<Value> {
+    let verifier = CsdVerifier::<C>::new(key.alg.verifier_from_jwk(&key.jwk)?);
+    Ok(Value::Object(verifier.verify(presentation)?))
+}
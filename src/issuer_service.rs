@@ -0,0 +1,185 @@
+use std::sync::{PoisonError, RwLock};
+
+use ark_ec::pairing::Pairing;
+use vb_accumulator::{
+    positive::{Accumulator, PositiveAccumulator},
+    prelude::MembershipWitness,
+    setup::{Keypair, PublicKey, SecretKey, SetupParams},
+};
+
+use crate::accumulator::{initialize_accumulator, scalar_from_str};
+use crate::csd::csd_error::{CsdError, CsdResult};
+use crate::state::{InMemoryState, SharedState};
+
+/// An accumulator value together with the epoch it was published in.
+///
+/// The epoch starts at 0 and is bumped by every mutation, so a witness handed out together with an
+/// [`AccumulatorEpoch`] is valid exactly for that epoch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccumulatorEpoch<C: Pairing> {
+    pub accumulator: PositiveAccumulator<C>,
+    pub epoch: u64,
+}
+
+/// Witnesses together with the epoch they are valid for.
+pub type IssuedWitnesses<C> = (Vec<MembershipWitness<<C as Pairing>::G1Affine>>, AccumulatorEpoch<C>);
+
+/// Issuer-side owner of a shared (revocation or batch) accumulator.
+///
+/// Mutations are serialized behind a write lock that covers both the accumulator value and its
+/// [`SharedState`], while witnesses are computed concurrently under the read lock. Readers therefore
+/// always observe an accumulator that matches the state.
+pub struct IssuerService<C: Pairing> {
+    params: SetupParams<C>,
+    keypair: Keypair<C>,
+    current: RwLock<AccumulatorEpoch<C>>,
+    state: SharedState<C::ScalarField>,
+}
+
+impl<C: Pairing> IssuerService<C> {
+    /// Creates a new [`IssuerService`] with an empty accumulator.
+    pub fn new(params: SetupParams<C>, keypair: Keypair<C>) -> Self {
+        let accumulator = PositiveAccumulator::initialize(&params);
+        Self::restore(params, keypair, accumulator, 0, InMemoryState::new())
+    }
+
+    /// Creates a new [`IssuerService`] the same way [`initialize_accumulator`] does.
+    pub fn from_secret_key(secret_key: &SecretKey<C::ScalarField>, param_seed: u64) -> Self {
+        let (params, keypair, accumulator, state) = initialize_accumulator::<C>(secret_key, param_seed);
+        Self::restore(params, keypair, accumulator, 0, state)
+    }
+
+    /// Resumes an [`IssuerService`] from a previously published accumulator and its state,
+    /// e.g. one obtained through [`InMemoryState::restore`].
+    pub fn restore(
+        params: SetupParams<C>,
+        keypair: Keypair<C>,
+        accumulator: PositiveAccumulator<C>,
+        epoch: u64,
+        state: InMemoryState<C::ScalarField>,
+    ) -> Self {
+        Self {
+            params,
+            keypair,
+            current: RwLock::new(AccumulatorEpoch { accumulator, epoch }),
+            state: SharedState::from(state),
+        }
+    }
+
+    pub fn params(&self) -> &SetupParams<C> {
+        &self.params
+    }
+
+    pub fn public_key(&self) -> &PublicKey<C> {
+        &self.keypair.public_key
+    }
+
+    /// Returns a consistent view of the current accumulator and epoch.
+    pub fn current(&self) -> AccumulatorEpoch<C> {
+        self.current.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Returns a snapshot-able copy of the state as of the current epoch.
+    pub fn state(&self) -> InMemoryState<C::ScalarField> {
+        let _current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        self.state.read().clone()
+    }
+
+    /// Adds a batch of elements to the accumulator and starts a new epoch.
+    pub fn add_batch(&self, elements: Vec<C::ScalarField>) -> CsdResult<AccumulatorEpoch<C>> {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let accumulator = current.accumulator
+            .add_batch(elements, &self.keypair.secret_key, &mut self.state.clone())
+            .map_err(|err| CsdError::AddBatch(format!("{:?}", err)))?;
+
+        *current = AccumulatorEpoch { accumulator, epoch: current.epoch + 1 };
+        Ok(current.clone())
+    }
+
+    /// Removes a batch of elements from the accumulator and starts a new epoch.
+    pub fn remove_batch(&self, elements: &[C::ScalarField]) -> CsdResult<AccumulatorEpoch<C>> {
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let accumulator = current.accumulator
+            .remove_batch(elements, &self.keypair.secret_key, &mut self.state.clone())
+            .map_err(|err| CsdError::RemoveBatch(format!("{:?}", err)))?;
+
+        *current = AccumulatorEpoch { accumulator, epoch: current.epoch + 1 };
+        Ok(current.clone())
+    }
+
+    /// Accumulates `key::value` claims and returns their witnesses for the resulting epoch.
+    ///
+    /// Addition and witness generation happen under the same lock, so the witnesses can't be
+    /// invalidated by a concurrent issuance before they are handed out.
+    pub fn issue_claims(&self, claims: &[String]) -> CsdResult<IssuedWitnesses<C>> {
+        let elements: Vec<C::ScalarField> = claims.iter().map(|claim| scalar_from_str::<C>(claim)).collect();
+
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let accumulator = current.accumulator
+            .add_batch(elements.clone(), &self.keypair.secret_key, &mut self.state.clone())
+            .map_err(|err| CsdError::AddBatch(format!("{:?}", err)))?;
+        let witnesses = accumulator
+            .get_membership_witnesses_for_batch(&elements, &self.keypair.secret_key, &*self.state.read())
+            .map_err(|err| CsdError::WitnessBatch(format!("{:?}", err)))?;
+
+        *current = AccumulatorEpoch { accumulator, epoch: current.epoch + 1 };
+        Ok((witnesses, current.clone()))
+    }
+
+    /// Computes the witnesses of already accumulated elements for the current epoch.
+    pub fn witnesses(&self, elements: &[C::ScalarField]) -> CsdResult<IssuedWitnesses<C>> {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        let witnesses = current.accumulator
+            .get_membership_witnesses_for_batch(elements, &self.keypair.secret_key, &*self.state.read())
+            .map_err(|err| CsdError::WitnessBatch(format!("{:?}", err)))?;
+
+        Ok((witnesses, current.clone()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::thread;
+
+    use ark_bn254::Bn254;
+    use vb_accumulator::positive::Accumulator;
+
+    use crate::accumulator::{generate_secret_key, scalar_from_str, verify_state};
+
+    use super::IssuerService;
+
+    #[test]
+    fn concurrent_issuance() {
+        let service = Arc::new(IssuerService::<Bn254>::from_secret_key(&generate_secret_key::<Bn254>(), 1u64));
+
+        let handles = (0..8).map(|t| {
+            let service = service.clone();
+            thread::spawn(move || {
+                let claims: Vec<String> = (0..4).map(|i| format!("Thread {t}::Claim {i}")).collect();
+                let (witnesses, issued) = service.issue_claims(&claims).unwrap();
+                for (claim, witness) in claims.iter().zip(witnesses.iter()) {
+                    assert!(issued.accumulator.verify_membership(&scalar_from_str::<Bn254>(claim), witness, service.public_key(), service.params()));
+                }
+                claims
+            })
+        }).collect::<Vec<_>>();
+        let claims: Vec<String> = handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect();
+
+        let current = service.current();
+        assert_eq!(current.epoch, 8);
+        let elements: Vec<_> = claims.iter().map(|claim| scalar_from_str::<Bn254>(claim)).collect();
+        let (witnesses, epoch) = service.witnesses(&elements).unwrap();
+        assert_eq!(epoch, current);
+        for (element, witness) in elements.iter().zip(witnesses.iter()) {
+            assert!(current.accumulator.verify_membership(element, witness, service.public_key(), service.params()));
+        }
+
+        let state = service.state();
+        assert_eq!(state.db.len(), 32);
+        let removed = service.remove_batch(&elements[..4]).unwrap();
+        assert_eq!(removed.epoch, 9);
+        assert!(!verify_state(&state, &removed.accumulator, service.params(), &service.keypair.secret_key));
+        assert!(verify_state(&service.state(), &removed.accumulator, service.params(), &service.keypair.secret_key));
+    }
+}
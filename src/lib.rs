@@ -0,0 +1,21 @@
+//!
+//! CSD-JWT: selectively disclosable JWTs whose claims are committed to with a cryptographic
+//! accumulator instead of salted digests.
+//!
+//! [`csd::csd_issuer::CsdIssuer`] issues credentials, [`csd::csd_holder::CsdHolder`] derives
+//! presentations from them and [`csd::csd_verifier::CsdVerifier`] verifies those presentations.
+//! The same flow is exposed to C through [`ffi`], and to Python with the `python` feature. On
+//! wasm32, `wasm` exposes the verifier to JavaScript.
+//!
+pub mod accumulator;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bls;
+pub mod csd;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ffi;
+pub mod issuer_service;
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+pub mod python;
+pub mod state;
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
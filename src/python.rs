@@ -0,0 +1,367 @@
+//!
+//! Python bindings of the issuer, holder and verifier flow, built with the `python` feature into
+//! the `csd_jwt` extension module. Claims are passed as Python objects through their JSON form,
+//! and every [`CsdError`] variant is raised as its own subclass of `csd_jwt.CsdError`.
+//!
+use std::str::FromStr;
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use josekit::jwk::Jwk;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use serde_json::Value;
+
+use crate::csd;
+use csd::csd_encoder::{CsdEncoder, DEFAULT_PARAM_SEED};
+use csd::csd_error::{CsdCoreError, CsdError};
+use csd::csd_holder::CsdHolder;
+use csd::csd_issuer::{jwk_accumulator_key, set_jwk_accumulator_key, CsdIssuer};
+use csd::csd_issuance::{CsdIssuancePolicy, CsdSelectivity};
+use csd::csd_jws::{issue_with_key_id, CsdJwsAlgorithm};
+use csd::csd_verifier::CsdVerifier;
+
+/// The Python exceptions, one per [`CsdError`] variant, all deriving from `CsdError`.
+mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyException;
+
+    create_exception!(csd_jwt, CsdError, PyException, "Base class of the errors of csd_jwt.");
+    create_exception!(csd_jwt, InvalidDisclosureError, CsdError);
+    create_exception!(csd_jwt, MissingHasherError, CsdError);
+    create_exception!(csd_jwt, DataTypeMismatchError, CsdError);
+    create_exception!(csd_jwt, ClaimCollisionError, CsdError);
+    create_exception!(csd_jwt, DuplicateDigestError, CsdError);
+    create_exception!(csd_jwt, InvalidArrayDisclosureObjectError, CsdError);
+    create_exception!(csd_jwt, InvalidPathError, CsdError);
+    create_exception!(csd_jwt, DeserializationError, CsdError);
+    create_exception!(csd_jwt, UnspecifiedError, CsdError);
+    create_exception!(csd_jwt, InvalidSaltSizeError, CsdError);
+    create_exception!(csd_jwt, UnusedDisclosuresError, CsdError);
+    create_exception!(csd_jwt, AddBatchError, CsdError);
+    create_exception!(csd_jwt, WitnessBatchError, CsdError);
+    create_exception!(csd_jwt, RemoveBatchError, CsdError);
+    create_exception!(csd_jwt, VerificationFailedError, CsdError);
+    create_exception!(csd_jwt, JwsError, CsdError);
+    create_exception!(csd_jwt, AlgorithmNotAllowedError, CsdError);
+    create_exception!(csd_jwt, StateIntegrityError, CsdError);
+    create_exception!(csd_jwt, DidResolutionError, CsdError);
+    create_exception!(csd_jwt, UntrustedIssuerError, CsdError);
+    create_exception!(csd_jwt, ClaimValidationError, CsdError);
+    create_exception!(csd_jwt, KeyBindingError, CsdError);
+    create_exception!(csd_jwt, UnmetRequirementError, CsdError);
+}
+
+impl From<CsdError> for PyErr {
+    fn from(error: CsdError) -> Self {
+        let message = error.to_string();
+        match error {
+            CsdError::Core(CsdCoreError::InvalidDisclosure(_)) => exceptions::InvalidDisclosureError::new_err(message),
+            CsdError::MissingHasher(_) => exceptions::MissingHasherError::new_err(message),
+            CsdError::DataTypeMismatch(_) => exceptions::DataTypeMismatchError::new_err(message),
+            CsdError::ClaimCollisionError(_) => exceptions::ClaimCollisionError::new_err(message),
+            CsdError::DuplicateDigestError(_) => exceptions::DuplicateDigestError::new_err(message),
+            CsdError::InvalidArrayDisclosureObject => exceptions::InvalidArrayDisclosureObjectError::new_err(message),
+            CsdError::InvalidPath(_) => exceptions::InvalidPathError::new_err(message),
+            CsdError::Core(CsdCoreError::DeserializationError(_)) => exceptions::DeserializationError::new_err(message),
+            CsdError::Unspecified(_) => exceptions::UnspecifiedError::new_err(message),
+            CsdError::InvalidSaltSize => exceptions::InvalidSaltSizeError::new_err(message),
+            CsdError::UnusedDisclosures(_) => exceptions::UnusedDisclosuresError::new_err(message),
+            CsdError::AddBatch(_) => exceptions::AddBatchError::new_err(message),
+            CsdError::WitnessBatch(_) => exceptions::WitnessBatchError::new_err(message),
+            CsdError::RemoveBatch(_) => exceptions::RemoveBatchError::new_err(message),
+            CsdError::VerificationFailed(_) => exceptions::VerificationFailedError::new_err(message),
+            CsdError::Jws(_) => exceptions::JwsError::new_err(message),
+            CsdError::AlgorithmNotAllowed(_) => exceptions::AlgorithmNotAllowedError::new_err(message),
+            CsdError::StateIntegrity(_) => exceptions::StateIntegrityError::new_err(message),
+            CsdError::DidResolution(_) => exceptions::DidResolutionError::new_err(message),
+            CsdError::UntrustedIssuer(_) => exceptions::UntrustedIssuerError::new_err(message),
+            CsdError::ClaimValidation(_) => exceptions::ClaimValidationError::new_err(message),
+            CsdError::KeyBinding(_) => exceptions::KeyBindingError::new_err(message),
+            CsdError::UnmetRequirement(_) => exceptions::UnmetRequirementError::new_err(message),
+        }
+    }
+}
+
+/// The pairing curve of the accumulator, named `"bn254"` or `"bls12381"` in Python.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Curve {
+    Bn254,
+    Bls12381,
+}
+
+impl FromStr for Curve {
+    type Err = PyErr;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bn254" => Ok(Curve::Bn254),
+            "bls12381" => Ok(Curve::Bls12381),
+            _ => Err(PyValueError::new_err(format!("unknown curve {s}, expected bn254 or bls12381"))),
+        }
+    }
+}
+
+/// Reads a Python object, or a JSON string, as JSON.
+fn to_json(object: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let json = match object.cast::<PyString>() {
+        Ok(string) => string.to_string(),
+        Err(_) => object.py().import("json")?.call_method1("dumps", (object,))?.extract()?,
+    };
+    Ok(serde_json::from_str(&json).map_err(|err| CsdError::Core(CsdCoreError::DeserializationError(err.to_string())))?)
+}
+
+/// Turns JSON into the equivalent Python object.
+fn from_json<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    py.import("json")?.call_method1("loads", (value.to_string(),))
+}
+
+/// Reads a JWK and the JWS algorithm of its `alg` parameter.
+fn read_jwk(jwk: &str) -> PyResult<(Jwk, CsdJwsAlgorithm)> {
+    let jwk = Jwk::from_bytes(jwk.as_bytes()).map_err(|err| CsdError::Jws(err.to_string()))?;
+    let alg = jwk
+        .algorithm()
+        .ok_or(CsdError::Jws(String::from("the JWK has no alg parameter")))?;
+    let alg = CsdJwsAlgorithm::from_str(alg)?;
+    Ok((jwk, alg))
+}
+
+/// Generates a private JWK for the JWS algorithm `alg`: ES256, ES384, ES512, EdDSA or PS256,
+/// holding a random accumulator secret key on `curve` as well.
+#[pyfunction]
+#[pyo3(signature = (alg = "ES256", curve = "bn254"))]
+fn generate_key(alg: &str, curve: &str) -> PyResult<String> {
+    let alg = CsdJwsAlgorithm::from_str(alg)?;
+    let mut jwk = alg.generate_jwk()?;
+    match Curve::from_str(curve)? {
+        Curve::Bn254 => add_accumulator_key::<Bn254>(&alg, &mut jwk)?,
+        Curve::Bls12381 => add_accumulator_key::<Bls12_381>(&alg, &mut jwk)?,
+    }
+    Ok(jwk.to_string())
+}
+
+fn add_accumulator_key<C: Pairing>(alg: &CsdJwsAlgorithm, jwk: &mut Jwk) -> PyResult<()> {
+    let issuer = CsdIssuer::<C>::new(alg.signer_from_jwk(jwk)?);
+    Ok(set_jwk_accumulator_key(jwk, &issuer, true)?)
+}
+
+/// The public JWK of the private `jwk`, keeping its `alg`.
+#[pyfunction]
+fn public_key(jwk: &str) -> PyResult<String> {
+    let (jwk, alg) = read_jwk(jwk)?;
+    let mut public_jwk = jwk.to_public_key().map_err(|err| CsdError::Jws(err.to_string()))?;
+    public_jwk.set_algorithm(alg.name());
+    Ok(public_jwk.to_string())
+}
+
+/// Issues credentials for a claims object with a [`CsdEncoder`], one disclosure per claim.
+#[pyclass]
+struct Encoder {
+    claims: Value,
+    curve: Curve,
+    param_seed: u64,
+    issuer: Option<String>,
+    key_id: Option<String>,
+    credential_type: Option<String>,
+    always_disclosed: Vec<String>,
+}
+
+#[pymethods]
+impl Encoder {
+    #[new]
+    #[pyo3(signature = (claims, curve = "bn254"))]
+    fn new(claims: &Bound<'_, PyAny>, curve: &str) -> PyResult<Self> {
+        Ok(Encoder {
+            claims: to_json(claims)?,
+            curve: Curve::from_str(curve)?,
+            param_seed: DEFAULT_PARAM_SEED,
+            issuer: None,
+            key_id: None,
+            credential_type: None,
+            always_disclosed: vec![],
+        })
+    }
+
+    /// Sets the seed of the accumulator parameters.
+    fn set_param_seed(&mut self, param_seed: u64) {
+        self.param_seed = param_seed;
+    }
+
+    /// Sets the `iss` claim, and the `kid` header naming the signing key.
+    #[pyo3(signature = (issuer, key_id = None))]
+    fn set_issuer(&mut self, issuer: String, key_id: Option<String>) {
+        self.issuer = Some(issuer);
+        self.key_id = key_id;
+    }
+
+    /// Sets the `vct` claim.
+    fn set_credential_type(&mut self, credential_type: String) {
+        self.credential_type = Some(credential_type);
+    }
+
+    /// Keeps the claims `names` plain, so every presentation discloses them.
+    fn set_always_disclosed(&mut self, names: Vec<String>) {
+        self.always_disclosed = names;
+    }
+
+    /// Signs the credential with the private `jwk`, accumulating with the accumulator key that
+    /// [`generate_key`] stored in it, and returns it serialized.
+    fn issue(&self, jwk: &str) -> PyResult<String> {
+        match self.curve {
+            Curve::Bn254 => self.issue_on::<Bn254>(jwk),
+            Curve::Bls12381 => self.issue_on::<Bls12_381>(jwk),
+        }
+    }
+}
+
+impl Encoder {
+    fn issue_on<C: Pairing>(&self, jwk: &str) -> PyResult<String> {
+        let (jwk, alg) = read_jwk(jwk)?;
+        let mut issuance_policy = CsdIssuancePolicy::new();
+        for name in &self.always_disclosed {
+            issuance_policy.set(name.to_owned(), CsdSelectivity::NeverSelective);
+        }
+
+        let secret_key = jwk_accumulator_key::<C>(&jwk)
+            ?
+            .ok_or_else(|| CsdError::Unspecified(String::from("the JWK has no accumulator key, generate it with generate_key")))?;
+
+        let mut encoder: CsdEncoder<C> = self.claims.clone().try_into()?;
+        encoder.set_secret_key(secret_key);
+        encoder.set_param_seed(self.param_seed);
+        if let Some(issuer) = &self.issuer {
+            encoder.set_issuer(issuer);
+        }
+        if let Some(credential_type) = &self.credential_type {
+            encoder.set_credential_type(credential_type);
+        }
+        encoder.set_issuance_policy(issuance_policy);
+        encoder.add_sd_alg_property();
+        let (object, disclosures) = encoder.encode()?;
+
+        let mut credential = issue_with_key_id(&object, &*alg.signer_from_jwk(&jwk)?, self.key_id.as_deref())?;
+        credential.disclosures = disclosures.into_iter().map(|disclosure| disclosure.into_string()).collect();
+        Ok(credential.presentation())
+    }
+}
+
+/// Holds a credential and derives presentations from it, see [`CsdHolder`].
+#[pyclass]
+struct Holder(CsdHolder);
+
+#[pymethods]
+impl Holder {
+    #[new]
+    fn new(credential: &str) -> PyResult<Self> {
+        Ok(Holder(CsdHolder::new(credential)?))
+    }
+
+    /// The names of the selectively disclosable claims.
+    #[getter]
+    fn disclosures(&self) -> Vec<String> {
+        self.0.disclosures().iter().filter_map(|disclosure| disclosure.claim_name.clone()).collect()
+    }
+
+    /// The names of the claims every presentation discloses.
+    #[getter]
+    fn always_disclosed(&self) -> Vec<String> {
+        self.0.always_disclosed().to_vec()
+    }
+
+    /// All the claims of the credential.
+    fn claims<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        from_json(py, &Value::Object(self.0.claims()?))
+    }
+
+    /// Derives a presentation disclosing the claims at `pointers`, e.g. `"/name"`.
+    fn present(&self, pointers: Vec<String>) -> PyResult<String> {
+        let pointers: Vec<&str> = pointers.iter().map(String::as_str).collect();
+        Ok(self.0.present(&pointers)?.presentation())
+    }
+}
+
+/// Verifies presentations with a [`csd::csd_decoder::CsdDecoder`], as [`CsdVerifier`] does.
+#[pyclass]
+struct Decoder {
+    curve: Curve,
+    allowed_algorithms: Option<Vec<String>>,
+    threads: Option<usize>,
+}
+
+#[pymethods]
+impl Decoder {
+    #[new]
+    #[pyo3(signature = (curve = "bn254"))]
+    fn new(curve: &str) -> PyResult<Self> {
+        Ok(Decoder { curve: Curve::from_str(curve)?, allowed_algorithms: None, threads: None })
+    }
+
+    /// Restricts the accepted JWS algorithms.
+    fn set_allowed_algorithms(&mut self, allowed_algorithms: Vec<String>) {
+        self.allowed_algorithms = Some(allowed_algorithms);
+    }
+
+    /// Sets the number of witness verification threads, or the default with `None`.
+    #[pyo3(signature = (threads = None))]
+    fn set_threads(&mut self, threads: Option<usize>) {
+        self.threads = threads;
+    }
+
+    /// Verifies `presentation` against the issuer's public `jwk` and returns the disclosed claims.
+    fn verify<'py>(&self, py: Python<'py>, presentation: &str, jwk: &str) -> PyResult<Bound<'py, PyAny>> {
+        let claims = match self.curve {
+            Curve::Bn254 => self.verify_on::<Bn254>(presentation, jwk)?,
+            Curve::Bls12381 => self.verify_on::<Bls12_381>(presentation, jwk)?,
+        };
+        from_json(py, &claims)
+    }
+}
+
+impl Decoder {
+    fn verify_on<C: Pairing>(&self, presentation: &str, jwk: &str) -> PyResult<Value> {
+        let (jwk, alg) = read_jwk(jwk)?;
+        let mut verifier = CsdVerifier::<C>::new(alg.verifier_from_jwk(&jwk)?);
+        if let Some(allowed_algorithms) = &self.allowed_algorithms {
+            verifier.set_allowed_algorithms(allowed_algorithms.clone());
+        }
+        verifier.set_threads(self.threads);
+        Ok(Value::Object(verifier.verify(presentation)?))
+    }
+}
+
+#[pymodule]
+fn csd_jwt(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(generate_key, m)?)?;
+    m.add_function(wrap_pyfunction!(public_key, m)?)?;
+    m.add_class::<Encoder>()?;
+    m.add_class::<Holder>()?;
+    m.add_class::<Decoder>()?;
+
+    let py = m.py();
+    m.add("CsdError", py.get_type::<exceptions::CsdError>())?;
+    m.add("InvalidDisclosureError", py.get_type::<exceptions::InvalidDisclosureError>())?;
+    m.add("MissingHasherError", py.get_type::<exceptions::MissingHasherError>())?;
+    m.add("DataTypeMismatchError", py.get_type::<exceptions::DataTypeMismatchError>())?;
+    m.add("ClaimCollisionError", py.get_type::<exceptions::ClaimCollisionError>())?;
+    m.add("DuplicateDigestError", py.get_type::<exceptions::DuplicateDigestError>())?;
+    m.add("InvalidArrayDisclosureObjectError", py.get_type::<exceptions::InvalidArrayDisclosureObjectError>())?;
+    m.add("InvalidPathError", py.get_type::<exceptions::InvalidPathError>())?;
+    m.add("DeserializationError", py.get_type::<exceptions::DeserializationError>())?;
+    m.add("UnspecifiedError", py.get_type::<exceptions::UnspecifiedError>())?;
+    m.add("InvalidSaltSizeError", py.get_type::<exceptions::InvalidSaltSizeError>())?;
+    m.add("UnusedDisclosuresError", py.get_type::<exceptions::UnusedDisclosuresError>())?;
+    m.add("AddBatchError", py.get_type::<exceptions::AddBatchError>())?;
+    m.add("WitnessBatchError", py.get_type::<exceptions::WitnessBatchError>())?;
+    m.add("RemoveBatchError", py.get_type::<exceptions::RemoveBatchError>())?;
+    m.add("VerificationFailedError", py.get_type::<exceptions::VerificationFailedError>())?;
+    m.add("JwsError", py.get_type::<exceptions::JwsError>())?;
+    m.add("AlgorithmNotAllowedError", py.get_type::<exceptions::AlgorithmNotAllowedError>())?;
+    m.add("StateIntegrityError", py.get_type::<exceptions::StateIntegrityError>())?;
+    m.add("DidResolutionError", py.get_type::<exceptions::DidResolutionError>())?;
+    m.add("UntrustedIssuerError", py.get_type::<exceptions::UntrustedIssuerError>())?;
+    m.add("ClaimValidationError", py.get_type::<exceptions::ClaimValidationError>())?;
+    m.add("KeyBindingError", py.get_type::<exceptions::KeyBindingError>())?;
+    m.add("UnmetRequirementError", py.get_type::<exceptions::UnmetRequirementError>())?;
+    Ok(())
+}
@@ -1,8 +1,17 @@
 use std::collections::HashSet;
 use std::hash::Hash;
+use std::io::{Read, Write};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
 use vb_accumulator::persistence::{State, UniversalAccumulatorState};
 
+use crate::csd::csd_error::{CsdCoreError, CsdError, CsdResult};
+
+#[derive(Debug, Clone)]
 pub struct InMemoryState<T: Clone> {
     pub db: HashSet<T>,
 }
@@ -14,6 +23,12 @@ impl<T: Clone> InMemoryState<T> {
     }
 }
 
+impl<T: Clone> Default for InMemoryState<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Clone + Hash + Eq + Sized> State<T> for InMemoryState<T> {
     fn add(&mut self, element: T) {
         self.db.insert(element);
@@ -24,7 +39,7 @@ impl<T: Clone + Hash + Eq + Sized> State<T> for InMemoryState<T> {
     }
 
     fn has(&self, element: &T) -> bool {
-        self.db.get(element).is_some()
+        self.db.contains(element)
     }
 
     fn size(&self) -> u64 {
@@ -39,4 +54,240 @@ impl<'a, T: Clone + Hash + Eq + Sized + 'a> UniversalAccumulatorState<'a, T> for
     fn elements(&'a self) -> Self::ElementIterator {
         self.db.iter()
     }
-}
\ No newline at end of file
+}
+
+impl<T: Clone + Ord> InMemoryState<T> {
+    ///
+    /// sorted_elements(&self) -> Vec<&T>
+    ///
+    /// Returns the members of the state in ascending order. The ordering is what makes the
+    /// canonical serialization (and therefore the digest) independent of the insertion order.
+    ///
+    pub fn sorted_elements(&self) -> Vec<&T> {
+        let mut elements: Vec<&T> = self.db.iter().collect();
+        elements.sort();
+        elements
+    }
+}
+
+impl<T: Clone + Ord + CanonicalSerialize> InMemoryState<T> {
+    ///
+    /// digest(&self) -> [u8; 32]
+    ///
+    /// Sha3_256 of the compressed canonical serialization of the state.
+    ///
+    pub fn digest(&self) -> [u8; 32] {
+        let mut compressed_bytes = Vec::new();
+        self.serialize_compressed(&mut compressed_bytes).unwrap();
+
+        let mut hasher = Sha3_256::new();
+        hasher.update(&compressed_bytes);
+        hasher.finalize().into()
+    }
+
+    ///
+    /// snapshot(&self) -> StateSnapshot
+    ///
+    /// Checkpoints the state into a serde-friendly [`StateSnapshot`] carrying the compressed
+    /// canonical serialization together with its digest.
+    ///
+    pub fn snapshot(&self) -> StateSnapshot {
+        let mut compressed_bytes = Vec::new();
+        self.serialize_compressed(&mut compressed_bytes).unwrap();
+
+        StateSnapshot {
+            elements: base64::engine::general_purpose::STANDARD.encode(compressed_bytes),
+            digest: hex::encode(self.digest()),
+        }
+    }
+}
+
+impl<T: Clone + Hash + Eq + Ord + CanonicalSerialize + CanonicalDeserialize> InMemoryState<T> {
+    ///
+    /// restore(snapshot: &StateSnapshot) -> CsdResult<InMemoryState<T>>
+    ///
+    /// Rebuilds a state from a [`StateSnapshot`], rejecting it if the elements can't be decoded or
+    /// if they don't match the digest recorded in the snapshot.
+    ///
+    pub fn restore(snapshot: &StateSnapshot) -> CsdResult<Self> {
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(&snapshot.elements)
+            .map_err(|e| CsdError::Core(CsdCoreError::DeserializationError(e.to_string())))?;
+        let state = Self::deserialize_compressed(&*decoded)
+            .map_err(|e| CsdError::Core(CsdCoreError::DeserializationError(e.to_string())))?;
+
+        let digest = hex::encode(state.digest());
+        if digest != snapshot.digest {
+            return Err(CsdError::StateIntegrity(format!("expected digest {}, found {}", snapshot.digest, digest)));
+        }
+
+        Ok(state)
+    }
+}
+
+impl<T: Clone + Ord + CanonicalSerialize> CanonicalSerialize for InMemoryState<T> {
+    fn serialize_with_mode<W: Write>(&self, mut writer: W, compress: Compress) -> Result<(), SerializationError> {
+        let elements = self.sorted_elements();
+        (elements.len() as u64).serialize_with_mode(&mut writer, compress)?;
+        for element in elements {
+            element.serialize_with_mode(&mut writer, compress)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.db
+            .iter()
+            .fold(0u64.serialized_size(compress), |size, element| size + element.serialized_size(compress))
+    }
+}
+
+impl<T: Clone + Sync> Valid for InMemoryState<T> {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl<T: Clone + Hash + Eq + Ord + CanonicalDeserialize> CanonicalDeserialize for InMemoryState<T> {
+    fn deserialize_with_mode<R: Read>(mut reader: R, compress: Compress, validate: Validate) -> Result<Self, SerializationError> {
+        let len = u64::deserialize_with_mode(&mut reader, compress, validate)?;
+        let mut db = HashSet::<T>::new();
+        let mut previous: Option<T> = None;
+        for _ in 0..len {
+            let element = T::deserialize_with_mode(&mut reader, compress, validate)?;
+            // Canonical encodings are strictly ascending, which also rules out duplicates.
+            if previous.as_ref().is_some_and(|previous| previous >= &element) {
+                return Err(SerializationError::InvalidData);
+            }
+            previous = Some(element.clone());
+            db.insert(element);
+        }
+        Ok(Self { db })
+    }
+}
+
+/// Clonable, thread-safe handle to an [`InMemoryState`].
+///
+/// Every clone refers to the same underlying state, so it can be handed to the accumulator API
+/// from several threads. Single operations are atomic; callers that need several operations to be
+/// observed together (e.g. an accumulator update and its state change) must hold [`SharedState::write`].
+#[derive(Debug, Clone)]
+pub struct SharedState<T: Clone> {
+    inner: Arc<RwLock<InMemoryState<T>>>,
+}
+
+impl<T: Clone> Default for SharedState<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> SharedState<T> {
+    pub fn new() -> Self {
+        Self::from(InMemoryState::new())
+    }
+
+    /// Locks the state for reading.
+    pub fn read(&self) -> RwLockReadGuard<'_, InMemoryState<T>> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the state for writing.
+    pub fn write(&self) -> RwLockWriteGuard<'_, InMemoryState<T>> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Clone> From<InMemoryState<T>> for SharedState<T> {
+    fn from(state: InMemoryState<T>) -> Self {
+        Self { inner: Arc::new(RwLock::new(state)) }
+    }
+}
+
+impl<T: Clone + Hash + Eq + Sized> State<T> for SharedState<T> {
+    fn add(&mut self, element: T) {
+        self.write().add(element);
+    }
+
+    fn remove(&mut self, element: &T) {
+        self.write().remove(element);
+    }
+
+    fn has(&self, element: &T) -> bool {
+        self.read().has(element)
+    }
+
+    fn size(&self) -> u64 {
+        self.read().size()
+    }
+}
+
+/// Serde-friendly checkpoint of an [`InMemoryState`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// The base64-encoded compressed canonical serialization of the state.
+    pub elements: String,
+    /// The hex-encoded sha3_256 digest of the decoded elements.
+    pub digest: String,
+}
+
+#[cfg(test)]
+mod test {
+    use ark_bn254::Bn254;
+    use ark_ec::pairing::Pairing;
+    use ark_serialize::CanonicalSerialize;
+
+    use crate::accumulator::{generate_secret_key, initialize_accumulator, scalar_from_str, verify_state};
+    use crate::csd::csd_error::CsdError;
+
+    use super::InMemoryState;
+
+    type Fr = <Bn254 as Pairing>::ScalarField;
+
+    fn elements() -> Vec<Fr> {
+        (0..10).map(|i| scalar_from_str::<Bn254>(&format!("Claim Key {i}::Claim Value {i}"))).collect()
+    }
+
+    #[test]
+    fn deterministic_serialization() {
+        let mut forward: InMemoryState<Fr> = InMemoryState::new();
+        let mut backward: InMemoryState<Fr> = InMemoryState::new();
+        forward.db.extend(elements());
+        backward.db.extend(elements().into_iter().rev());
+
+        let mut forward_bytes = Vec::new();
+        let mut backward_bytes = Vec::new();
+        forward.serialize_compressed(&mut forward_bytes).unwrap();
+        backward.serialize_compressed(&mut backward_bytes).unwrap();
+        assert_eq!(forward_bytes, backward_bytes);
+        assert_eq!(forward.digest(), backward.digest());
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let mut state: InMemoryState<Fr> = InMemoryState::new();
+        state.db.extend(elements());
+
+        let snapshot = state.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored = InMemoryState::<Fr>::restore(&serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(restored.db, state.db);
+
+        let mut tampered = snapshot.clone();
+        tampered.digest = hex::encode([0u8; 32]);
+        assert!(matches!(InMemoryState::<Fr>::restore(&tampered).unwrap_err(), CsdError::StateIntegrity(_)));
+    }
+
+    #[test]
+    fn verify_against_accumulator() {
+        let (params, keypair, accumulator, mut state) = initialize_accumulator::<Bn254>(&generate_secret_key::<Bn254>(), 1u64);
+        let accumulator = accumulator.add_batch(elements(), &keypair.secret_key, &mut state).unwrap();
+
+        let restored = InMemoryState::<Fr>::restore(&state.snapshot()).unwrap();
+        assert!(verify_state(&restored, &accumulator, &params, &keypair.secret_key));
+
+        let mut shrunk = restored;
+        shrunk.db.remove(&elements()[0]);
+        assert!(!verify_state(&shrunk, &accumulator, &params, &keypair.secret_key));
+    }
+}
@@ -0,0 +1,92 @@
+//!
+//! The verifier for browsers and other JavaScript hosts, built for `wasm32-unknown-unknown`:
+//!
+//! ```js
+//! import init, { Verifier } from "./csd_jwt.js";
+//!
+//! await init();
+//! const verifier = new Verifier(issuerJwk, "bn254");
+//! const claims = verifier.verify(presentation);
+//! ```
+//!
+//! Issuer JWTs are verified with a [`CsdJwkVerifier`] and witnesses sequentially, as there are no
+//! threads. Key bound presentations, DID resolution and the CWT form need the native verifier.
+//!
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::csd;
+use csd::csd_compact::{from_compact_text, COMPACT_TEXT_PREFIX};
+use csd::csd_decoder::CsdDecoder;
+use csd::csd_disclosure::CsdDisclosure;
+use csd::csd_error::{CsdError, CsdResult};
+use csd::csd_jwk_verifier::CsdJwkVerifier;
+use csd::csd_jwt::CsdJwt;
+
+/// The decoder on the curve of the accumulator, named `"bn254"` or `"bls12381"` in JavaScript.
+enum Decoder {
+    Bn254(CsdDecoder<'static, Bn254>),
+    Bls12381(CsdDecoder<'static, Bls12_381>),
+}
+
+/// Verifies presentations of one issuer against its public JWK.
+#[wasm_bindgen]
+pub struct Verifier {
+    verifier: CsdJwkVerifier,
+    decoder: Decoder,
+}
+
+#[wasm_bindgen]
+impl Verifier {
+    /// Reads the issuer's public `jwk`, a JSON string, for accumulators on `curve`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(jwk: &str, curve: Option<String>) -> Result<Verifier, JsError> {
+        let jwk: Map<String, Value> = serde_json::from_str(jwk)
+            .map_err(|err| CsdError::Jws(format!("the JWK is not a JSON object: {err}")))?;
+        let decoder = match curve.as_deref().unwrap_or("bn254") {
+            "bn254" => Decoder::Bn254(CsdDecoder::new()),
+            "bls12381" => Decoder::Bls12381(CsdDecoder::new()),
+            curve => return Err(JsError::new(&format!("unknown curve {curve}, expected bn254 or bls12381"))),
+        };
+        Ok(Verifier { verifier: CsdJwkVerifier::from_jwk(&jwk)?, decoder })
+    }
+
+    /// Restricts the accepted JWS algorithms.
+    #[wasm_bindgen(js_name = setAllowedAlgorithms)]
+    pub fn set_allowed_algorithms(&mut self, allowed_algorithms: Vec<String>) {
+        match &mut self.decoder {
+            Decoder::Bn254(decoder) => decoder.set_allowed_algorithms(allowed_algorithms),
+            Decoder::Bls12381(decoder) => decoder.set_allowed_algorithms(allowed_algorithms),
+        }
+    }
+
+    /// Verifies `presentation`, in its SD-JWT or compact text form, and returns the disclosed
+    /// claims as an object.
+    pub fn verify(&self, presentation: &str) -> Result<JsValue, JsError> {
+        let claims = match &self.decoder {
+            Decoder::Bn254(decoder) => verify_with(decoder, &self.verifier, presentation)?,
+            Decoder::Bls12381(decoder) => verify_with(decoder, &self.verifier, presentation)?,
+        };
+        let json = Value::Object(claims).to_string();
+        js_sys::JSON::parse(&json).map_err(|_| JsError::new("the claims are not valid JSON"))
+    }
+}
+
+fn verify_with<C: Pairing>(decoder: &CsdDecoder<C>, verifier: &CsdJwkVerifier, presentation: &str) -> CsdResult<Map<String, Value>> {
+    let csd_jwt = match presentation.trim().starts_with(COMPACT_TEXT_PREFIX) {
+        true => from_compact_text(presentation)?,
+        false => CsdJwt::parse(presentation)?,
+    };
+    if csd_jwt.key_binding_jwt.is_some() {
+        return Err(CsdError::KeyBinding(String::from("key bound presentations need the native verifier")));
+    }
+    let (object, _header) = decoder.decode_jwt_with_jwk(&csd_jwt, verifier)?;
+    let disclosures = csd_jwt.disclosures
+        .iter()
+        .map(|disclosure| CsdDisclosure::parse(disclosure.to_owned()))
+        .collect::<Result<Vec<CsdDisclosure>, _>>()?;
+    decoder.verify_disclosures(&object, disclosures)
+}
@@ -0,0 +1,33 @@
+#![cfg(not(target_arch = "wasm32"))]
+//!
+//! Runs the `bench` subcommand of the binary on a tiny grid.
+//!
+use std::process::Command;
+
+fn bench(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_CSD-JWT")).arg("bench").args(args).output().unwrap()
+}
+
+#[test]
+fn csv_smoke() {
+    let output = bench(&["--claims", "2", "--ratios", "1.0", "--curves", "bn254", "--threads", "1", "--iterations", "1"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let csv = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "scheme,curve,claims,disclosed,threads,issuance_us,presentation_us,verification_us,credential_bytes,presentation_bytes"
+    );
+    // One CSD-JWT row for the single curve and thread count, one SD-JWT row.
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("csd-jwt,bn254,2,2,1,"), "{}", lines[1]);
+    assert!(lines[2].starts_with("sd-jwt,-,2,2,1,"), "{}", lines[2]);
+}
+
+#[test]
+fn rejects_out_of_range_arguments() {
+    assert!(!bench(&["--iterations", "0"]).status.success());
+    assert!(!bench(&["--ratios", "0.5,1.5"]).status.success());
+    assert!(!bench(&["--ratios=-0.1"]).status.success());
+}
@@ -0,0 +1,102 @@
+/*
+ * Issues, presents and verifies a credential through the C API, as a wallet core would.
+ * Built and run by tests/c_api.rs.
+ */
+#include <stdio.h>
+#include <string.h>
+
+#include "csd_jwt.h"
+
+#define CHECK(call)                                                                         \
+    do {                                                                                    \
+        CsdErrorCode code = (call);                                                         \
+        if (code != CSD_ERROR_CODE_OK) {                                                    \
+            fprintf(stderr, "%s:%d: %s failed with %d: %s\n", __FILE__, __LINE__, #call,     \
+                    (int) code, csd_last_error_message());                                  \
+            return 1;                                                                       \
+        }                                                                                   \
+    } while (0)
+
+#define EXPECT(condition)                                                                   \
+    do {                                                                                    \
+        if (!(condition)) {                                                                 \
+            fprintf(stderr, "%s:%d: expected %s\n", __FILE__, __LINE__, #condition);         \
+            return 1;                                                                       \
+        }                                                                                   \
+    } while (0)
+
+int main(void) {
+    CsdIssuerKey *key = NULL;
+    CHECK(csd_issuer_key_generate("ES256", CSD_CURVE_BN254, &key));
+
+    /* The verifier only gets the public key. */
+    char *public_jwk = NULL;
+    CsdIssuerKey *public_key = NULL;
+    CHECK(csd_issuer_key_public_jwk(key, &public_jwk));
+    EXPECT(strstr(public_jwk, "\"d\"") == NULL);
+    EXPECT(strstr(public_jwk, "\"pk\"") != NULL);
+    EXPECT(strstr(public_jwk, "\"sk\"") == NULL);
+    CHECK(csd_issuer_key_from_jwk(public_jwk, CSD_CURVE_BN254, &public_key));
+    csd_string_free(public_jwk);
+
+    /* The accumulator key is stored with the JWS key, so a reloaded key issues the same way. */
+    char *private_jwk = NULL;
+    CsdIssuerKey *reloaded = NULL;
+    CHECK(csd_issuer_key_jwk(key, &private_jwk));
+    EXPECT(strstr(private_jwk, "\"sk\"") != NULL);
+    CHECK(csd_issuer_key_from_jwk(private_jwk, CSD_CURVE_BN254, &reloaded));
+    csd_string_free(private_jwk);
+
+    CsdCredential *credential = NULL;
+    CHECK(csd_issue(reloaded, "{\"name\": \"Alice\", \"age\": 30, \"country\": \"DE\"}", &credential));
+
+    /* The credential survives serialization, as it would in the wallet's storage. */
+    char *serialized = NULL;
+    CsdCredential *stored = NULL;
+    CHECK(csd_credential_serialize(credential, &serialized));
+    CHECK(csd_credential_parse(serialized, &stored));
+    csd_string_free(serialized);
+
+    const char *pointers[] = {"/name", "/country"};
+    CsdPresentation *presentation = NULL;
+    CHECK(csd_derive_presentation(stored, pointers, 2, &presentation));
+
+    char *sent = NULL;
+    CsdPresentation *received = NULL;
+    CHECK(csd_presentation_serialize(presentation, &sent));
+    CHECK(csd_presentation_parse(sent, &received));
+    csd_string_free(sent);
+
+    char *claims = NULL;
+    CHECK(csd_verify(public_key, received, &claims));
+    EXPECT(strstr(claims, "\"name\":\"Alice\"") != NULL);
+    EXPECT(strstr(claims, "\"country\":\"DE\"") != NULL);
+    EXPECT(strstr(claims, "age") == NULL);
+    csd_string_free(claims);
+
+    /* Errors come back as codes, with a message. */
+    CsdIssuerKey *other_key = NULL;
+    CHECK(csd_issuer_key_generate("ES256", CSD_CURVE_BN254, &other_key));
+    claims = NULL;
+    EXPECT(csd_verify(other_key, received, &claims) == CSD_ERROR_CODE_JWS);
+    EXPECT(claims == NULL);
+    EXPECT(strlen(csd_last_error_message()) > 0);
+
+    const char *unknown[] = {"/email"};
+    CsdPresentation *invalid = NULL;
+    EXPECT(csd_derive_presentation(stored, unknown, 1, &invalid) == CSD_ERROR_CODE_INVALID_PATH);
+    EXPECT(csd_issuer_key_generate("HS256", CSD_CURVE_BN254, &other_key) == CSD_ERROR_CODE_ALGORITHM_NOT_ALLOWED);
+    EXPECT(csd_issue(public_key, "{\"name\": \"Alice\"}", &credential) == CSD_ERROR_CODE_JWS);
+    EXPECT(csd_issue(key, NULL, &credential) == CSD_ERROR_CODE_NULL_POINTER);
+    EXPECT(csd_presentation_parse("not a presentation", &invalid) != CSD_ERROR_CODE_OK);
+
+    csd_presentation_free(received);
+    csd_presentation_free(presentation);
+    csd_credential_free(stored);
+    csd_credential_free(credential);
+    csd_issuer_key_free(other_key);
+    csd_issuer_key_free(public_key);
+    csd_issuer_key_free(reloaded);
+    csd_issuer_key_free(key);
+    return 0;
+}
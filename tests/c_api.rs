@@ -0,0 +1,63 @@
+#![cfg(not(target_arch = "wasm32"))]
+//!
+//! Checks the generated C header and runs `tests/c/csd_api.c` against the `cdylib`.
+//!
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const HEADER: &str = "include/csd_jwt.h";
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// The header must match what cbindgen generates from `src/ffi.rs`. Set `CSD_JWT_UPDATE_HEADER`
+/// to regenerate it.
+#[test]
+fn header_is_up_to_date() {
+    let config = cbindgen::Config::from_file(manifest_dir().join("cbindgen.toml")).unwrap();
+    let mut generated = vec![];
+    cbindgen::generate_with_config(manifest_dir(), config).unwrap().write(&mut generated);
+    let generated = String::from_utf8(generated).unwrap();
+
+    let path = manifest_dir().join(HEADER);
+    if std::env::var_os("CSD_JWT_UPDATE_HEADER").is_some() {
+        std::fs::write(&path, &generated).unwrap();
+    }
+    let shipped = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(shipped == generated, "{HEADER} is out of date, regenerate it with CSD_JWT_UPDATE_HEADER=1");
+}
+
+#[cfg(unix)]
+#[test]
+fn c_integration() {
+    // Cargo only links the rlib into tests, so the cdylib is built here, into target/<profile>
+    // next to the deps directory the test runs from.
+    let mut build = Command::new(env!("CARGO"));
+    build.current_dir(manifest_dir()).args(["build", "--lib"]);
+    if !cfg!(debug_assertions) {
+        build.arg("--release");
+    }
+    assert!(build.status().unwrap().success());
+    let exe = std::env::current_exe().unwrap();
+    let lib_dir = exe.parent().and_then(Path::parent).unwrap();
+    let binary = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("csd_api");
+
+    let compiled = Command::new(std::env::var("CC").unwrap_or(String::from("cc")))
+        .arg(manifest_dir().join("tests/c/csd_api.c"))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg(format!("-I{}", manifest_dir().join("include").display()))
+        .arg(format!("-L{}", lib_dir.display()))
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .arg("-lcsd_jwt")
+        .arg("-o")
+        .arg(&binary)
+        .status()
+        .unwrap();
+    assert!(compiled.success());
+
+    let output = Command::new(&binary).output().unwrap();
+    assert!(output.status.success(), "{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+}
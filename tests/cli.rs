@@ -0,0 +1,74 @@
+#![cfg(not(target_arch = "wasm32"))]
+//!
+//! Runs the binary through keygen, issue, present, verify and inspect.
+//!
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use serde_json::{json, Value};
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_CSD-JWT")).current_dir(dir).args(args).output().unwrap()
+}
+
+/// Runs a subcommand which must succeed and returns its stdout.
+fn success(dir: &Path, args: &[&str]) -> String {
+    let output = run(dir, args);
+    assert!(output.status.success(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn work_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("claims.json"), json!({"name": "Alice", "age": 30}).to_string()).unwrap();
+    dir
+}
+
+#[test]
+fn keygen_issue_present_verify() {
+    let dir = work_dir("cli");
+    success(&dir, &["keygen", "--out", "issuer.jwk", "--public-out", "issuer.pub.jwk"]);
+    success(&dir, &["issue", "--key", "issuer.jwk", "--claims", "claims.json", "--out", "credential.txt"]);
+    success(&dir, &["present", "--credential", "credential.txt", "-p", "/age", "--out", "presentation.txt"]);
+
+    let claims: Value = serde_json::from_str(&success(&dir, &["verify", "--key", "issuer.pub.jwk", "--presentation", "presentation.txt"])).unwrap();
+    assert_eq!(claims, json!({"age": 30}));
+
+    let token: Value = serde_json::from_str(&success(&dir, &["inspect", "presentation.txt"])).unwrap();
+    assert_eq!(token["disclosures"].as_array().unwrap().len(), 1);
+    assert_eq!(token["disclosures"][0]["name"], "age");
+    assert!(token["payload"]["pk"].is_string());
+
+    // The issuer's JWS key along with another accumulator key.
+    success(&dir, &["keygen", "--out", "other.jwk", "--public-out", "other.pub.jwk"]);
+    let mut public = read_json(&dir.join("issuer.pub.jwk"));
+    public["csd_accumulator"] = read_json(&dir.join("other.pub.jwk"))["csd_accumulator"].take();
+    std::fs::write(dir.join("swapped.pub.jwk"), public.to_string()).unwrap();
+    assert!(!run(&dir, &["verify", "--key", "swapped.pub.jwk", "--presentation", "presentation.txt"]).status.success());
+}
+
+#[test]
+fn issuer_did_with_key() {
+    let dir = work_dir("cli_iss");
+    success(&dir, &["keygen", "--out", "issuer.jwk", "--public-out", "issuer.pub.jwk"]);
+    for (credential, extra) in [("credential.txt", None), ("omitted.txt", Some("--omit-pk"))] {
+        let mut args = vec!["issue", "--key", "issuer.jwk", "--claims", "claims.json", "--iss", "did:example:issuer", "--out", credential];
+        args.extend(extra);
+        success(&dir, &args);
+        let presentation = format!("presentation-{}", credential);
+        success(&dir, &["present", "--credential", credential, "-p", "/name", "--out", &presentation]);
+
+        let claims: Value = serde_json::from_str(&success(&dir, &["verify", "--key", "issuer.pub.jwk", "--presentation", &presentation])).unwrap();
+        assert_eq!(claims, json!({"name": "Alice"}));
+        let token: Value = serde_json::from_str(&success(&dir, &["inspect", &presentation])).unwrap();
+        assert_eq!(token["payload"]["iss"], "did:example:issuer");
+        assert_eq!(token["payload"]["pk"].is_string(), extra.is_none());
+    }
+    assert!(!run(&dir, &["issue", "--key", "issuer.jwk", "--claims", "claims.json", "--omit-pk"]).status.success());
+}
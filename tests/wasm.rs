@@ -0,0 +1,46 @@
+//!
+//! Verifies presentations issued by the native command line in the wasm verifier, run headless
+//! in Node.js with `cargo test --target wasm32-unknown-unknown --test wasm`.
+//!
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+use csd_jwt::wasm::Verifier;
+
+const ISSUER_JWK: &str = include_str!("wasm/issuer_jwk.json");
+const OTHER_JWK: &str = include_str!("wasm/other_jwk.json");
+const PRESENTATION: &str = include_str!("wasm/presentation.txt");
+const PRESENTATION_QR: &str = include_str!("wasm/presentation_qr.txt");
+const PRESENTATION_NESTED: &str = include_str!("wasm/presentation_nested.txt");
+
+fn claims(value: JsValue) -> String {
+    js_sys::JSON::stringify(&value).unwrap().into()
+}
+
+#[wasm_bindgen_test]
+fn verify() {
+    let verifier = Verifier::new(ISSUER_JWK, None).unwrap();
+    assert_eq!(claims(verifier.verify(PRESENTATION.trim()).unwrap()), r#"{"country":"DE","name":"Alice"}"#);
+    assert_eq!(claims(verifier.verify(PRESENTATION_QR).unwrap()), r#"{"name":"Alice"}"#);
+
+    let mut tampered = String::from(PRESENTATION.trim());
+    tampered.insert(tampered.find('~').unwrap(), 'A');
+    assert!(verifier.verify(&tampered).is_err());
+    assert!(Verifier::new(OTHER_JWK, None).unwrap().verify(PRESENTATION.trim()).is_err());
+    assert!(Verifier::new(ISSUER_JWK, Some(String::from("bls12381"))).unwrap().verify(PRESENTATION.trim()).is_err());
+
+    let mut restricted = Verifier::new(ISSUER_JWK, None).unwrap();
+    restricted.set_allowed_algorithms(vec![String::from("EdDSA")]);
+    assert!(restricted.verify(PRESENTATION.trim()).is_err());
+}
+
+#[wasm_bindgen_test]
+fn verify_nested() {
+    // Issued natively, where nested objects keep their keys in the order they were hashed in.
+    let verifier = Verifier::new(ISSUER_JWK, None).unwrap();
+    assert_eq!(
+        claims(verifier.verify(PRESENTATION_NESTED.trim()).unwrap()),
+        r#"{"address":{"city":"Berlin","street":"Main St","zip":"10115"},"name":"Alice"}"#
+    );
+}